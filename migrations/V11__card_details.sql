@@ -0,0 +1,5 @@
+-- Accounts, created before, get their card details on reissue
+ALTER TABLE accounts
+ADD COLUMN card_expiry_month SMALLINT CHECK (card_expiry_month BETWEEN 1 AND 12),
+ADD COLUMN card_expiry_year SMALLINT,
+ADD COLUMN cvv_hash TEXT;
@@ -0,0 +1,6 @@
+ALTER TABLE tokens
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX tokens_account_idx ON tokens(account);
+CREATE INDEX tokens_token_idx ON tokens(token);
@@ -0,0 +1,26 @@
+CREATE TABLE transfer_orders (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sender INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    recipient INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    -- Either one-time run or cron schedule
+    run_at TIMESTAMP WITH TIME ZONE,
+    cron VARCHAR(100),
+    next_run_at TIMESTAMP WITH TIME ZONE,
+    cancelled_at TIMESTAMP WITH TIME ZONE,
+    CHECK ((run_at IS NULL) <> (cron IS NULL))
+);
+
+CREATE INDEX transfer_orders_next_run_idx ON transfer_orders(next_run_at);
+
+CREATE TABLE transfer_order_executions (
+    id SERIAL PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES transfer_orders(id) ON DELETE CASCADE,
+    executed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    transaction_id UUID,
+    error TEXT
+);
+
+CREATE INDEX transfer_order_executions_order_idx
+ON transfer_order_executions(order_id);
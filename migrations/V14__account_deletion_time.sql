@@ -0,0 +1,3 @@
+-- Accounts deleted before this migration have no deletion time
+ALTER TABLE accounts
+ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,4 @@
+-- Acquiring fees are stored as separate transactions
+-- to the bank revenue account
+ALTER TABLE transactions
+ADD COLUMN kind VARCHAR(20) NOT NULL DEFAULT 'transfer';
@@ -0,0 +1,77 @@
+ALTER TABLE holds
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE holds SET expires_at = created_at + INTERVAL '1 hour';
+
+ALTER TABLE holds
+ALTER COLUMN expires_at SET NOT NULL;
+
+-- Expired holds don't reserve funds
+CREATE OR REPLACE FUNCTION check_balance_before_transaction()
+RETURNS TRIGGER AS $$
+DECLARE
+    balance BIGINT;
+    held BIGINT;
+    overdraft_limit BIGINT;
+    sender_exists BOOLEAN;
+    recipient_exists BOOLEAN;
+    sender_status VARCHAR(16);
+    recipient_status VARCHAR(16);
+BEGIN
+    -- Concurrent debits wait for each other here, both accounts are
+    -- locked in the same order to avoid deadlocks
+    PERFORM 1
+    FROM accounts
+    WHERE id IN (NEW.sender, NEW.recipient)
+    ORDER BY id
+    FOR UPDATE;
+
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.sender AND is_existing = TRUE) INTO sender_exists;
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.recipient AND is_existing = TRUE) INTO recipient_exists;
+
+    IF NOT sender_exists OR NOT recipient_exists THEN
+        RAISE EXCEPTION 'Sender or recipient account does not exist or is not active';
+    END IF;
+
+    SELECT status INTO sender_status FROM accounts WHERE id = NEW.sender;
+    SELECT status INTO recipient_status FROM accounts WHERE id = NEW.recipient;
+
+    IF sender_status = 'blocked' OR recipient_status = 'blocked' THEN
+        RAISE EXCEPTION 'Account is blocked';
+    END IF;
+
+    IF sender_status = 'frozen' THEN
+        RAISE EXCEPTION 'Account is frozen';
+    END IF;
+
+    IF NEW.sender = 1 THEN
+        RETURN NEW;
+    END IF;
+
+    IF NEW.sender = NEW.recipient THEN
+        RAISE EXCEPTION 'Sender and recipient cannot be the same';
+    END IF;
+
+    IF NEW.amount <= 0 THEN
+        RAISE EXCEPTION 'Amount must be greater than 0';
+    END IF;
+
+    SELECT accounts.balance, accounts.overdraft_limit
+    INTO balance, overdraft_limit
+    FROM accounts
+    WHERE id = NEW.sender;
+
+    SELECT COALESCE(SUM(amount), 0) INTO held
+    FROM holds
+    WHERE account = NEW.sender AND expires_at > CURRENT_TIMESTAMP;
+
+    IF balance - held + overdraft_limit < NEW.amount THEN
+        IF overdraft_limit > 0 THEN
+            RAISE EXCEPTION 'Overdraft limit exceeded';
+        END IF;
+        RAISE EXCEPTION 'Not enough funds';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,6 @@
+-- System accounts, like the bank revenue account, are marked explicitly,
+-- because their usernames could be taken by customers
+CREATE TABLE system_accounts (
+    role VARCHAR(20) PRIMARY KEY,
+    account INTEGER NOT NULL UNIQUE REFERENCES accounts(id) ON DELETE RESTRICT
+);
@@ -0,0 +1,4 @@
+-- Tokens are bound to the terminal, which registered them,
+-- tokens created before this migration are not bound
+ALTER TABLE tokens
+ADD COLUMN terminal_key UUID;
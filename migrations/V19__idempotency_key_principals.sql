@@ -0,0 +1,3 @@
+-- Keys are prefixed with the terminal or system user, which sent the request
+ALTER TABLE idempotency_keys
+ALTER COLUMN idempotency_key TYPE TEXT;
@@ -0,0 +1,7 @@
+-- Store account of each merchant terminal
+CREATE TABLE terminals (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    terminal_key UUID NOT NULL UNIQUE,
+    account INTEGER NOT NULL UNIQUE REFERENCES accounts(id) ON DELETE RESTRICT
+);
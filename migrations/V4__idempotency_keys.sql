@@ -0,0 +1,9 @@
+-- Results of requests performed with `Idempotency-Key` header
+CREATE TABLE idempotency_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    request_hash VARCHAR(64) NOT NULL,
+    response_status SMALLINT,
+    response_content_type VARCHAR(255),
+    response_body BYTEA
+);
@@ -0,0 +1,2 @@
+ALTER TABLE transactions
+ADD COLUMN transaction_id UUID NOT NULL UNIQUE DEFAULT gen_random_uuid();
@@ -0,0 +1,79 @@
+ALTER TABLE accounts
+ADD COLUMN balance BIGINT NOT NULL DEFAULT 0;
+
+-- Backfill balances from existing transactions
+WITH received_amount AS (
+    SELECT recipient, COALESCE(SUM(amount), 0) AS received_total
+    FROM transactions
+    GROUP BY recipient
+),
+spent_amount AS (
+    SELECT sender, COALESCE(SUM(amount), 0) AS spent_total
+    FROM transactions
+    GROUP BY sender
+)
+UPDATE accounts a
+SET balance = COALESCE(ra.received_total, 0) - COALESCE(sa.spent_total, 0)
+FROM accounts acc
+LEFT JOIN received_amount ra ON acc.id = ra.recipient
+LEFT JOIN spent_amount sa ON acc.id = sa.sender
+WHERE a.id = acc.id;
+
+CREATE INDEX holds_account_idx ON holds(account);
+
+-- Check balance before transaction, held funds are not available
+CREATE OR REPLACE FUNCTION check_balance_before_transaction()
+RETURNS TRIGGER AS $$
+DECLARE
+    balance BIGINT;
+    held BIGINT;
+    sender_exists BOOLEAN;
+    recipient_exists BOOLEAN;
+BEGIN
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.sender AND is_existing = TRUE) INTO sender_exists;
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.recipient AND is_existing = TRUE) INTO recipient_exists;
+
+    IF NOT sender_exists OR NOT recipient_exists THEN
+        RAISE EXCEPTION 'Sender or recipient account does not exist or is not active';
+    END IF;
+
+    IF NEW.sender = 1 THEN
+        RETURN NEW;
+    END IF;
+
+    IF NEW.sender = NEW.recipient THEN
+        RAISE EXCEPTION 'Sender and recipient cannot be the same';
+    END IF;
+
+    IF NEW.amount <= 0 THEN
+        RAISE EXCEPTION 'Amount must be greater than 0';
+    END IF;
+
+    SELECT accounts.balance INTO balance
+    FROM accounts
+    WHERE id = NEW.sender;
+
+    SELECT COALESCE(SUM(amount), 0) INTO held
+    FROM holds
+    WHERE account = NEW.sender;
+
+    IF balance - held < NEW.amount THEN
+        RAISE EXCEPTION 'Not enough funds';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Keep balances up to date in the same db transaction as the insert
+CREATE OR REPLACE FUNCTION update_balances_after_transaction()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE accounts SET balance = balance - NEW.amount WHERE id = NEW.sender;
+    UPDATE accounts SET balance = balance + NEW.amount WHERE id = NEW.recipient;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+CREATE TRIGGER trg_update_balances_after_transaction
+AFTER INSERT ON transactions
+FOR EACH ROW EXECUTE FUNCTION update_balances_after_transaction();
@@ -0,0 +1,51 @@
+-- Check balance before transaction under the accounts row locks
+CREATE OR REPLACE FUNCTION check_balance_before_transaction()
+RETURNS TRIGGER AS $$
+DECLARE
+    balance BIGINT;
+    held BIGINT;
+    sender_exists BOOLEAN;
+    recipient_exists BOOLEAN;
+BEGIN
+    -- Concurrent debits wait for each other here, both accounts are
+    -- locked in the same order to avoid deadlocks
+    PERFORM 1
+    FROM accounts
+    WHERE id IN (NEW.sender, NEW.recipient)
+    ORDER BY id
+    FOR UPDATE;
+
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.sender AND is_existing = TRUE) INTO sender_exists;
+    SELECT EXISTS(SELECT 1 FROM accounts WHERE id = NEW.recipient AND is_existing = TRUE) INTO recipient_exists;
+
+    IF NOT sender_exists OR NOT recipient_exists THEN
+        RAISE EXCEPTION 'Sender or recipient account does not exist or is not active';
+    END IF;
+
+    IF NEW.sender = 1 THEN
+        RETURN NEW;
+    END IF;
+
+    IF NEW.sender = NEW.recipient THEN
+        RAISE EXCEPTION 'Sender and recipient cannot be the same';
+    END IF;
+
+    IF NEW.amount <= 0 THEN
+        RAISE EXCEPTION 'Amount must be greater than 0';
+    END IF;
+
+    SELECT accounts.balance INTO balance
+    FROM accounts
+    WHERE id = NEW.sender;
+
+    SELECT COALESCE(SUM(amount), 0) INTO held
+    FROM holds
+    WHERE account = NEW.sender;
+
+    IF balance - held < NEW.amount THEN
+        RAISE EXCEPTION 'Not enough funds';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,10 @@
+CREATE TABLE loans (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    account INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    principal BIGINT NOT NULL CHECK (principal > 0),
+    interest_rate NUMERIC NOT NULL CHECK (interest_rate >= 0),
+    term INTEGER NOT NULL CHECK (term > 0),
+    period_secs BIGINT NOT NULL CHECK (period_secs > 0),
+    repaid BIGINT NOT NULL DEFAULT 0
+);
@@ -2,15 +2,49 @@
 SELECT COUNT(*)
 FROM accounts;
 
---! get_emission_account
+--! get_emission_account : (status_reason?, status_changed_at?, card_expiry_month?, card_expiry_year?, cvv_hash?, deleted_at?)
 SELECT *
 FROM accounts
 WHERE accounts.id = 1;
 
---! get_store_account
+--! get_legacy_store_account : (status_reason?, status_changed_at?, card_expiry_month?, card_expiry_year?, cvv_hash?, deleted_at?)
 SELECT *
 FROM accounts
-WHERE accounts.id = 2;
+WHERE accounts.id = 2
+    AND NOT EXISTS (SELECT 1 FROM terminals WHERE terminals.account = 2);
+
+--! get_terminal_store_account
+SELECT
+    a.username,
+    a.card_number,
+    a.is_existing
+FROM terminals
+JOIN accounts a ON terminals.account = a.id
+WHERE terminals.terminal_key = :terminal_key;
+
+--! insert_terminal
+INSERT INTO terminals(terminal_key, account)
+VALUES (
+    :terminal_key,
+    (
+        SELECT id FROM accounts WHERE card_number = :card_number
+    )
+);
+
+--! is_store_account
+SELECT EXISTS (
+    SELECT 1
+    FROM terminals
+    JOIN accounts a ON terminals.account = a.id
+    WHERE a.card_number = :card_number
+);
+
+--! lock_accounts
+SELECT id
+FROM accounts
+WHERE card_number = ANY(:card_numbers)
+ORDER BY id
+FOR UPDATE;
 
 --! insert_account
 INSERT INTO accounts(username, card_number, password_hash)
@@ -22,18 +56,47 @@ WHERE card_number = :card_number;
 
 --! mark_account_as_deleted
 UPDATE accounts
-SET is_existing = FALSE
+SET is_existing = FALSE,
+    deleted_at = CURRENT_TIMESTAMP
+WHERE card_number = :card_number;
+
+--! mark_account_as_restored
+UPDATE accounts
+SET is_existing = TRUE,
+    deleted_at = NULL
+WHERE card_number = :card_number;
+
+--! get_account_status
+SELECT status
+FROM accounts
+WHERE card_number = :card_number;
+
+--! set_account_status (status_reason?)
+UPDATE accounts
+SET status = :status,
+    status_reason = :status_reason,
+    status_changed_at = CURRENT_TIMESTAMP
 WHERE card_number = :card_number;
 
---! get_account
+--! get_account : (card_expiry_month?, card_expiry_year?, cvv_hash?)
 SELECT 
     accounts.username,
     accounts.card_number,
     accounts.is_existing,
-    accounts.password_hash
+    accounts.password_hash,
+    accounts.card_expiry_month,
+    accounts.card_expiry_year,
+    accounts.cvv_hash
 FROM accounts
 WHERE card_number = :card_number;
 
+--! set_card_details
+UPDATE accounts
+SET card_expiry_month = :card_expiry_month,
+    card_expiry_year = :card_expiry_year,
+    cvv_hash = :cvv_hash
+WHERE card_number = :card_number;
+
 --! get_account_by_token
 SELECT 
     a.username,
@@ -43,26 +106,62 @@ FROM tokens
 LEFT JOIN accounts a ON tokens.account = a.id
 WHERE  tokens.token = :token;
 
+--! get_token : (expires_at?, revoked_at?, terminal_key?)
+SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+WHERE tokens.token = :token;
+
+--! list_account_tokens : (expires_at?, revoked_at?, terminal_key?)
+SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+WHERE a.card_number = :card_number
+ORDER BY tokens.created_at, tokens.id;
+
+--! revoke_token
+UPDATE tokens
+SET revoked_at = CURRENT_TIMESTAMP
+WHERE token = :token AND revoked_at IS NULL;
+
 --! get_account_balance
-WITH received_amount AS (
-    SELECT recipient, COALESCE(SUM(amount), 0) AS received_total
-    FROM transactions
-    GROUP BY recipient
-),
-spent_amount AS (
-    SELECT sender, COALESCE(SUM(amount), 0) AS spent_total
-    FROM transactions
-    GROUP BY sender
-)
-SELECT COALESCE(ra.received_total, 0) - COALESCE(sa.spent_total, 0) AS balance
-FROM accounts a
-LEFT JOIN received_amount ra ON a.id = ra.recipient
-LEFT JOIN spent_amount sa ON a.id = sa.sender
-WHERE a.card_number = :card_number;
+SELECT balance
+FROM accounts
+WHERE card_number = :card_number;
+
+--! get_account_overdraft_limit
+SELECT overdraft_limit
+FROM accounts
+WHERE card_number = :card_number;
+
+--! set_account_overdraft_limit
+UPDATE accounts
+SET overdraft_limit = :overdraft_limit
+WHERE card_number = :card_number;
+
+--! get_account_held_amount
+SELECT COALESCE(SUM(holds.amount), 0) AS held
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE a.card_number = :card_number AND holds.expires_at > CURRENT_TIMESTAMP;
 
 --! list_account_transactions
 SELECT 
+    t.transaction_id,
     t.amount,
+    t.kind,
     t.created_at,
     sender_account.username AS sender_username,
     sender_account.card_number AS sender_card_number,
@@ -75,33 +174,111 @@ LEFT JOIN accounts sender_account ON t.sender = sender_account.id
 LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
 WHERE sender_account.card_number = :card_number OR recipient_account.card_number = :card_number;
 
+--! get_transaction
+SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+LEFT JOIN accounts sender_account ON t.sender = sender_account.id
+LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE t.transaction_id = :transaction_id;
 
---! get_accounts : (tokens[?])
-WITH received_amount AS (
-    SELECT recipient, COALESCE(SUM(amount), 0) AS received_total
-    FROM transactions
-    GROUP BY recipient
-),
-spent_amount AS (
-    SELECT sender, COALESCE(SUM(amount), 0) AS spent_total
-    FROM transactions
-    GROUP BY sender
+--! list_transactions (last_id?)
+SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+LEFT JOIN accounts sender_account ON t.sender = sender_account.id
+LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE t.id > COALESCE(
+    (SELECT id FROM transactions WHERE transaction_id = :last_id),
+    0
 )
+ORDER BY t.id;
+
+--! account_statement (cursor?, date_from?, date_to?, direction?, counterparty?, min_amount?, max_amount?)
+SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+JOIN accounts sender_account ON t.sender = sender_account.id
+JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE (sender_account.card_number = :card_number OR recipient_account.card_number = :card_number)
+    AND (
+        :cursor::UUID IS NULL
+        OR (t.created_at, t.id) > (
+            SELECT created_at, id FROM transactions WHERE transaction_id = :cursor
+        )
+    )
+    AND (:date_from::TIMESTAMPTZ IS NULL OR t.created_at >= :date_from)
+    AND (:date_to::TIMESTAMPTZ IS NULL OR t.created_at < :date_to)
+    AND (
+        :direction::TEXT IS NULL
+        OR (:direction = 'incoming' AND recipient_account.card_number = :card_number)
+        OR (:direction = 'outgoing' AND sender_account.card_number = :card_number)
+    )
+    AND (
+        :counterparty::TEXT IS NULL
+        OR (sender_account.card_number = :card_number AND recipient_account.card_number = :counterparty)
+        OR (recipient_account.card_number = :card_number AND sender_account.card_number = :counterparty)
+    )
+    AND (:min_amount::BIGINT IS NULL OR t.amount >= :min_amount)
+    AND (:max_amount::BIGINT IS NULL OR t.amount <= :max_amount)
+ORDER BY t.created_at, t.id
+LIMIT :limit;
+
+--! is_transaction_exists
+SELECT EXISTS (
+    SELECT 1 FROM transactions WHERE transaction_id = :transaction_id
+);
+
+
+--! get_accounts : (status_reason?, tokens[?])
 SELECT
     a.username,
     a.card_number,
     a.is_existing,
-    COALESCE(ra.received_total, 0) - COALESCE(sa.spent_total, 0) AS balance,
+    a.balance,
+    a.overdraft_limit,
+    a.status,
+    a.status_reason,
     ARRAY_AGG(t.token) AS tokens
 FROM accounts a
-LEFT JOIN received_amount ra ON a.id = ra.recipient
-LEFT JOIN spent_amount sa ON a.id = sa.sender
 LEFT JOIN tokens t ON a.id = t.account
-GROUP BY a.username, a.card_number, a.is_existing, ra.received_total, sa.spent_total;
+    AND t.revoked_at IS NULL
+    AND (t.expires_at IS NULL OR t.expires_at > CURRENT_TIMESTAMP)
+WHERE a.id NOT IN (SELECT account FROM terminals)
+    AND a.id NOT IN (SELECT account FROM system_accounts)
+GROUP BY a.id;
 
 --! create_transaction
-INSERT INTO transactions(sender, recipient, amount)
+INSERT INTO transactions(transaction_id, sender, recipient, amount)
 VALUES (
+    :transaction_id,
     (
         SELECT id FROM accounts WHERE card_number = :sender_card 
     ),
@@ -111,11 +288,345 @@ VALUES (
      :amount
 );
 
---! insert_token
-INSERT INTO tokens(account, token)
+--! create_fee_transaction
+INSERT INTO transactions(transaction_id, sender, recipient, amount, kind)
+VALUES (
+    :transaction_id,
+    (
+        SELECT id FROM accounts WHERE card_number = :sender_card
+    ),
+    (
+        SELECT account FROM system_accounts WHERE role = 'revenue'
+    ),
+    :amount,
+    'acquiring_fee'
+);
+
+--! create_fee_refund_transaction
+INSERT INTO transactions(transaction_id, sender, recipient, amount, kind)
+VALUES (
+    :transaction_id,
+    (
+        SELECT account FROM system_accounts WHERE role = 'revenue'
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = :recipient_card
+    ),
+    :amount,
+    'fee_refund'
+);
+
+--! get_account_by_username
+SELECT card_number
+FROM accounts
+WHERE username = :username;
+
+--! get_revenue_account
+SELECT a.card_number
+FROM system_accounts
+JOIN accounts a ON system_accounts.account = a.id
+WHERE system_accounts.role = 'revenue';
+
+--! mark_revenue_account
+INSERT INTO system_accounts(role, account)
+VALUES (
+    'revenue',
+    (
+        SELECT id FROM accounts WHERE card_number = :card_number
+    )
+);
+
+--! insert_token (expires_at?, terminal_key?)
+INSERT INTO tokens(account, token, expires_at, terminal_key)
+VALUES (
+    (
+        SELECT id FROM accounts WHERE card_number = :card_number
+    ),
+    :token,
+    :expires_at,
+    :terminal_key
+);
+
+--! insert_hold
+INSERT INTO holds(id, account, amount, expires_at)
+VALUES (
+    :id,
+    (
+        SELECT id FROM accounts WHERE card_number = :card_number
+    ),
+    :amount,
+    :expires_at
+);
+
+--! get_hold
+SELECT
+    holds.amount,
+    a.card_number
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE holds.id = :id AND holds.expires_at > CURRENT_TIMESTAMP;
+
+--! delete_hold
+DELETE FROM holds
+WHERE id = :id;
+
+--! delete_all_holds
+DELETE FROM holds;
+
+--! list_account_holds
+SELECT
+    holds.id,
+    holds.amount,
+    holds.created_at,
+    holds.expires_at
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE a.card_number = :card_number AND holds.expires_at > CURRENT_TIMESTAMP
+ORDER BY holds.created_at;
+
+--! insert_idempotency_key
+INSERT INTO idempotency_keys(idempotency_key, request_hash)
+VALUES (:idempotency_key, :request_hash)
+ON CONFLICT DO NOTHING;
+
+--! get_idempotency_key : (response_status?, response_content_type?, response_body?)
+SELECT
+    request_hash,
+    response_status,
+    response_content_type,
+    response_body
+FROM idempotency_keys
+WHERE idempotency_key = :idempotency_key;
+
+--! save_idempotent_response (response_content_type?)
+UPDATE idempotency_keys
+SET
+    response_status = :response_status,
+    response_content_type = :response_content_type,
+    response_body = :response_body
+WHERE idempotency_key = :idempotency_key;
+
+--! delete_idempotency_key
+DELETE FROM idempotency_keys
+WHERE idempotency_key = :idempotency_key;
+
+--! insert_loan
+INSERT INTO loans(id, created_at, account, principal, interest_rate, term, period_secs)
+VALUES (
+    :id,
+    :created_at,
+    (
+        SELECT id FROM accounts WHERE card_number = :card_number
+    ),
+    :principal,
+    :interest_rate,
+    :term,
+    :period_secs
+);
+
+--! get_loan
+SELECT
+    loans.id,
+    loans.created_at,
+    a.card_number,
+    loans.principal,
+    loans.interest_rate,
+    loans.term,
+    loans.period_secs,
+    loans.repaid
+FROM loans
+JOIN accounts a ON loans.account = a.id
+WHERE loans.id = :id;
+
+--! lock_loan
+SELECT id
+FROM loans
+WHERE id = :id
+FOR UPDATE;
+
+--! add_loan_repayment
+UPDATE loans
+SET repaid = repaid + :amount
+WHERE id = :id;
+
+--! insert_transfer_order (run_at?, cron?, next_run_at?)
+INSERT INTO transfer_orders(id, created_at, sender, recipient, amount, run_at, cron, next_run_at)
+VALUES (
+    :id,
+    :created_at,
+    (
+        SELECT id FROM accounts WHERE card_number = :sender
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = :recipient
+    ),
+    :amount,
+    :run_at,
+    :cron,
+    :next_run_at
+);
+
+--! get_transfer_orders (due_at?) : (run_at?, cron?, next_run_at?, cancelled_at?)
+SELECT
+    transfer_orders.id,
+    transfer_orders.created_at,
+    s.card_number AS sender,
+    r.card_number AS recipient,
+    transfer_orders.amount,
+    transfer_orders.run_at,
+    transfer_orders.cron,
+    transfer_orders.next_run_at,
+    transfer_orders.cancelled_at
+FROM transfer_orders
+JOIN accounts s ON transfer_orders.sender = s.id
+JOIN accounts r ON transfer_orders.recipient = r.id
+WHERE :due_at::TIMESTAMP WITH TIME ZONE IS NULL
+    OR (transfer_orders.cancelled_at IS NULL AND transfer_orders.next_run_at <= :due_at)
+ORDER BY transfer_orders.created_at;
+
+--! get_order_executions : (transaction_id?, error?)
+SELECT order_id, executed_at, transaction_id, error
+FROM transfer_order_executions
+WHERE order_id = ANY(:order_ids)
+ORDER BY id;
+
+--! cancel_transfer_order
+UPDATE transfer_orders
+SET cancelled_at = CURRENT_TIMESTAMP, next_run_at = NULL
+WHERE id = :id AND cancelled_at IS NULL;
+
+--! is_transfer_order_exists
+SELECT EXISTS(SELECT 1 FROM transfer_orders WHERE id = :id);
+
+--! lock_transfer_order
+SELECT id
+FROM transfer_orders
+WHERE id = :id AND cancelled_at IS NULL AND next_run_at <= :due_at
+FOR UPDATE;
+
+--! insert_order_execution (transaction_id?, error?)
+INSERT INTO transfer_order_executions(order_id, executed_at, transaction_id, error)
+VALUES (:order_id, :executed_at, :transaction_id, :error);
+
+--! set_order_next_run (next_run_at?)
+UPDATE transfer_orders
+SET next_run_at = :next_run_at
+WHERE id = :id AND cancelled_at IS NULL;
+
+--! audit_accounts : (deleted_at?)
+SELECT
+    id,
+    card_number,
+    balance,
+    overdraft_limit,
+    deleted_at
+FROM accounts
+ORDER BY id;
+
+--! audit_tokens : (card_number?)
+SELECT
+    tokens.token,
+    a.card_number
+FROM tokens
+LEFT JOIN accounts a ON tokens.account = a.id
+ORDER BY tokens.id;
+
+--! export_accounts : (deleted_at?, status_reason?, card_expiry_month?, card_expiry_year?, cvv_hash?, terminal_key?)
+SELECT
+    a.id,
+    a.username,
+    a.card_number,
+    a.password_hash,
+    a.is_existing,
+    a.deleted_at,
+    a.overdraft_limit,
+    a.status,
+    a.status_reason,
+    a.card_expiry_month,
+    a.card_expiry_year,
+    a.cvv_hash,
+    t.terminal_key
+FROM accounts a
+LEFT JOIN terminals t ON t.account = a.id
+ORDER BY a.id;
+
+--! export_tokens : (expires_at?, revoked_at?, terminal_key?)
+SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+ORDER BY tokens.id;
+
+--! clear_bank
+TRUNCATE accounts, transactions, tokens, holds, terminals, idempotency_keys,
+    loans, transfer_orders, transfer_order_executions, system_accounts
+RESTART IDENTITY;
+
+--! disable_balance_check
+ALTER TABLE transactions
+DISABLE TRIGGER trg_check_balance_before_transaction;
+
+--! enable_balance_check
+ALTER TABLE transactions
+ENABLE TRIGGER trg_check_balance_before_transaction;
+
+--! import_account (deleted_at?, status_reason?, card_expiry_month?, card_expiry_year?, cvv_hash?)
+INSERT INTO accounts(
+    username,
+    card_number,
+    password_hash,
+    is_existing,
+    deleted_at,
+    overdraft_limit,
+    status,
+    status_reason,
+    card_expiry_month,
+    card_expiry_year,
+    cvv_hash
+)
+VALUES (
+    :username,
+    :card_number,
+    :password_hash,
+    :is_existing,
+    :deleted_at,
+    :overdraft_limit,
+    :status,
+    :status_reason,
+    :card_expiry_month,
+    :card_expiry_year,
+    :cvv_hash
+);
+
+--! import_token (expires_at?, revoked_at?, terminal_key?)
+INSERT INTO tokens(account, token, created_at, expires_at, revoked_at, terminal_key)
 VALUES (
     (
         SELECT id FROM accounts WHERE card_number = :card_number
     ),
-    :token
+    :token,
+    :created_at,
+    :expires_at,
+    :revoked_at,
+    :terminal_key
+);
+
+--! import_transaction
+INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount, kind)
+VALUES (
+    :transaction_id,
+    :created_at,
+    (
+        SELECT id FROM accounts WHERE card_number = :sender_card
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = :recipient_card
+    ),
+    :amount,
+    :kind
 );
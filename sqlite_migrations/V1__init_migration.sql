@@ -0,0 +1,158 @@
+-- Schema of all Postgres migrations up to V15, times are stored
+-- as UTC text and uuids as blobs
+CREATE TABLE accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TEXT NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    card_number TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    is_existing BOOLEAN NOT NULL DEFAULT TRUE,
+    deleted_at TEXT,
+    balance INTEGER NOT NULL DEFAULT 0,
+    overdraft_limit INTEGER NOT NULL DEFAULT 0 CHECK (overdraft_limit >= 0),
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'frozen', 'blocked')),
+    status_reason TEXT,
+    status_changed_at TEXT,
+    card_expiry_month INTEGER CHECK (card_expiry_month BETWEEN 1 AND 12),
+    card_expiry_year INTEGER,
+    cvv_hash TEXT
+);
+
+CREATE TABLE transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id BLOB NOT NULL UNIQUE,
+    created_at TEXT NOT NULL,
+    sender INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    recipient INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    amount INTEGER NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'transfer'
+);
+
+CREATE TABLE tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TEXT NOT NULL,
+    account INTEGER REFERENCES accounts(id) ON DELETE RESTRICT,
+    token TEXT NOT NULL,
+    expires_at TEXT,
+    revoked_at TEXT
+);
+
+CREATE INDEX tokens_account_idx ON tokens(account);
+CREATE INDEX tokens_token_idx ON tokens(token);
+
+CREATE TABLE holds (
+    id BLOB PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    account INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    amount INTEGER NOT NULL CHECK (amount > 0)
+);
+
+CREATE INDEX holds_account_idx ON holds(account);
+
+-- Store account of each merchant terminal
+CREATE TABLE terminals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    terminal_key BLOB NOT NULL UNIQUE,
+    account INTEGER NOT NULL UNIQUE REFERENCES accounts(id) ON DELETE RESTRICT
+);
+
+-- Results of requests performed with `Idempotency-Key` header
+CREATE TABLE idempotency_keys (
+    idempotency_key TEXT PRIMARY KEY,
+    request_hash TEXT NOT NULL,
+    response_status INTEGER,
+    response_content_type TEXT,
+    response_body BLOB
+);
+
+-- Interest rate is stored as decimal text
+CREATE TABLE loans (
+    id BLOB PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    account INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    principal INTEGER NOT NULL CHECK (principal > 0),
+    interest_rate TEXT NOT NULL,
+    term INTEGER NOT NULL CHECK (term > 0),
+    period_secs INTEGER NOT NULL CHECK (period_secs > 0),
+    repaid INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE transfer_orders (
+    id BLOB PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    sender INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    recipient INTEGER NOT NULL REFERENCES accounts(id) ON DELETE RESTRICT,
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    -- Either one-time run or cron schedule
+    run_at TEXT,
+    cron TEXT,
+    next_run_at TEXT,
+    cancelled_at TEXT,
+    CHECK ((run_at IS NULL) <> (cron IS NULL))
+);
+
+CREATE INDEX transfer_orders_next_run_idx ON transfer_orders(next_run_at);
+
+CREATE TABLE transfer_order_executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    order_id BLOB NOT NULL REFERENCES transfer_orders(id) ON DELETE CASCADE,
+    executed_at TEXT NOT NULL,
+    transaction_id BLOB,
+    error TEXT
+);
+
+CREATE INDEX transfer_order_executions_order_idx
+ON transfer_order_executions(order_id);
+
+-- Same checks as the Postgres trigger. Frozen account can't send
+-- money, blocked account can't send or receive money, and account
+-- can go negative down to it's overdraft limit.
+CREATE TRIGGER check_balance_before_transaction
+BEFORE INSERT ON transactions
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'Sender or recipient account does not exist or is not active')
+    WHERE NOT EXISTS (SELECT 1 FROM accounts WHERE id = NEW.sender AND is_existing)
+        OR NOT EXISTS (SELECT 1 FROM accounts WHERE id = NEW.recipient AND is_existing);
+
+    SELECT RAISE(ABORT, 'Account is blocked')
+    WHERE EXISTS (
+        SELECT 1 FROM accounts
+        WHERE id IN (NEW.sender, NEW.recipient) AND status = 'blocked'
+    );
+
+    SELECT RAISE(ABORT, 'Account is frozen')
+    WHERE EXISTS (
+        SELECT 1 FROM accounts WHERE id = NEW.sender AND status = 'frozen'
+    );
+
+    SELECT RAISE(ABORT, 'Sender and recipient cannot be the same')
+    WHERE NEW.sender <> 1 AND NEW.sender = NEW.recipient;
+
+    SELECT RAISE(ABORT, 'Amount must be greater than 0')
+    WHERE NEW.sender <> 1 AND NEW.amount <= 0;
+
+    SELECT RAISE(ABORT, 'Overdraft limit exceeded')
+    FROM accounts
+    WHERE NEW.sender <> 1
+        AND id = NEW.sender
+        AND overdraft_limit > 0
+        AND balance - (SELECT COALESCE(SUM(amount), 0) FROM holds WHERE account = NEW.sender)
+            + overdraft_limit < NEW.amount;
+
+    SELECT RAISE(ABORT, 'Not enough funds')
+    FROM accounts
+    WHERE NEW.sender <> 1
+        AND id = NEW.sender
+        AND balance - (SELECT COALESCE(SUM(amount), 0) FROM holds WHERE account = NEW.sender)
+            + overdraft_limit < NEW.amount;
+END;
+
+-- Keep balances up to date in the same db transaction as the insert
+CREATE TRIGGER update_balances_after_transaction
+AFTER INSERT ON transactions
+FOR EACH ROW
+BEGIN
+    UPDATE accounts SET balance = balance - NEW.amount WHERE id = NEW.sender;
+    UPDATE accounts SET balance = balance + NEW.amount WHERE id = NEW.recipient;
+END;
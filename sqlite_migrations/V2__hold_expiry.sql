@@ -0,0 +1,54 @@
+ALTER TABLE holds
+ADD COLUMN expires_at TEXT NOT NULL DEFAULT '';
+
+UPDATE holds SET expires_at = datetime(created_at, '+1 hour');
+
+-- Expired holds don't reserve funds
+DROP TRIGGER check_balance_before_transaction;
+
+CREATE TRIGGER check_balance_before_transaction
+BEFORE INSERT ON transactions
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'Sender or recipient account does not exist or is not active')
+    WHERE NOT EXISTS (SELECT 1 FROM accounts WHERE id = NEW.sender AND is_existing)
+        OR NOT EXISTS (SELECT 1 FROM accounts WHERE id = NEW.recipient AND is_existing);
+
+    SELECT RAISE(ABORT, 'Account is blocked')
+    WHERE EXISTS (
+        SELECT 1 FROM accounts
+        WHERE id IN (NEW.sender, NEW.recipient) AND status = 'blocked'
+    );
+
+    SELECT RAISE(ABORT, 'Account is frozen')
+    WHERE EXISTS (
+        SELECT 1 FROM accounts WHERE id = NEW.sender AND status = 'frozen'
+    );
+
+    SELECT RAISE(ABORT, 'Sender and recipient cannot be the same')
+    WHERE NEW.sender <> 1 AND NEW.sender = NEW.recipient;
+
+    SELECT RAISE(ABORT, 'Amount must be greater than 0')
+    WHERE NEW.sender <> 1 AND NEW.amount <= 0;
+
+    SELECT RAISE(ABORT, 'Overdraft limit exceeded')
+    FROM accounts
+    WHERE NEW.sender <> 1
+        AND id = NEW.sender
+        AND overdraft_limit > 0
+        AND balance - (
+            SELECT COALESCE(SUM(amount), 0) FROM holds
+            WHERE account = NEW.sender
+                AND julianday(expires_at) > julianday('now')
+        ) + overdraft_limit < NEW.amount;
+
+    SELECT RAISE(ABORT, 'Not enough funds')
+    FROM accounts
+    WHERE NEW.sender <> 1
+        AND id = NEW.sender
+        AND balance - (
+            SELECT COALESCE(SUM(amount), 0) FROM holds
+            WHERE account = NEW.sender
+                AND julianday(expires_at) > julianday('now')
+        ) + overdraft_limit < NEW.amount;
+END;
@@ -0,0 +1,8 @@
+-- History is append-only, like in Postgres, where the balance check
+-- runs before delete too. Sqlite trigger can't handle both events.
+CREATE TRIGGER forbid_transaction_delete
+BEFORE DELETE ON transactions
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'Transactions can not be deleted');
+END;
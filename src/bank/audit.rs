@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::domain::card_number::CardNumber;
+
+use super::{iso_format, Transaction};
+
+/// Account state, collected by the backend for the audit
+#[derive(Debug)]
+pub struct AuditedAccount {
+    pub card_number: CardNumber,
+    /// Balance, stored by the backend
+    pub balance: i64,
+    pub overdraft_limit: i64,
+    pub is_emission: bool,
+    /// Not set for existing accounts and accounts, deleted
+    /// before the deletion time was tracked
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+/// Card token with the account it points to, if any
+#[derive(Debug)]
+pub struct AuditedToken {
+    pub token: String,
+    pub card_number: Option<CardNumber>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuditReport {
+    #[serde(with = "iso_format")]
+    pub checked_at: OffsetDateTime,
+    pub accounts: usize,
+    pub transactions: usize,
+    pub tokens: usize,
+    /// Sum of all balances including emission, should be zero
+    pub total_balance: i64,
+    pub consistent: bool,
+    pub violations: Vec<AuditViolation>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditViolation {
+    NonZeroSum {
+        total: i64,
+    },
+    /// Account went below it's overdraft limit, emission account
+    /// is always negative and is not checked
+    NegativeBalance {
+        card_number: CardNumber,
+        balance: i64,
+        overdraft_limit: i64,
+    },
+    /// Stored balance differs from the sum of account transactions
+    BalanceMismatch {
+        card_number: CardNumber,
+        stored: i64,
+        computed: i64,
+    },
+    TransactionAfterDeletion {
+        transaction_id: uuid::Uuid,
+        card_number: CardNumber,
+        #[serde(with = "iso_format")]
+        deleted_at: OffsetDateTime,
+        #[serde(with = "iso_format")]
+        datetime: OffsetDateTime,
+    },
+    /// Token doesn't point to any account
+    DanglingToken {
+        token: String,
+    },
+    /// Stored transactions don't match the ledger
+    LedgerTampered,
+}
+
+/// Check bank invariants on a consistent snapshot of backend data
+pub fn audit(
+    accounts: &[AuditedAccount],
+    transactions: &[Transaction],
+    tokens: &[AuditedToken],
+) -> AuditReport {
+    let mut violations = Vec::new();
+
+    let total_balance = accounts.iter().map(|acc| acc.balance).sum();
+    if total_balance != 0 {
+        violations.push(AuditViolation::NonZeroSum {
+            total: total_balance,
+        });
+    }
+
+    let mut computed: HashMap<&CardNumber, i64> = HashMap::new();
+    for transaction in transactions {
+        *computed.entry(&transaction.sender.card_number).or_default() -=
+            transaction.amount;
+        *computed
+            .entry(&transaction.recipient.card_number)
+            .or_default() += transaction.amount;
+    }
+
+    for acc in accounts {
+        if !acc.is_emission && acc.balance < -acc.overdraft_limit {
+            violations.push(AuditViolation::NegativeBalance {
+                card_number: acc.card_number.clone(),
+                balance: acc.balance,
+                overdraft_limit: acc.overdraft_limit,
+            });
+        }
+        let computed =
+            computed.get(&acc.card_number).copied().unwrap_or_default();
+        if computed != acc.balance {
+            violations.push(AuditViolation::BalanceMismatch {
+                card_number: acc.card_number.clone(),
+                stored: acc.balance,
+                computed,
+            });
+        }
+    }
+
+    let deleted: HashMap<&CardNumber, OffsetDateTime> = accounts
+        .iter()
+        .filter_map(|acc| Some((&acc.card_number, acc.deleted_at?)))
+        .collect();
+    for transaction in transactions {
+        for party in [&transaction.sender, &transaction.recipient] {
+            let Some(&deleted_at) = deleted.get(&party.card_number) else {
+                continue;
+            };
+            if transaction.datetime > deleted_at {
+                violations.push(AuditViolation::TransactionAfterDeletion {
+                    transaction_id: transaction.id,
+                    card_number: party.card_number.clone(),
+                    deleted_at,
+                    datetime: transaction.datetime,
+                });
+            }
+        }
+    }
+
+    for token in tokens {
+        let points_to_account =
+            token.card_number.as_ref().is_some_and(|card| {
+                accounts.iter().any(|acc| acc.card_number.eq(card))
+            });
+        if !points_to_account {
+            violations.push(AuditViolation::DanglingToken {
+                token: token.token.clone(),
+            });
+        }
+    }
+
+    AuditReport {
+        checked_at: OffsetDateTime::now_utc(),
+        accounts: accounts.len(),
+        transactions: transactions.len(),
+        tokens: tokens.len(),
+        total_balance,
+        consistent: violations.is_empty(),
+        violations,
+    }
+}
@@ -2,15 +2,27 @@ use std::sync::Arc;
 
 use axum::async_trait;
 use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use rust_decimal::Decimal;
 use secrecy::Secret;
+use time::{Duration, OffsetDateTime};
 use tokio::sync::watch::Receiver;
 use tokio::sync::watch::Sender;
+use uuid::Uuid;
 
 use crate::domain::card_number::CardNumber;
+use crate::domain::requests::system_api::StatementRequest;
+use crate::domain::responses::system_api::StatementResponse;
 use crate::middleware::Credentials;
 use crate::Settings;
 
-use super::{Account, BankOperationError, Transaction};
+use super::audit::AuditReport;
+use super::dump::BankDump;
+use super::loan::Loan;
+use super::transfer_order::{OrderExecution, OrderSchedule, TransferOrder};
+use super::{
+    Account, AccountStatus, BankOperationError, CardExpiry, CardToken,
+    IdempotencyStatus, IdempotentResponse, IssuedCard, Transaction,
+};
 
 pub trait InitBankDataBackend {
     fn new(
@@ -33,11 +45,33 @@ where
         &self,
         username: &str,
         password: &Secret<String>,
-    ) -> Result<CardNumber, BankOperationError>;
+    ) -> Result<IssuedCard, BankOperationError>;
+    /// Issue new expiry date and CVV for the account card,
+    /// expiry is generated if not set
+    async fn reissue_card(
+        &self,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> Result<IssuedCard, BankOperationError>;
+    /// `None` if card details were never issued for the account
+    async fn card_expiry(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Option<CardExpiry>, BankOperationError>;
     async fn delete_account(
         &self,
         card: &CardNumber,
     ) -> Result<(), BankOperationError>;
+    async fn restore_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError>;
+    async fn set_account_status(
+        &self,
+        card: &CardNumber,
+        status: AccountStatus,
+        reason: Option<&str>,
+    ) -> Result<(), BankOperationError>;
     async fn list_accounts(
         &self,
     ) -> Result<
@@ -49,12 +83,30 @@ where
         card: &CardNumber,
         password: &Secret<String>,
     ) -> Result<Account, BankOperationError>;
+    /// Authorize account with card details, entered on the payment page
+    async fn authorize_card(
+        &self,
+        card: &CardNumber,
+        password: &Secret<String>,
+        expiry: CardExpiry,
+        cvv: &Secret<String>,
+    ) -> Result<Account, BankOperationError>;
     async fn find_account(
         &self,
         card: &CardNumber,
     ) -> Result<Account, BankOperationError>;
-    async fn get_store_account(&self) -> Result<Account, BankOperationError>;
-    async fn store_balance(&self) -> Result<i64, BankOperationError>;
+    async fn account_status(
+        &self,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError>;
+    async fn get_store_account(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<Account, BankOperationError>;
+    async fn store_balance(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<i64, BankOperationError>;
     async fn balance(
         &self,
         card: &CardNumber,
@@ -64,28 +116,192 @@ where
         sender: &CardNumber,
         recipient: &CardNumber,
         amount: i64,
-    ) -> Result<(), BankOperationError>;
+    ) -> Result<Uuid, BankOperationError>;
+    /// Payment to the store, acquiring `fee` is withheld from the
+    /// `amount` and goes to the bank revenue account
+    async fn new_payment(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<Uuid, BankOperationError>;
+    /// Beneficiaries share the `amount` without acquiring `fee`
     async fn new_split_transaction(
         &self,
         sender: &CardNumber,
         amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<Vec<Uuid>, BankOperationError>;
+    /// Refund of the payment to the store, returned acquiring `fee` is
+    /// taken from the bank revenue account, the rest of the `amount`
+    /// from the store
+    async fn new_refund(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError>;
+    /// Beneficiaries return the `amount` without acquiring `fee`,
+    /// which is returned from the bank revenue account
+    async fn new_split_refund_transaction(
+        &self,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError>;
+    async fn new_hold(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError>;
+    async fn release_hold(&self, hold: Uuid) -> Result<(), BankOperationError>;
+    /// Holds belong to payment sessions, which are kept only in memory,
+    /// so holds of the previous run are released on startup
+    async fn release_all_holds(&self) -> Result<(), BankOperationError>;
+    async fn capture_hold(
+        &self,
+        hold: Uuid,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError>;
+    async fn capture_split_hold(
+        &self,
+        hold: Uuid,
+        amount: i64,
+        fee: i64,
         beneficiaries: &Beneficiaries,
     ) -> Result<(), BankOperationError>;
     async fn open_credit(
         &self,
         card: &CardNumber,
         amount: i64,
+    ) -> Result<Uuid, BankOperationError>;
+    async fn open_loan(
+        &self,
+        card: &CardNumber,
+        principal: i64,
+        interest_rate: Decimal,
+        term: u32,
+        period: Duration,
+    ) -> Result<Loan, BankOperationError>;
+    async fn repay_loan(
+        &self,
+        id: &Uuid,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError>;
+    async fn get_loan(&self, id: &Uuid) -> Result<Loan, BankOperationError>;
+    async fn new_transfer_order(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        schedule: OrderSchedule,
+    ) -> Result<TransferOrder, BankOperationError>;
+    /// All orders with their executions
+    async fn list_transfer_orders(
+        &self,
+    ) -> Result<Vec<TransferOrder>, BankOperationError>;
+    async fn cancel_transfer_order(
+        &self,
+        id: &Uuid,
+    ) -> Result<(), BankOperationError>;
+    /// Not cancelled orders, which should run at `now`
+    async fn due_transfer_orders(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<Vec<TransferOrder>, BankOperationError>;
+    /// Perform the transfer of the due order and save the run outcome
+    /// at once, so the order runs only once for each due time. `None` if
+    /// the order is not due anymore, e.g. it was cancelled or already run.
+    async fn run_transfer_order(
+        &self,
+        id: &Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Option<OrderExecution>, BankOperationError>;
+    async fn set_overdraft_limit(
+        &self,
+        card: &CardNumber,
+        limit: i64,
     ) -> Result<(), BankOperationError>;
     async fn list_transactions(
         &self,
     ) -> Result<Vec<Transaction>, BankOperationError>;
+    /// Transactions, stored after the `last` one. All of them are
+    /// returned if it is not set or not stored anymore.
+    async fn list_transactions_after(
+        &self,
+        last: Option<Uuid>,
+    ) -> Result<Vec<Transaction>, BankOperationError>;
+    async fn get_transaction(
+        &self,
+        id: &Uuid,
+    ) -> Result<Transaction, BankOperationError>;
+    async fn account_statement(
+        &self,
+        req: &StatementRequest,
+    ) -> Result<StatementResponse, BankOperationError>;
     async fn bank_emission(&self) -> Result<i64, BankOperationError>;
+    /// Balance of the account, which collects acquiring fees
+    async fn bank_revenue(&self) -> Result<i64, BankOperationError>;
+    /// Check bank invariants, violations are reported, not returned as error
+    async fn audit(&self) -> Result<AuditReport, BankOperationError>;
+    /// Token without `expires_at` lives until it is revoked, token with
+    /// `terminal_key` can be charged only by that terminal
     async fn new_card_token(
         &self,
         card: &CardNumber,
+        expires_at: Option<OffsetDateTime>,
+        terminal_key: Option<Uuid>,
     ) -> Result<String, BankOperationError>;
+    async fn revoke_card_token(
+        &self,
+        token: &str,
+    ) -> Result<(), BankOperationError>;
+    /// All tokens of the account, including revoked and expired ones
+    async fn list_card_tokens(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Vec<CardToken>, BankOperationError>;
+    /// Token itself, even if it is revoked or expired
+    async fn get_card_token(
+        &self,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError>;
+    /// Fails for revoked and expired tokens
     async fn get_account_by_token(
         &self,
         token: &str,
     ) -> Result<Account, BankOperationError>;
+    async fn begin_idempotent_request(
+        &self,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyStatus, BankOperationError>;
+    async fn complete_idempotent_request(
+        &self,
+        key: &str,
+        response: &IdempotentResponse,
+    ) -> Result<(), BankOperationError>;
+    async fn abort_idempotent_request(
+        &self,
+        key: &str,
+    ) -> Result<(), BankOperationError>;
+    /// All accounts with their secret hashes, tokens and transactions
+    async fn export_dump(&self) -> Result<BankDump, BankOperationError>;
+    /// Replace the whole bank state with the validated dump, system
+    /// accounts keep their credentials
+    async fn import_dump(
+        &self,
+        dump: &BankDump,
+    ) -> Result<(), BankOperationError>;
+    /// Save data, which is kept only in memory, it is called
+    /// on shutdown. Database backends have nothing to save.
+    async fn save_snapshot(&self) -> Result<(), BankOperationError> {
+        Ok(())
+    }
 }
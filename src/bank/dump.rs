@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::config::TerminalSettings;
+use crate::domain::card_number::CardNumber;
+use crate::Settings;
+
+use super::{
+    AccountStatus, BankOperationError, CardExpiry, CardToken, Transaction,
+    TransactionKind,
+};
+
+/// Bumped on every incompatible change of the dump format
+pub const DUMP_VERSION: u32 = 1;
+
+/// Whole bank state in the format, which any backend can import.
+/// Secrets are kept as hashes in PHC string format. Holds, loans,
+/// transfer orders and idempotency keys are not dumped.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BankDump {
+    pub version: u32,
+    pub accounts: Vec<DumpAccount>,
+    pub tokens: Vec<DumpToken>,
+    pub transactions: Vec<DumpTransaction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum AccountRole {
+    Customer,
+    Emission,
+    /// Account, which collects acquiring fees
+    Revenue,
+    Store {
+        terminal_key: Uuid,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DumpAccount {
+    pub username: String,
+    pub card_number: CardNumber,
+    #[serde(flatten)]
+    pub role: AccountRole,
+    pub password_hash: String,
+    pub is_existing: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub deleted_at: Option<OffsetDateTime>,
+    pub overdraft_limit: i64,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
+    /// `None` if card details were never issued for the account
+    pub card: Option<DumpCard>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DumpCard {
+    pub expiry: CardExpiry,
+    pub cvv_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DumpToken {
+    pub token: String,
+    pub card_number: CardNumber,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    /// Dumps made before tokens were bound to terminals lack the field
+    #[serde(default)]
+    pub terminal_key: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DumpTransaction {
+    pub id: Uuid,
+    pub sender: CardNumber,
+    pub recipient: CardNumber,
+    pub amount: i64,
+    pub kind: TransactionKind,
+    #[serde(with = "time::serde::rfc3339")]
+    pub datetime: OffsetDateTime,
+}
+
+impl BankDump {
+    /// Check the dump before it replaces the bank state
+    pub fn validate(&self) -> Result<(), BankOperationError> {
+        if self.version != DUMP_VERSION {
+            return Err(bad_dump(format!(
+                "Unsupported dump version: {}",
+                self.version
+            )));
+        }
+
+        let mut cards = HashSet::new();
+        let mut usernames = HashSet::new();
+        let mut terminals = HashSet::new();
+        let (mut emission, mut revenue) = (0, 0);
+        for acc in self.accounts.iter() {
+            if !cards.insert(&acc.card_number) {
+                return Err(bad_dump(format!(
+                    "Duplicated card number: {}",
+                    acc.card_number.as_ref()
+                )));
+            }
+            if !usernames.insert(acc.username.as_str()) {
+                return Err(bad_dump(format!(
+                    "Duplicated username: {}",
+                    acc.username
+                )));
+            }
+            match acc.role {
+                AccountRole::Customer => (),
+                AccountRole::Emission => emission += 1,
+                AccountRole::Revenue => revenue += 1,
+                AccountRole::Store { terminal_key } => {
+                    if !terminals.insert(terminal_key) {
+                        return Err(bad_dump(format!(
+                            "Duplicated terminal: {terminal_key}"
+                        )));
+                    }
+                }
+            }
+            if acc.overdraft_limit < 0 {
+                return Err(bad_dump(
+                    "Overdraft limit should not be negative".to_string(),
+                ));
+            }
+            check_hash(&acc.password_hash)?;
+            if let Some(ref card) = acc.card {
+                CardExpiry::new(card.expiry.month, card.expiry.year)?;
+                check_hash(&card.cvv_hash)?;
+            }
+        }
+        if emission != 1 || revenue != 1 {
+            return Err(bad_dump(
+                "Dump should have one emission and one revenue account"
+                    .to_string(),
+            ));
+        }
+
+        for token in self.tokens.iter() {
+            if !cards.contains(&token.card_number) {
+                return Err(bad_dump(format!(
+                    "Token of unknown card: {}",
+                    token.card_number.as_ref()
+                )));
+            }
+        }
+        let mut ids = HashSet::new();
+        for t in self.transactions.iter() {
+            if !ids.insert(t.id) {
+                return Err(bad_dump(format!(
+                    "Duplicated transaction: {}",
+                    t.id
+                )));
+            }
+            for card in [&t.sender, &t.recipient] {
+                if !cards.contains(card) {
+                    return Err(bad_dump(format!(
+                        "Transaction {} of unknown card: {}",
+                        t.id,
+                        card.as_ref()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Accounts in the import order, emission goes first, because
+    /// database backends find it by id
+    pub fn import_order(&self) -> impl Iterator<Item = &DumpAccount> {
+        let emission = self
+            .accounts
+            .iter()
+            .filter(|acc| acc.role == AccountRole::Emission);
+        let others = self
+            .accounts
+            .iter()
+            .filter(|acc| acc.role != AccountRole::Emission);
+        emission.chain(others)
+    }
+
+    /// Terminals of the importing bank without store account in the dump
+    pub fn missing_terminals<'a>(
+        &'a self,
+        settings: &'a Settings,
+    ) -> impl Iterator<Item = &'a TerminalSettings> {
+        settings.all_terminals().filter(|terminal| {
+            !self.accounts.iter().any(|acc| {
+                acc.role
+                    == AccountRole::Store {
+                        terminal_key: terminal.terminal_key,
+                    }
+            })
+        })
+    }
+}
+
+impl DumpAccount {
+    /// Username and password of the system account are taken from the
+    /// settings of the importing bank, so the system API stays
+    /// available. `None` for other accounts, they keep dumped ones.
+    pub fn system_credentials<'a>(
+        &self,
+        settings: &'a Settings,
+    ) -> Option<(String, &'a Secret<String>)> {
+        match self.role {
+            AccountRole::Customer => None,
+            AccountRole::Emission => Some((
+                settings.bank_username.clone(),
+                &settings.terminal_settings.password,
+            )),
+            AccountRole::Revenue => Some((
+                settings.revenue_username(),
+                &settings.terminal_settings.password,
+            )),
+            AccountRole::Store { terminal_key } => settings
+                .find_terminal(Some(terminal_key))
+                .map(|terminal| (self.username.clone(), &terminal.password)),
+        }
+    }
+}
+
+impl From<CardToken> for DumpToken {
+    fn from(token: CardToken) -> Self {
+        DumpToken {
+            token: token.token,
+            card_number: token.card_number,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            revoked_at: token.revoked_at,
+            terminal_key: token.terminal_key,
+        }
+    }
+}
+
+impl From<&Transaction> for DumpTransaction {
+    fn from(t: &Transaction) -> Self {
+        DumpTransaction {
+            id: t.id,
+            sender: t.sender.card_number.clone(),
+            recipient: t.recipient.card_number.clone(),
+            amount: t.amount,
+            kind: t.kind,
+            datetime: t.datetime,
+        }
+    }
+}
+
+fn check_hash(hash: &str) -> Result<(), BankOperationError> {
+    argon2::PasswordHash::new(hash)
+        .map(|_| ())
+        .map_err(|_| bad_dump("Secrets should be hashed".to_string()))
+}
+
+fn bad_dump(reason: String) -> BankOperationError {
+    BankOperationError::BadOperation(format!("Bad bank dump: {reason}"))
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use rs_merkle::algorithms::Sha256;
+use rs_merkle::{Hasher, MerkleProof, MerkleTree};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{BankOperationError, Transaction, SIMPLE_ISO};
+
+/// Append-only Merkle tree over all bank transactions in the order
+/// they were stored, so auditors can check that the history was
+/// not rewritten.
+pub struct Ledger {
+    tree: MerkleTree<Sha256>,
+    /// Leaf index of each transaction
+    indices: HashMap<Uuid, usize>,
+    /// Last hashed transaction, newer ones are appended after it
+    last: Option<Uuid>,
+    /// Set once stored transactions stopped matching the leaves
+    tampered: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LedgerRoot {
+    /// Hex-encoded root hash, not set for the empty ledger
+    pub root: Option<String>,
+    pub leaves: usize,
+}
+
+/// Proof that the transaction is included into the ledger, can be
+/// checked with `rs_merkle::MerkleProof::verify`
+#[derive(Serialize, Debug)]
+pub struct InclusionProof {
+    pub transaction_id: Uuid,
+    pub leaf_index: usize,
+    pub leaf_hash: String,
+    /// Hex-encoded sibling hashes from the leaf up to the root
+    pub proof_hashes: Vec<String>,
+    pub root: String,
+    pub leaves: usize,
+}
+
+impl std::fmt::Debug for Ledger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Ledger {{ leaves: {} }}",
+            self.tree.leaves_len()
+        ))
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Ledger {
+            tree: MerkleTree::new(),
+            indices: HashMap::new(),
+            last: None,
+            tampered: false,
+        }
+    }
+}
+
+impl Ledger {
+    /// SHA-256 of `id|sender card|recipient card|amount|kind|datetime`,
+    /// datetime is formatted like in the transactions list.
+    pub fn leaf_hash(
+        transaction: &Transaction,
+    ) -> Result<[u8; 32], BankOperationError> {
+        let datetime = transaction
+            .datetime
+            .format(&SIMPLE_ISO)
+            .map_err(|e| anyhow::anyhow!("Failed to format datetime: {e}"))?;
+        let leaf = format!(
+            "{}|{}|{}|{}|{}|{}",
+            transaction.id,
+            transaction.sender.card_number.as_ref(),
+            transaction.recipient.card_number.as_ref(),
+            transaction.amount,
+            transaction.kind.as_str(),
+            datetime
+        );
+        Ok(Sha256::hash(leaf.as_bytes()))
+    }
+
+    /// Last hashed transaction, only transactions stored after it
+    /// should be appended.
+    pub fn last_transaction(&self) -> Option<Uuid> {
+        self.last
+    }
+
+    /// Append transactions, stored after the last hashed one. Fails if
+    /// some of them are already hashed, which happens when the last
+    /// hashed transaction was removed.
+    pub fn append(
+        &mut self,
+        transactions: &[Transaction],
+    ) -> Result<(), BankOperationError> {
+        if self.tampered
+            || transactions
+                .iter()
+                .any(|transaction| self.indices.contains_key(&transaction.id))
+        {
+            self.tampered = true;
+            return Err(BankOperationError::LedgerTampered);
+        }
+        let Some(last) = transactions.last() else {
+            return Ok(());
+        };
+        let mut leaves = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            leaves.push(Self::leaf_hash(transaction)?);
+        }
+        for (transaction, leaf) in transactions.iter().zip(leaves) {
+            self.indices.insert(transaction.id, self.indices.len());
+            self.tree.insert(leaf);
+        }
+        self.tree.commit();
+        self.last = Some(last.id);
+        Ok(())
+    }
+
+    /// Check that all stored transactions still match the leaves.
+    /// The whole history is hashed again, so it's done only by the
+    /// audit, the ledger stays tampered once it fails.
+    pub fn verify(
+        &mut self,
+        transactions: &[Transaction],
+    ) -> Result<(), BankOperationError> {
+        let leaves = self.tree.leaves().unwrap_or_default();
+        self.tampered |= transactions.len() < leaves.len();
+        for (leaf, transaction) in leaves.iter().zip(transactions) {
+            if self.tampered {
+                break;
+            }
+            self.tampered = Self::leaf_hash(transaction)?.ne(leaf);
+        }
+        if self.tampered {
+            return Err(BankOperationError::LedgerTampered);
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> LedgerRoot {
+        LedgerRoot {
+            root: self.tree.root().map(hex::encode),
+            leaves: self.tree.leaves_len(),
+        }
+    }
+
+    pub fn proof(
+        &self,
+        transaction_id: &Uuid,
+    ) -> Result<InclusionProof, BankOperationError> {
+        let leaf_index = *self
+            .indices
+            .get(transaction_id)
+            .ok_or(BankOperationError::TransactionNotFound)?;
+        let leaves = self.tree.leaves().unwrap_or_default();
+        let root = self
+            .tree
+            .root()
+            .ok_or(BankOperationError::TransactionNotFound)?;
+        Ok(InclusionProof {
+            transaction_id: *transaction_id,
+            leaf_index,
+            leaf_hash: hex::encode(leaves[leaf_index]),
+            proof_hashes: self.tree.proof(&[leaf_index]).proof_hashes_hex(),
+            root: hex::encode(root),
+            leaves: leaves.len(),
+        })
+    }
+}
+
+impl InclusionProof {
+    pub fn verify(&self) -> bool {
+        let decode = |hash: &str| -> Option<[u8; 32]> {
+            hex::decode(hash).ok()?.try_into().ok()
+        };
+        let (Some(root), Some(leaf)) =
+            (decode(&self.root), decode(&self.leaf_hash))
+        else {
+            return false;
+        };
+        let Some(proof_hashes) =
+            self.proof_hashes.iter().map(|h| decode(h)).collect()
+        else {
+            return false;
+        };
+        MerkleProof::<Sha256>::new(proof_hashes).verify(
+            root,
+            &[self.leaf_index],
+            &[leaf],
+            self.leaves,
+        )
+    }
+}
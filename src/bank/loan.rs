@@ -0,0 +1,305 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::card_number::CardNumber;
+
+use super::{iso_format, BankOperationError};
+
+/// Longest loan term, 100 years of monthly installments
+const MAX_TERM: u32 = 1200;
+const MAX_PERIOD: Duration = Duration::days(366);
+/// Largest interest rate per period, 100%
+const MAX_INTEREST_RATE: Decimal = Decimal::ONE;
+
+/// Loan, issued from the emission account. Interest accrues
+/// every period, so the period can be shortened to simulate time.
+#[derive(Clone, Debug)]
+pub struct Loan {
+    pub id: Uuid,
+    pub card_number: CardNumber,
+    pub principal: i64,
+    /// Interest rate per period
+    pub interest_rate: Decimal,
+    /// Number of periods
+    pub term: u32,
+    pub period: Duration,
+    pub opened_at: OffsetDateTime,
+    /// Total amount, transferred back to the emission account
+    pub repaid: i64,
+}
+
+/// Annuity installment of the loan
+#[derive(Serialize, Debug)]
+pub struct Installment {
+    pub number: u32,
+    #[serde(with = "iso_format")]
+    pub due_date: OffsetDateTime,
+    pub principal: i64,
+    pub interest: i64,
+    pub amount: i64,
+    /// Part of the amount, covered by repayments
+    pub paid: i64,
+    pub overdue: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LoanSchedule {
+    pub id: Uuid,
+    pub card_number: CardNumber,
+    pub principal: i64,
+    pub interest_rate: Decimal,
+    pub term: u32,
+    pub period_secs: i64,
+    #[serde(with = "iso_format")]
+    pub opened_at: OffsetDateTime,
+    pub repaid: i64,
+    /// Left to pay according to the whole schedule
+    pub outstanding: i64,
+    /// Interest of installments, which are already due
+    pub accrued_interest: i64,
+    /// Due, but not paid amount
+    pub overdue: i64,
+    pub installments: Vec<Installment>,
+}
+
+impl Loan {
+    pub fn validate(&self) -> Result<(), BankOperationError> {
+        if self.principal <= 0 {
+            return Err(BankOperationError::BadOperation(
+                "Loan principal should be positive".to_string(),
+            ));
+        }
+        if self.interest_rate.is_sign_negative()
+            || self.interest_rate > MAX_INTEREST_RATE
+        {
+            return Err(BankOperationError::BadOperation(format!(
+                "Loan interest rate should be from 0 to {MAX_INTEREST_RATE}"
+            )));
+        }
+        if self.term == 0 || self.term > MAX_TERM {
+            return Err(BankOperationError::BadOperation(format!(
+                "Loan term should be from 1 to {MAX_TERM} periods"
+            )));
+        }
+        if !self.period.is_positive() || self.period > MAX_PERIOD {
+            return Err(BankOperationError::BadOperation(format!(
+                "Loan period should be positive and not longer than {} secs",
+                MAX_PERIOD.whole_seconds()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Total amount to be repaid, including interest
+    pub fn total_due(&self) -> Result<i64, BankOperationError> {
+        sum_amounts(&self.installments()?)
+    }
+
+    pub fn outstanding(&self) -> Result<i64, BankOperationError> {
+        self.total_due()?
+            .checked_sub(self.repaid)
+            .ok_or_else(overflow_err)
+    }
+
+    pub fn schedule(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<LoanSchedule, BankOperationError> {
+        let mut installments = self.installments()?;
+        let mut repaid = self.repaid;
+        let mut accrued_interest = 0;
+        let mut overdue = 0;
+        for installment in installments.iter_mut() {
+            installment.paid = installment.amount.min(repaid);
+            repaid -= installment.paid;
+            if installment.due_date <= now {
+                accrued_interest += installment.interest;
+                if installment.paid < installment.amount {
+                    installment.overdue = true;
+                    overdue += installment.amount - installment.paid;
+                }
+            }
+        }
+        let total_due = sum_amounts(&installments)?;
+
+        Ok(LoanSchedule {
+            id: self.id,
+            card_number: self.card_number.clone(),
+            principal: self.principal,
+            interest_rate: self.interest_rate,
+            term: self.term,
+            period_secs: self.period.whole_seconds(),
+            opened_at: self.opened_at,
+            repaid: self.repaid,
+            outstanding: total_due
+                .checked_sub(self.repaid)
+                .ok_or_else(overflow_err)?,
+            accrued_interest,
+            overdue,
+            installments,
+        })
+    }
+
+    /// Equal installments, interest is charged on the principal left
+    fn installments(&self) -> Result<Vec<Installment>, BankOperationError> {
+        // Loans, stored before the limits, are checked too
+        self.validate()?;
+
+        let convert_err = || {
+            BankOperationError::BadOperation(
+                "Can't convert money correctly".to_string(),
+            )
+        };
+        let principal =
+            Decimal::from_i64(self.principal).ok_or_else(convert_err)?;
+        let term = Decimal::from_u32(self.term).ok_or_else(convert_err)?;
+        let rate = self.interest_rate;
+
+        let payment = if rate.is_zero() {
+            principal / term
+        } else {
+            let mut growth = Decimal::ONE;
+            for _ in 0..self.term {
+                growth = growth
+                    .checked_mul(Decimal::ONE + rate)
+                    .ok_or_else(overflow_err)?;
+            }
+            principal
+                .checked_mul(rate)
+                .and_then(|p| p.checked_mul(growth))
+                .and_then(|p| p.checked_div(growth - Decimal::ONE))
+                .ok_or_else(overflow_err)?
+        };
+        let payment = payment.round().to_i64().ok_or_else(convert_err)?;
+
+        let mut left = self.principal;
+        let mut installments = Vec::with_capacity(self.term as usize);
+        for number in 1..=self.term {
+            let left_dec = Decimal::from_i64(left).ok_or_else(convert_err)?;
+            let interest = left_dec
+                .checked_mul(rate)
+                .ok_or_else(overflow_err)?
+                .round()
+                .to_i64()
+                .ok_or_else(convert_err)?;
+            // The last installment takes the rounding remainder
+            let principal = if number == self.term {
+                left
+            } else {
+                payment
+                    .checked_sub(interest)
+                    .ok_or_else(overflow_err)?
+                    .clamp(0, left)
+            };
+            left -= principal;
+            let due_date = i32::try_from(number)
+                .ok()
+                .and_then(|number| self.period.checked_mul(number))
+                .and_then(|offset| self.opened_at.checked_add(offset))
+                .ok_or_else(overflow_err)?;
+            installments.push(Installment {
+                number,
+                due_date,
+                principal,
+                interest,
+                amount: principal
+                    .checked_add(interest)
+                    .ok_or_else(overflow_err)?,
+                paid: 0,
+                overdue: false,
+            });
+        }
+        Ok(installments)
+    }
+}
+
+fn overflow_err() -> BankOperationError {
+    BankOperationError::BadOperation("Loan amounts are too large".to_string())
+}
+
+fn sum_amounts(
+    installments: &[Installment],
+) -> Result<i64, BankOperationError> {
+    installments
+        .iter()
+        .try_fold(0i64, |sum, i| sum.checked_add(i.amount))
+        .ok_or_else(overflow_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CardSettings;
+
+    fn make_loan(principal: i64, rate: Decimal, term: u32) -> Loan {
+        Loan {
+            id: Uuid::new_v4(),
+            card_number: CardNumber::generate(&CardSettings::default()),
+            principal,
+            interest_rate: rate,
+            term,
+            period: Duration::days(30),
+            opened_at: OffsetDateTime::now_utc(),
+            repaid: 0,
+        }
+    }
+
+    #[test]
+    fn installments_repay_whole_principal() {
+        let loan = make_loan(1000, Decimal::new(1, 2), 12);
+        let installments = loan.installments().unwrap();
+        assert_eq!(installments.len(), 12);
+        let principal: i64 = installments.iter().map(|i| i.principal).sum();
+        assert_eq!(principal, 1000);
+        assert_eq!(installments[0].interest, 10);
+        // Equal payments, except for the last one with the remainder
+        assert!(installments[..11].iter().all(|i| i.amount == 89));
+
+        let loan = make_loan(1000, Decimal::ZERO, 3);
+        let amounts: Vec<_> = loan
+            .installments()
+            .unwrap()
+            .iter()
+            .map(|i| i.amount)
+            .collect();
+        assert_eq!(amounts, vec![333, 333, 334]);
+    }
+
+    #[test]
+    fn schedule_accrues_interest_over_time() {
+        let mut loan = make_loan(1000, Decimal::new(1, 2), 12);
+        let schedule = loan.schedule(loan.opened_at).unwrap();
+        assert_eq!(schedule.accrued_interest, 0);
+        assert_eq!(schedule.overdue, 0);
+
+        loan.repaid = 89;
+        let later = loan.opened_at + Duration::days(61);
+        let schedule = loan.schedule(later).unwrap();
+        assert_eq!(schedule.accrued_interest, 10 + 9);
+        assert_eq!(schedule.overdue, 89);
+        assert!(!schedule.installments[0].overdue);
+        assert!(schedule.installments[1].overdue);
+    }
+
+    #[test]
+    fn huge_loans_are_rejected() {
+        assert!(make_loan(1000, Decimal::new(1, 2), MAX_TERM + 1)
+            .validate()
+            .is_err());
+        assert!(make_loan(1000, Decimal::TWO, 12).validate().is_err());
+        let mut loan = make_loan(1000, Decimal::new(1, 2), 12);
+        loan.period = Duration::days(10_000);
+        assert!(loan.validate().is_err());
+
+        // Overflows are errors, not panics
+        let loan = make_loan(i64::MAX, MAX_INTEREST_RATE, MAX_TERM);
+        assert!(loan.validate().is_ok());
+        assert!(loan.schedule(loan.opened_at).is_err());
+        let mut loan = make_loan(1000, Decimal::new(1, 2), 12);
+        loan.opened_at = OffsetDateTime::now_utc().replace_year(9999).unwrap();
+        assert!(loan.schedule(loan.opened_at).is_err());
+    }
+}
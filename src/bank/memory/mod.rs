@@ -0,0 +1,1660 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::async_trait;
+use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use rust_decimal::Decimal;
+use secrecy::{ExposeSecret, Secret};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::watch::{Receiver, Sender};
+use tokio::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+use crate::config::CardSettings;
+use crate::domain::card_number::CardNumber;
+use crate::domain::requests::system_api::{Direction, StatementRequest};
+use crate::domain::responses::system_api::StatementResponse;
+use crate::middleware::Credentials;
+use crate::Settings;
+
+use super::audit::{self, AuditReport, AuditedAccount, AuditedToken};
+use super::backend::{BankDataBackend, InitBankDataBackend};
+use super::dump::BankDump;
+use super::loan::Loan;
+use super::pg::{argon2_obj, verify_password_hash_blocking};
+use super::split;
+use super::transfer_order::{OrderExecution, OrderSchedule, TransferOrder};
+use super::{
+    check_fee, check_refund_fee, generate_cvv, generate_token, Account,
+    AccountStatus, BankOperationError, CardExpiry, CardToken, Hold,
+    IdempotencyStatus, IdempotentResponse, IssuedCard, Transaction,
+    TransactionKind, HOLD_LIFETIME,
+};
+
+use self::snapshot::Snapshot;
+
+mod snapshot;
+
+#[derive(Debug)]
+pub struct MemoryStorage {
+    inner: Mutex<Inner>,
+    /// Held for the whole snapshot save, so concurrent saves can't
+    /// replace a newer snapshot with an older one
+    saving: Mutex<()>,
+    settings: Settings,
+}
+
+#[derive(Debug)]
+struct IdempotencyRecord {
+    request_hash: String,
+    response: Option<IdempotentResponse>,
+}
+
+/// Memory storage keeps secrets as is, imported ones are
+/// known only by their hashes
+#[derive(Clone, Debug)]
+enum Credential {
+    Plain(Secret<String>),
+    Hash(Secret<String>),
+}
+
+impl Credential {
+    async fn verify(
+        &self,
+        candidate: &Secret<String>,
+    ) -> Result<(), BankOperationError> {
+        match self {
+            Credential::Plain(secret) => {
+                if secret.expose_secret().eq(candidate.expose_secret()) {
+                    Ok(())
+                } else {
+                    Err(BankOperationError::NotAuthorized)
+                }
+            }
+            Credential::Hash(hash) => verify_password_hash_blocking(
+                hash.clone(),
+                candidate.clone(),
+                argon2_obj(),
+            )
+            .await
+            .map_err(|_| BankOperationError::NotAuthorized),
+        }
+    }
+}
+
+/// Expiry date and CVV of the account card
+#[derive(Clone, Debug)]
+struct CardDetails {
+    expiry: CardExpiry,
+    cvv: Credential,
+}
+
+/// Last status transition of the account
+#[derive(Debug)]
+struct StatusRecord {
+    status: AccountStatus,
+    reason: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Inner {
+    tokens: HashMap<String, CardToken>,
+    accounts: Vec<Account>,
+    transactions: Vec<Transaction>,
+    holds: Vec<Hold>,
+    loans: Vec<Loan>,
+    transfer_orders: Vec<TransferOrder>,
+    overdraft_limits: HashMap<CardNumber, i64>,
+    statuses: HashMap<CardNumber, StatusRecord>,
+    deleted_at: HashMap<CardNumber, OffsetDateTime>,
+    cards: HashMap<CardNumber, CardDetails>,
+    /// Passwords of imported accounts, `Account` keeps the others
+    password_hashes: HashMap<CardNumber, Secret<String>>,
+    idempotency_keys: HashMap<String, IdempotencyRecord>,
+    // System account
+    emission_account: Account,
+    // Collects acquiring fees
+    revenue_account: Account,
+    // Store account of each terminal
+    stores: HashMap<Uuid, Account>,
+    card_settings: CardSettings,
+    notifier: Sender<()>,
+}
+
+impl MemoryStorage {
+    async fn lock(&self) -> MutexGuard<Inner> {
+        self.inner.lock().await
+    }
+
+    /// Save snapshot with the given period, while storage is alive
+    fn run_snapshots(storage: &Arc<MemoryStorage>, interval_secs: u64) {
+        let storage = Arc::downgrade(storage);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(
+                std::time::Duration::from_secs(interval_secs),
+            );
+            let mut notifier = match storage.upgrade() {
+                Some(storage) => storage.subscribe().await,
+                None => return,
+            };
+            loop {
+                interval.tick().await;
+                let Some(storage) = storage.upgrade() else {
+                    break;
+                };
+                if !notifier.has_changed().unwrap_or(false) {
+                    continue;
+                }
+                notifier.mark_unchanged();
+                if let Err(e) = storage.save_snapshot().await {
+                    tracing::error!("Failed to save memory snapshot: {e}");
+                }
+            }
+        });
+    }
+
+    fn balance(&self, guard: &MutexGuard<Inner>, account: &Account) -> i64 {
+        let balance = guard
+            .transactions
+            .iter()
+            .filter(|&transaction| {
+                transaction.sender.eq(&account)
+                    || transaction.recipient.eq(&account)
+            })
+            .fold(0i64, |amount, transaction| {
+                if transaction.sender.eq(&account) {
+                    amount - transaction.amount
+                } else {
+                    amount + transaction.amount
+                }
+            });
+        balance
+    }
+
+    /// Balance without funds reserved by holds
+    fn available_balance(
+        &self,
+        guard: &MutexGuard<Inner>,
+        account: &Account,
+    ) -> i64 {
+        let now = OffsetDateTime::now_utc();
+        let held: i64 = guard
+            .holds
+            .iter()
+            .filter(|hold| hold.card_number.eq(&account.card_number))
+            .filter(|hold| !hold.is_expired(now))
+            .map(|hold| hold.amount)
+            .sum();
+        self.balance(guard, account) - held
+    }
+
+    fn overdraft_limit(&self, guard: &MutexGuard<Inner>, acc: &Account) -> i64 {
+        guard
+            .overdraft_limits
+            .get(&acc.card_number)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn status(
+        &self,
+        guard: &MutexGuard<Inner>,
+        acc: &Account,
+    ) -> AccountStatus {
+        guard
+            .statuses
+            .get(&acc.card_number)
+            .map(|record| record.status)
+            .unwrap_or_default()
+    }
+
+    /// Generate expiry date, if it is not set, and CVV for the card
+    fn issue_card(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> IssuedCard {
+        let expiry = expiry.unwrap_or_else(|| {
+            CardExpiry::after_months(
+                OffsetDateTime::now_utc(),
+                guard.card_settings.validity_months,
+            )
+        });
+        let cvv = generate_cvv();
+        guard.cards.insert(
+            card.clone(),
+            CardDetails {
+                expiry,
+                cvv: Credential::Plain(cvv.clone()),
+            },
+        );
+        IssuedCard {
+            card_number: card.clone(),
+            expiry,
+            cvv,
+        }
+    }
+
+    /// Account can go negative down to it's overdraft limit
+    fn check_funds(
+        &self,
+        guard: &MutexGuard<Inner>,
+        account: &Account,
+        amount: i64,
+    ) -> Result<(), BankOperationError> {
+        let limit = self.overdraft_limit(guard, account);
+        if self.available_balance(guard, account) + limit >= amount {
+            Ok(())
+        } else if limit > 0 {
+            Err(BankOperationError::OverdraftLimitExceeded)
+        } else {
+            Err(BankOperationError::NotEnoughFunds)
+        }
+    }
+
+    fn account_holds(
+        &self,
+        guard: &MutexGuard<Inner>,
+        acc: &Account,
+    ) -> Vec<Hold> {
+        let now = OffsetDateTime::now_utc();
+        guard
+            .holds
+            .iter()
+            .filter(|hold| hold.card_number.eq(&acc.card_number))
+            .filter(|hold| !hold.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    fn take_hold(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        hold: Uuid,
+    ) -> Result<Hold, BankOperationError> {
+        let now = OffsetDateTime::now_utc();
+        guard.holds.retain(|h| !h.is_expired(now));
+        let idx = guard
+            .holds
+            .iter()
+            .position(|h| h.id.eq(&hold))
+            .ok_or(BankOperationError::HoldNotFound)?;
+        Ok(guard.holds.remove(idx))
+    }
+
+    fn transaction(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let sender = self.find_account(guard, sender)?;
+        let recipient = self.find_account(guard, recipient)?;
+
+        if sender == recipient {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        self.status(guard, &sender).check_can_send()?;
+        self.status(guard, &recipient).check_can_receive()?;
+        self.check_funds(guard, &sender, amount)?;
+
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: sender.clone(),
+            recipient: recipient.clone(),
+            amount,
+            kind: TransactionKind::Transfer,
+            datetime: OffsetDateTime::now_utc(),
+        };
+        let id = transaction.id;
+
+        guard.transactions.push(transaction);
+        Ok(id)
+    }
+
+    /// Payer should afford the whole `amount`, recipients get it
+    /// without the acquiring `fee`
+    fn check_payment(
+        &self,
+        guard: &MutexGuard<Inner>,
+        payer: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<i64, BankOperationError> {
+        check_fee(amount, fee)?;
+        let payer = self.find_account(guard, payer)?;
+        self.check_funds(guard, &payer, amount)?;
+        Ok(amount - fee)
+    }
+
+    /// Move acquiring fee from the payer to the bank revenue account
+    fn fee_transaction(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        payer: &CardNumber,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: self.find_account(guard, payer)?,
+            recipient: guard.revenue_account.clone(),
+            amount: fee,
+            kind: TransactionKind::AcquiringFee,
+            datetime: OffsetDateTime::now_utc(),
+        };
+        guard.transactions.push(transaction);
+        Ok(())
+    }
+
+    /// Bank revenue account should afford returned acquiring `fee`,
+    /// the rest of the `amount` is returned by recipients of the payment
+    fn check_refund(
+        &self,
+        guard: &MutexGuard<Inner>,
+        payer: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<i64, BankOperationError> {
+        check_refund_fee(amount, fee)?;
+        let payer = self.find_account(guard, payer)?;
+        self.status(guard, &payer).check_can_receive()?;
+        self.check_funds(guard, &guard.revenue_account, fee)?;
+        Ok(amount - fee)
+    }
+
+    /// Return acquiring fee from the bank revenue account to the payer
+    fn fee_refund_transaction(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        payer: &CardNumber,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: guard.revenue_account.clone(),
+            recipient: self.find_account(guard, payer)?,
+            amount: fee,
+            kind: TransactionKind::FeeRefund,
+            datetime: OffsetDateTime::now_utc(),
+        };
+        guard.transactions.push(transaction);
+        Ok(())
+    }
+
+    fn split_transaction(
+        &self,
+        guard: &mut MutexGuard<Inner>,
+        sender: &CardNumber,
+        amount: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<Vec<Uuid>, BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+        let mut bfc = Vec::with_capacity(beneficiaries.count());
+        for (token, amount) in split::split_amount(amount, beneficiaries)? {
+            let acc = self.get_account_by_token(guard, token)?;
+            self.status(guard, &acc).check_can_receive()?;
+            bfc.push((acc, amount));
+        }
+
+        if bfc
+            .iter()
+            .map(|(acc, _)| acc)
+            .find(|acc| acc.card_number.eq(&sender))
+            .is_some()
+        {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        let sender = self.find_account(guard, sender)?;
+        self.status(guard, &sender).check_can_send()?;
+        self.check_funds(guard, &sender, amount)?;
+
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        let mut ids = Vec::with_capacity(bfc.len());
+        for (recipient, amount) in bfc.into_iter() {
+            let transaction = Transaction {
+                id: Uuid::new_v4(),
+                sender: sender.clone(),
+                recipient: recipient.clone(),
+                amount,
+                kind: TransactionKind::Transfer,
+                datetime: OffsetDateTime::now_utc(),
+            };
+            ids.push(transaction.id);
+            guard.transactions.push(transaction);
+        }
+        Ok(ids)
+    }
+
+    fn account_transactions(
+        &self,
+        guard: &MutexGuard<Inner>,
+        acc: &Account,
+    ) -> Vec<Transaction> {
+        guard
+            .transactions
+            .iter()
+            .filter(|&transaction| {
+                transaction.sender.eq(&acc) || transaction.recipient.eq(&acc)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// I want to notify my subscribers to update their accounts info
+    /// after every bank lock
+    fn notify(&self, guard: &MutexGuard<Inner>) {
+        if let Err(e) = guard.notifier.send(()) {
+            tracing::error!("Failed to send bank lock notification: {e}");
+        }
+    }
+
+    fn find_account(
+        &self,
+        guard: &MutexGuard<Inner>,
+        card: &CardNumber,
+    ) -> Result<Account, BankOperationError> {
+        let account = guard
+            .accounts
+            .iter()
+            .find(|&acc| acc.card_number.eq(card))
+            .or_else(|| {
+                guard.stores.values().find(|&acc| acc.card_number.eq(card))
+            })
+            .ok_or(BankOperationError::AccountNotFound)?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
+        }
+        Ok(account.clone())
+    }
+
+    fn get_account_by_token(
+        &self,
+        guard: &MutexGuard<Inner>,
+        token: &str,
+    ) -> Result<Account, BankOperationError> {
+        let token = guard
+            .tokens
+            .get(token)
+            .ok_or(BankOperationError::TokenNotFound)?;
+        token.check_active(OffsetDateTime::now_utc())?;
+        self.find_account(guard, &token.card_number)
+    }
+}
+
+impl Inner {
+    fn password(&self, acc: &Account) -> Credential {
+        match self.password_hashes.get(&acc.card_number) {
+            Some(hash) => Credential::Hash(hash.clone()),
+            None => Credential::Plain(acc.password.clone()),
+        }
+    }
+
+    /// Empty storage with new system accounts
+    fn new(settings: &Settings, tx: Sender<()>) -> Self {
+        let emission_account = Account {
+            card_number: CardNumber::generate(&settings.card_settings),
+            password: settings.terminal_settings.password.clone(),
+            is_existing: true,
+            username: settings.bank_username.clone(),
+        };
+        let revenue_account = Account {
+            card_number: CardNumber::generate(&settings.card_settings),
+            password: settings.terminal_settings.password.clone(),
+            is_existing: true,
+            username: settings.revenue_username(),
+        };
+
+        let stores = settings
+            .all_terminals()
+            .map(|terminal| {
+                let store_account = Account {
+                    card_number: CardNumber::generate(&settings.card_settings),
+                    password: terminal.password.clone(),
+                    is_existing: true,
+                    username: settings.store_username(terminal),
+                };
+                (terminal.terminal_key, store_account)
+            })
+            .collect();
+
+        Inner {
+            tokens: HashMap::new(),
+            accounts: Vec::new(),
+            emission_account,
+            revenue_account,
+            stores,
+            card_settings: settings.card_settings.clone(),
+            transactions: Vec::new(),
+            holds: Vec::new(),
+            loans: Vec::new(),
+            transfer_orders: Vec::new(),
+            overdraft_limits: HashMap::new(),
+            statuses: HashMap::new(),
+            deleted_at: HashMap::new(),
+            cards: HashMap::new(),
+            password_hashes: HashMap::new(),
+            idempotency_keys: HashMap::new(),
+            notifier: tx,
+        }
+    }
+}
+
+impl InitBankDataBackend for MemoryStorage {
+    fn new(
+        settings: &Settings,
+        tx: Sender<()>,
+    ) -> Arc<dyn BankDataBackend + Send + Sync> {
+        let snapshot = settings.memory_settings.as_ref().and_then(|s| {
+            Snapshot::load(&s.snapshot_path)
+                .expect("Failed to load memory storage snapshot")
+        });
+        let inner = match snapshot {
+            Some(snapshot) => {
+                tracing::info!("Memory storage is restored from snapshot");
+                snapshot
+                    .restore(settings, tx)
+                    .expect("Failed to restore memory storage snapshot")
+            }
+            None => Inner::new(settings, tx),
+        };
+
+        let storage = Arc::new(MemoryStorage {
+            inner: Mutex::new(inner),
+            saving: Mutex::new(()),
+            settings: settings.clone(),
+        });
+        if let Some(ref memory_settings) = settings.memory_settings {
+            MemoryStorage::run_snapshots(
+                &storage,
+                memory_settings.snapshot_interval_secs,
+            );
+        }
+        storage
+    }
+}
+
+#[async_trait]
+impl BankDataBackend for MemoryStorage {
+    async fn subscribe(&self) -> Receiver<()> {
+        let guard = self.lock().await;
+
+        guard.notifier.subscribe()
+    }
+
+    /// Validate system account credentials
+    async fn authorize_system(
+        &self,
+        credentials: Credentials,
+    ) -> Result<(), BankOperationError> {
+        let guard = self.lock().await;
+
+        let bank_username = &guard.emission_account.username;
+        let password = guard.emission_account.password.expose_secret();
+        if bank_username.eq(&credentials.username)
+            && password.eq(credentials.password.expose_secret())
+        {
+            Ok(())
+        } else {
+            Err(BankOperationError::NotAuthorized)
+        }
+    }
+
+    /// Add a new account
+    async fn add_account(
+        &self,
+        username: &str,
+        password: &Secret<String>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = Account {
+            card_number: CardNumber::generate(&guard.card_settings),
+            is_existing: true,
+            password: password.clone(),
+            username: username.to_string(),
+        };
+        guard.accounts.push(account.clone());
+        let card = self.issue_card(&mut guard, &account.card_number, None);
+
+        self.notify(&guard);
+        Ok(card)
+    }
+
+    async fn reissue_card(
+        &self,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        if !guard.accounts.contains(&account) {
+            return Err(BankOperationError::BadOperation(
+                "Can't issue card for store account".to_string(),
+            ));
+        }
+        let card = self.issue_card(&mut guard, &account.card_number, expiry);
+
+        self.notify(&guard);
+        Ok(card)
+    }
+
+    async fn card_expiry(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Option<CardExpiry>, BankOperationError> {
+        let guard = self.lock().await;
+
+        let _ = self.find_account(&guard, card)?;
+        Ok(guard.cards.get(card).map(|details| details.expiry))
+    }
+
+    /// Mark existing account as deleted
+    async fn delete_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let result = match guard
+            .accounts
+            .iter_mut()
+            .find(|acc| acc.card_number.eq(&card))
+        {
+            Some(acc) => {
+                if acc.is_existing {
+                    acc.is_existing = false;
+                    guard
+                        .deleted_at
+                        .insert(card.clone(), OffsetDateTime::now_utc());
+                    Ok(())
+                } else {
+                    Err(BankOperationError::AccountIsDeleted)
+                }
+            }
+            None => {
+                if guard.stores.values().any(|acc| acc.card_number.eq(&card)) {
+                    Err(BankOperationError::BadOperation(
+                        "Can't delete store account".to_string(),
+                    ))
+                } else {
+                    Err(BankOperationError::AccountNotFound)
+                }
+            }
+        };
+
+        self.notify(&guard);
+        result
+    }
+
+    /// Restore account, marked as deleted
+    async fn restore_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let acc = guard
+            .accounts
+            .iter_mut()
+            .find(|acc| acc.card_number.eq(card))
+            .ok_or(BankOperationError::AccountNotFound)?;
+        if acc.is_existing {
+            return Err(BankOperationError::BadOperation(
+                "Account is not deleted".to_string(),
+            ));
+        }
+        acc.is_existing = true;
+        guard.deleted_at.remove(card);
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn set_account_status(
+        &self,
+        card: &CardNumber,
+        status: AccountStatus,
+        reason: Option<&str>,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        if guard.stores.values().any(|acc| acc.eq(&account)) {
+            return Err(BankOperationError::BadOperation(
+                "Can't change status of store account".to_string(),
+            ));
+        }
+        guard.statuses.insert(
+            account.card_number,
+            StatusRecord {
+                status,
+                reason: reason.map(str::to_string),
+            },
+        );
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    /// Get Vec<Account>
+    async fn list_accounts(
+        &self,
+    ) -> Result<
+        Vec<crate::domain::responses::system_api::Account>,
+        BankOperationError,
+    > {
+        let guard = self.lock().await;
+
+        let now = OffsetDateTime::now_utc();
+        let mut accounts = Vec::new();
+        for acc in guard.accounts.iter() {
+            let tokens = guard
+                .tokens
+                .values()
+                .filter(|token| token.card_number.eq(&acc.card_number))
+                .filter(|token| token.check_active(now).is_ok())
+                .map(|token| token.token.clone())
+                .collect();
+            let balance = self.balance(&guard, acc);
+            let status = guard.statuses.get(&acc.card_number);
+            accounts.push(crate::domain::responses::system_api::Account {
+                card_number: acc.card_number.clone(),
+                balance,
+                overdraft_limit: self.overdraft_limit(&guard, acc),
+                debt: (-balance).max(0),
+                transactions: self.account_transactions(&guard, acc),
+                holds: self.account_holds(&guard, acc),
+                exists: acc.is_existing,
+                status: status.map(|s| s.status).unwrap_or_default(),
+                status_reason: status.and_then(|s| s.reason.clone()),
+                tokens,
+                username: acc.username.clone(),
+            })
+        }
+        Ok(accounts)
+    }
+
+    async fn authorize_account(
+        &self,
+        card: &CardNumber,
+        password: &Secret<String>,
+    ) -> Result<Account, BankOperationError> {
+        let guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        let credential = guard.password(&account);
+        drop(guard);
+
+        credential.verify(password).await?;
+        Ok(account)
+    }
+
+    async fn authorize_card(
+        &self,
+        card: &CardNumber,
+        password: &Secret<String>,
+        expiry: CardExpiry,
+        cvv: &Secret<String>,
+    ) -> Result<Account, BankOperationError> {
+        let guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        let credential = guard.password(&account);
+        let details = guard.cards.get(card).cloned();
+        drop(guard);
+
+        credential.verify(password).await?;
+        let details = details.ok_or(BankOperationError::BadOperation(
+            "Card details are not issued".to_string(),
+        ))?;
+        if details.expiry != expiry {
+            return Err(BankOperationError::InvalidExpiry);
+        }
+        if details.expiry.is_expired(OffsetDateTime::now_utc()) {
+            return Err(BankOperationError::CardExpired);
+        }
+        details
+            .cvv
+            .verify(cvv)
+            .await
+            .map_err(|_| BankOperationError::InvalidCvv)?;
+        Ok(account)
+    }
+
+    async fn find_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Account, BankOperationError> {
+        let guard = self.lock().await;
+
+        self.find_account(&guard, card)
+    }
+
+    async fn account_status(
+        &self,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError> {
+        let guard = self.lock().await;
+
+        let acc = self.find_account(&guard, card)?;
+        Ok(self.status(&guard, &acc))
+    }
+
+    async fn get_store_account(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<Account, BankOperationError> {
+        let guard = self.lock().await;
+
+        guard
+            .stores
+            .get(terminal_key)
+            .cloned()
+            .ok_or(BankOperationError::TerminalNotFound)
+    }
+
+    async fn store_balance(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<i64, BankOperationError> {
+        let guard = self.lock().await;
+
+        let store_acc = guard
+            .stores
+            .get(terminal_key)
+            .ok_or(BankOperationError::TerminalNotFound)?;
+        Ok(self.balance(&guard, store_acc))
+    }
+
+    async fn balance(
+        &self,
+        card: &CardNumber,
+    ) -> Result<i64, BankOperationError> {
+        let guard = self.lock().await;
+
+        let acc = self.find_account(&guard, card)?;
+        Ok(self.balance(&guard, &acc))
+    }
+
+    async fn new_transaction(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let id = self.transaction(&mut guard, sender, recipient, amount)?;
+
+        self.notify(&guard);
+        Ok(id)
+    }
+
+    async fn new_payment(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let net = self.check_payment(&guard, sender, amount, fee)?;
+        let id = self.transaction(&mut guard, sender, recipient, net)?;
+        self.fee_transaction(&mut guard, sender, fee)?;
+
+        self.notify(&guard);
+        Ok(id)
+    }
+
+    async fn new_split_transaction(
+        &self,
+        sender: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<Vec<Uuid>, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let net = self.check_payment(&guard, sender, amount, fee)?;
+        let ids =
+            self.split_transaction(&mut guard, sender, net, beneficiaries)?;
+        self.fee_transaction(&mut guard, sender, fee)?;
+
+        self.notify(&guard);
+        Ok(ids)
+    }
+
+    async fn new_refund(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let net = self.check_refund(&guard, recipient, amount, fee)?;
+        if net > 0 {
+            self.transaction(&mut guard, sender, recipient, net)?;
+        }
+        self.fee_refund_transaction(&mut guard, recipient, fee)?;
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn new_split_refund_transaction(
+        &self,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+        let net = self.check_refund(&guard, recipient, amount, fee)?;
+        let recipient = self.find_account(&guard, recipient)?;
+
+        // Each beneficiary returns it's part of the refunded amount,
+        // check all of them before applying any transaction
+        let mut transactions = Vec::with_capacity(beneficiaries.count());
+        for (token, amount) in split::split_amount(net, beneficiaries)? {
+            let sender = self.get_account_by_token(&guard, token)?;
+            if sender == recipient {
+                return Err(BankOperationError::BadTransaction);
+            }
+            self.status(&guard, &sender).check_can_send()?;
+            self.check_funds(&guard, &sender, amount)?;
+            transactions.push(Transaction {
+                id: Uuid::new_v4(),
+                sender,
+                recipient: recipient.clone(),
+                amount,
+                kind: TransactionKind::Transfer,
+                datetime: OffsetDateTime::now_utc(),
+            });
+        }
+
+        guard.transactions.extend(transactions);
+        self.fee_refund_transaction(&mut guard, &recipient.card_number, fee)?;
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn new_hold(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        self.status(&guard, &account).check_can_send()?;
+
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        self.check_funds(&guard, &account, amount)?;
+
+        let now = OffsetDateTime::now_utc();
+        let hold = Hold {
+            id: Uuid::new_v4(),
+            card_number: account.card_number,
+            amount,
+            datetime: now,
+            expires_at: now + HOLD_LIFETIME,
+        };
+        let id = hold.id;
+        guard.holds.push(hold);
+
+        self.notify(&guard);
+        Ok(id)
+    }
+
+    async fn release_hold(&self, hold: Uuid) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let _ = self.take_hold(&mut guard, hold)?;
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn release_all_holds(&self) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        guard.holds.clear();
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn capture_hold(
+        &self,
+        hold: Uuid,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let hold = self.take_hold(&mut guard, hold)?;
+        let payer = &hold.card_number;
+        let result = if amount > hold.amount {
+            Err(BankOperationError::BadTransaction)
+        } else {
+            self.check_payment(&guard, payer, amount, fee)
+                .and_then(|net| {
+                    self.transaction(&mut guard, payer, recipient, net)
+                })
+                .and_then(|_| self.fee_transaction(&mut guard, payer, fee))
+        };
+        // Keep funds reserved if capture failed
+        if result.is_err() {
+            guard.holds.push(hold);
+        }
+
+        self.notify(&guard);
+        result
+    }
+
+    async fn capture_split_hold(
+        &self,
+        hold: Uuid,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let hold = self.take_hold(&mut guard, hold)?;
+        let payer = &hold.card_number;
+        let result = if amount > hold.amount {
+            Err(BankOperationError::BadTransaction)
+        } else {
+            self.check_payment(&guard, payer, amount, fee)
+                .and_then(|net| {
+                    self.split_transaction(
+                        &mut guard,
+                        payer,
+                        net,
+                        beneficiaries,
+                    )
+                })
+                .and_then(|_| self.fee_transaction(&mut guard, payer, fee))
+        };
+        // Keep funds reserved if capture failed
+        if result.is_err() {
+            guard.holds.push(hold);
+        }
+
+        self.notify(&guard);
+        result
+    }
+
+    async fn open_credit(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = self.find_account(&guard, &card)?.clone();
+        self.status(&guard, &account).check_can_receive()?;
+
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: guard.emission_account.clone(),
+            recipient: account,
+            amount,
+            kind: TransactionKind::Transfer,
+            datetime: OffsetDateTime::now_utc(),
+        };
+        let id = transaction.id;
+
+        guard.transactions.push(transaction);
+
+        self.notify(&guard);
+        Ok(id)
+    }
+
+    async fn open_loan(
+        &self,
+        card: &CardNumber,
+        principal: i64,
+        interest_rate: Decimal,
+        term: u32,
+        period: Duration,
+    ) -> Result<Loan, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let account = self.find_account(&guard, card)?;
+        self.status(&guard, &account).check_can_receive()?;
+        let loan = Loan {
+            id: Uuid::new_v4(),
+            card_number: account.card_number.clone(),
+            principal,
+            interest_rate,
+            term,
+            period,
+            opened_at: OffsetDateTime::now_utc(),
+            repaid: 0,
+        };
+        loan.validate()?;
+
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: guard.emission_account.clone(),
+            recipient: account,
+            amount: principal,
+            kind: TransactionKind::Transfer,
+            datetime: loan.opened_at,
+        };
+        guard.transactions.push(transaction);
+        guard.loans.push(loan.clone());
+
+        self.notify(&guard);
+        Ok(loan)
+    }
+
+    async fn repay_loan(
+        &self,
+        id: &Uuid,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let loan = guard
+            .loans
+            .iter()
+            .find(|loan| loan.id.eq(id))
+            .cloned()
+            .ok_or(BankOperationError::LoanNotFound)?;
+
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+        if amount > loan.outstanding()? {
+            return Err(BankOperationError::BadOperation(
+                "Repayment exceeds outstanding loan amount".to_string(),
+            ));
+        }
+
+        let account = self.find_account(&guard, &loan.card_number)?;
+        self.status(&guard, &account).check_can_send()?;
+        self.check_funds(&guard, &account, amount)?;
+
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            sender: account,
+            recipient: guard.emission_account.clone(),
+            amount,
+            kind: TransactionKind::Transfer,
+            datetime: OffsetDateTime::now_utc(),
+        };
+        let transaction_id = transaction.id;
+        guard.transactions.push(transaction);
+        if let Some(loan) = guard.loans.iter_mut().find(|loan| loan.id.eq(id)) {
+            loan.repaid += amount;
+        }
+
+        self.notify(&guard);
+        Ok(transaction_id)
+    }
+
+    async fn get_loan(&self, id: &Uuid) -> Result<Loan, BankOperationError> {
+        let guard = self.lock().await;
+
+        guard
+            .loans
+            .iter()
+            .find(|loan| loan.id.eq(id))
+            .cloned()
+            .ok_or(BankOperationError::LoanNotFound)
+    }
+
+    async fn new_transfer_order(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        schedule: OrderSchedule,
+    ) -> Result<TransferOrder, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        for card in [sender, recipient] {
+            if !self.find_account(&guard, card)?.is_existing {
+                return Err(BankOperationError::AccountIsDeleted);
+            }
+        }
+        let order = TransferOrder::new(
+            sender.clone(),
+            recipient.clone(),
+            amount,
+            schedule,
+            OffsetDateTime::now_utc(),
+        )?;
+        guard.transfer_orders.push(order.clone());
+
+        self.notify(&guard);
+        Ok(order)
+    }
+
+    async fn list_transfer_orders(
+        &self,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let guard = self.lock().await;
+
+        Ok(guard.transfer_orders.clone())
+    }
+
+    async fn cancel_transfer_order(
+        &self,
+        id: &Uuid,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let order = guard
+            .transfer_orders
+            .iter_mut()
+            .find(|order| order.id.eq(id))
+            .ok_or(BankOperationError::OrderNotFound)?;
+        if order.cancelled_at.is_some() {
+            return Err(BankOperationError::BadOperation(
+                "Order is already cancelled".to_string(),
+            ));
+        }
+        order.cancelled_at = Some(OffsetDateTime::now_utc());
+        order.next_run_at = None;
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn due_transfer_orders(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let guard = self.lock().await;
+
+        Ok(guard
+            .transfer_orders
+            .iter()
+            .filter(|order| order.next_run_at.is_some_and(|at| at <= now))
+            .cloned()
+            .collect())
+    }
+
+    async fn run_transfer_order(
+        &self,
+        id: &Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Option<OrderExecution>, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let Some(order) = guard
+            .transfer_orders
+            .iter()
+            .find(|order| {
+                order.id.eq(id) && order.next_run_at.is_some_and(|at| at <= now)
+            })
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let result = self.transaction(
+            &mut guard,
+            &order.sender,
+            &order.recipient,
+            order.amount,
+        );
+        let execution = OrderExecution::new(&result);
+        let order = guard
+            .transfer_orders
+            .iter_mut()
+            .find(|order| order.id.eq(id))
+            .ok_or(BankOperationError::OrderNotFound)?;
+        order.executions.push(execution.clone());
+        order.next_run_at = order.schedule.next_run(now);
+
+        self.notify(&guard);
+        Ok(Some(execution))
+    }
+
+    async fn set_overdraft_limit(
+        &self,
+        card: &CardNumber,
+        limit: i64,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        if limit < 0 {
+            return Err(BankOperationError::BadOperation(
+                "Overdraft limit can't be negative".to_string(),
+            ));
+        }
+        let account = self.find_account(&guard, card)?;
+        guard.overdraft_limits.insert(account.card_number, limit);
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let guard = self.lock().await;
+        Ok(guard.transactions.clone())
+    }
+
+    async fn list_transactions_after(
+        &self,
+        last: Option<Uuid>,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let guard = self.lock().await;
+        // New transactions are at the end
+        let start = last
+            .and_then(|id| guard.transactions.iter().rposition(|t| t.id == id))
+            .map_or(0, |idx| idx + 1);
+        Ok(guard.transactions[start..].to_vec())
+    }
+
+    async fn get_transaction(
+        &self,
+        id: &Uuid,
+    ) -> Result<Transaction, BankOperationError> {
+        let guard = self.lock().await;
+
+        guard
+            .transactions
+            .iter()
+            .find(|transaction| transaction.id.eq(id))
+            .cloned()
+            .ok_or(BankOperationError::TransactionNotFound)
+    }
+
+    async fn account_statement(
+        &self,
+        req: &StatementRequest,
+    ) -> Result<StatementResponse, BankOperationError> {
+        let guard = self.lock().await;
+
+        let acc = self.find_account(&guard, &req.card_number)?;
+        let mut transactions = self.account_transactions(&guard, &acc);
+        // Stable sort, transactions with the same time keep insertion order
+        transactions.sort_by_key(|t| t.datetime);
+
+        let start = match req.cursor {
+            Some(cursor) => {
+                transactions
+                    .iter()
+                    .position(|t| t.id.eq(&cursor))
+                    .ok_or(BankOperationError::TransactionNotFound)?
+                    + 1
+            }
+            None => 0,
+        };
+
+        let limit = req.limit();
+        let page = transactions
+            .into_iter()
+            .skip(start)
+            .filter(|t| {
+                let incoming = t.recipient.eq(&acc);
+                let counterparty =
+                    if incoming { &t.sender } else { &t.recipient };
+                req.from.map_or(true, |from| t.datetime >= from)
+                    && req.to.map_or(true, |to| t.datetime < to)
+                    && req.direction.map_or(true, |direction| {
+                        incoming == (direction == Direction::Incoming)
+                    })
+                    && req
+                        .counterparty
+                        .as_ref()
+                        .map_or(true, |card| counterparty.card_number.eq(card))
+                    && req.min_amount.map_or(true, |min| t.amount >= min)
+                    && req.max_amount.map_or(true, |max| t.amount <= max)
+            })
+            .take(limit + 1)
+            .collect();
+
+        Ok(StatementResponse::from_transactions(page, limit))
+    }
+
+    async fn bank_emission(&self) -> Result<i64, BankOperationError> {
+        let guard = self.lock().await;
+
+        let emission_acc = &guard.emission_account;
+        Ok(self.balance(&guard, emission_acc))
+    }
+
+    async fn bank_revenue(&self) -> Result<i64, BankOperationError> {
+        let guard = self.lock().await;
+
+        let revenue_acc = &guard.revenue_account;
+        Ok(self.balance(&guard, revenue_acc))
+    }
+
+    async fn audit(&self) -> Result<AuditReport, BankOperationError> {
+        let guard = self.lock().await;
+
+        let accounts: Vec<_> = std::iter::once(&guard.emission_account)
+            .chain(std::iter::once(&guard.revenue_account))
+            .chain(guard.stores.values())
+            .chain(guard.accounts.iter())
+            .map(|acc| AuditedAccount {
+                card_number: acc.card_number.clone(),
+                balance: self.balance(&guard, acc),
+                overdraft_limit: self.overdraft_limit(&guard, acc),
+                is_emission: acc.eq(&guard.emission_account),
+                deleted_at: guard.deleted_at.get(&acc.card_number).copied(),
+            })
+            .collect();
+        let tokens: Vec<_> = guard
+            .tokens
+            .values()
+            .map(|token| AuditedToken {
+                token: token.token.clone(),
+                card_number: Some(token.card_number.clone()),
+            })
+            .collect();
+        Ok(audit::audit(&accounts, &guard.transactions, &tokens))
+    }
+
+    async fn new_card_token(
+        &self,
+        card: &CardNumber,
+        expires_at: Option<OffsetDateTime>,
+        terminal_key: Option<Uuid>,
+    ) -> Result<String, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let _ = self.find_account(&guard, card)?;
+        let token = generate_token();
+
+        guard.tokens.insert(
+            token.clone(),
+            CardToken {
+                token: token.clone(),
+                card_number: card.clone(),
+                created_at: OffsetDateTime::now_utc(),
+                expires_at,
+                revoked_at: None,
+                terminal_key,
+            },
+        );
+        self.notify(&guard);
+        Ok(token)
+    }
+
+    async fn revoke_card_token(
+        &self,
+        token: &str,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let token = guard
+            .tokens
+            .get_mut(token)
+            .ok_or(BankOperationError::TokenNotFound)?;
+        if token.revoked_at.is_some() {
+            return Err(BankOperationError::TokenRevoked);
+        }
+        token.revoked_at = Some(OffsetDateTime::now_utc());
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn list_card_tokens(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Vec<CardToken>, BankOperationError> {
+        let guard = self.lock().await;
+
+        let _ = self.find_account(&guard, card)?;
+        let mut tokens: Vec<_> = guard
+            .tokens
+            .values()
+            .filter(|token| token.card_number.eq(card))
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|token| token.created_at);
+        Ok(tokens)
+    }
+
+    async fn get_card_token(
+        &self,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError> {
+        let guard = self.lock().await;
+
+        guard
+            .tokens
+            .get(token)
+            .cloned()
+            .ok_or(BankOperationError::TokenNotFound)
+    }
+
+    async fn get_account_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Account, BankOperationError> {
+        let guard = self.lock().await;
+
+        self.get_account_by_token(&guard, token)
+    }
+
+    async fn begin_idempotent_request(
+        &self,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyStatus, BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let Some(record) = guard.idempotency_keys.get(key) else {
+            guard.idempotency_keys.insert(
+                key.to_string(),
+                IdempotencyRecord {
+                    request_hash: request_hash.to_string(),
+                    response: None,
+                },
+            );
+            return Ok(IdempotencyStatus::New);
+        };
+
+        if !record.request_hash.eq(request_hash) {
+            return Ok(IdempotencyStatus::KeyReused);
+        }
+        match record.response {
+            Some(ref response) => {
+                Ok(IdempotencyStatus::Completed(response.clone()))
+            }
+            None => Ok(IdempotencyStatus::InProgress),
+        }
+    }
+
+    async fn complete_idempotent_request(
+        &self,
+        key: &str,
+        response: &IdempotentResponse,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let record = guard.idempotency_keys.get_mut(key).ok_or(
+            BankOperationError::BadOperation(
+                "Idempotency key is not reserved".to_string(),
+            ),
+        )?;
+        record.response = Some(response.clone());
+        Ok(())
+    }
+
+    async fn abort_idempotent_request(
+        &self,
+        key: &str,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        guard.idempotency_keys.remove(key);
+        Ok(())
+    }
+
+    async fn export_dump(&self) -> Result<BankDump, BankOperationError> {
+        let snapshot = Snapshot::new(&*self.lock().await);
+        snapshot.into_dump().await
+    }
+
+    async fn import_dump(
+        &self,
+        dump: &BankDump,
+    ) -> Result<(), BankOperationError> {
+        let mut guard = self.lock().await;
+
+        let notifier = guard.notifier.clone();
+        *guard = Snapshot::from_dump(dump, &self.settings)?
+            .restore(&self.settings, notifier)?;
+
+        self.notify(&guard);
+        Ok(())
+    }
+
+    async fn save_snapshot(&self) -> Result<(), BankOperationError> {
+        let Some(ref settings) = self.settings.memory_settings else {
+            return Ok(());
+        };
+        let _saving = self.saving.lock().await;
+        let mut snapshot = Snapshot::new(&*self.lock().await);
+        let hashed = snapshot.hash_secrets().await?;
+        self.lock().await.keep_hashes(hashed);
+        snapshot.save(&settings.snapshot_path).await?;
+        tracing::debug!("Memory storage snapshot is saved");
+        Ok(())
+    }
+}
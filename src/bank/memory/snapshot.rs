@@ -0,0 +1,849 @@
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use rust_decimal::Decimal;
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch::Sender;
+use uuid::Uuid;
+
+use crate::domain::card_number::CardNumber;
+use crate::Settings;
+
+use super::super::dump::{
+    AccountRole, BankDump, DumpAccount, DumpCard, DumpToken, DumpTransaction,
+    DUMP_VERSION,
+};
+use super::super::loan::Loan;
+use super::super::pg::{argon2_obj, hash_password_blocking};
+use super::super::transfer_order::{
+    OrderExecution, OrderSchedule, TransferOrder,
+};
+use super::super::{
+    Account, AccountStatus, BankOperationError, CardExpiry, CardToken, Hold,
+    IdempotentResponse, Transaction, TransactionKind, HOLD_LIFETIME,
+};
+use super::{CardDetails, Credential, IdempotencyRecord, Inner, StatusRecord};
+
+/// Bumped on every incompatible change of the snapshot format
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Whole state of the `MemoryStorage`. Transactions, holds and tokens
+/// refer to accounts by card number, account state is kept with
+/// the account itself.
+#[derive(Serialize, Deserialize)]
+pub(super) struct Snapshot {
+    version: u32,
+    emission_account: SnapshotAccount,
+    revenue_account: SnapshotAccount,
+    stores: HashMap<Uuid, SnapshotAccount>,
+    accounts: Vec<SnapshotAccount>,
+    tokens: Vec<SnapshotToken>,
+    transactions: Vec<SnapshotTransaction>,
+    holds: Vec<SnapshotHold>,
+    loans: Vec<SnapshotLoan>,
+    transfer_orders: Vec<SnapshotTransferOrder>,
+    idempotency_keys: Vec<SnapshotIdempotencyKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotAccount {
+    username: String,
+    card_number: CardNumber,
+    /// Memory storage keeps passwords as is, the file gets only
+    /// the hash, so it's empty there
+    #[serde(default)]
+    password: String,
+    #[serde(default)]
+    password_hash: Option<String>,
+    is_existing: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    deleted_at: Option<OffsetDateTime>,
+    overdraft_limit: i64,
+    status: AccountStatus,
+    status_reason: Option<String>,
+    card: Option<SnapshotCard>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotCard {
+    expiry: CardExpiry,
+    /// Empty in the file, like the password
+    #[serde(default)]
+    cvv: String,
+    #[serde(default)]
+    cvv_hash: Option<String>,
+}
+
+/// Secrets, hashed for the file: card number, plain secret and hash
+#[derive(Default)]
+pub(super) struct HashedSecrets {
+    passwords: Vec<(CardNumber, String, String)>,
+    cvvs: Vec<(CardNumber, String, String)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotToken {
+    token: String,
+    card_number: CardNumber,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    revoked_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    terminal_key: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotTransaction {
+    id: Uuid,
+    sender: CardNumber,
+    recipient: CardNumber,
+    amount: i64,
+    kind: TransactionKind,
+    #[serde(with = "time::serde::rfc3339")]
+    datetime: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotHold {
+    id: Uuid,
+    card_number: CardNumber,
+    amount: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    datetime: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotLoan {
+    id: Uuid,
+    card_number: CardNumber,
+    principal: i64,
+    interest_rate: String,
+    term: u32,
+    period_secs: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    opened_at: OffsetDateTime,
+    repaid: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotTransferOrder {
+    id: Uuid,
+    sender: CardNumber,
+    recipient: CardNumber,
+    amount: i64,
+    schedule: OrderSchedule,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    next_run_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    cancelled_at: Option<OffsetDateTime>,
+    executions: Vec<SnapshotOrderExecution>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotOrderExecution {
+    #[serde(with = "time::serde::rfc3339")]
+    executed_at: OffsetDateTime,
+    transaction_id: Option<Uuid>,
+    error: Option<String>,
+}
+
+/// Only completed requests are kept, the running ones can't
+/// finish after restart
+#[derive(Serialize, Deserialize)]
+struct SnapshotIdempotencyKey {
+    key: String,
+    request_hash: String,
+    status: u16,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Snapshot {
+    pub(super) fn new(inner: &Inner) -> Self {
+        let account = |acc: &Account| {
+            let password_hash = inner
+                .password_hashes
+                .get(&acc.card_number)
+                .map(|hash| hash.expose_secret().clone());
+            SnapshotAccount {
+                username: acc.username.clone(),
+                card_number: acc.card_number.clone(),
+                // Plain password is kept only until it is hashed
+                password: match password_hash {
+                    Some(_) => String::new(),
+                    None => acc.password.expose_secret().clone(),
+                },
+                password_hash,
+                is_existing: acc.is_existing,
+                deleted_at: inner.deleted_at.get(&acc.card_number).copied(),
+                overdraft_limit: inner
+                    .overdraft_limits
+                    .get(&acc.card_number)
+                    .copied()
+                    .unwrap_or(0),
+                status: inner
+                    .statuses
+                    .get(&acc.card_number)
+                    .map(|record| record.status)
+                    .unwrap_or_default(),
+                status_reason: inner
+                    .statuses
+                    .get(&acc.card_number)
+                    .and_then(|record| record.reason.clone()),
+                card: inner.cards.get(&acc.card_number).map(|card| {
+                    let (cvv, cvv_hash) = match card.cvv {
+                        Credential::Plain(ref cvv) => {
+                            (cvv.expose_secret().clone(), None)
+                        }
+                        Credential::Hash(ref hash) => {
+                            (String::new(), Some(hash.expose_secret().clone()))
+                        }
+                    };
+                    SnapshotCard {
+                        expiry: card.expiry,
+                        cvv,
+                        cvv_hash,
+                    }
+                }),
+            }
+        };
+
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            emission_account: account(&inner.emission_account),
+            revenue_account: account(&inner.revenue_account),
+            stores: inner
+                .stores
+                .iter()
+                .map(|(key, acc)| (*key, account(acc)))
+                .collect(),
+            accounts: inner.accounts.iter().map(account).collect(),
+            tokens: inner
+                .tokens
+                .values()
+                .map(|token| SnapshotToken {
+                    token: token.token.clone(),
+                    card_number: token.card_number.clone(),
+                    created_at: token.created_at,
+                    expires_at: token.expires_at,
+                    revoked_at: token.revoked_at,
+                    terminal_key: token.terminal_key,
+                })
+                .collect(),
+            transactions: inner
+                .transactions
+                .iter()
+                .map(|t| SnapshotTransaction {
+                    id: t.id,
+                    sender: t.sender.card_number.clone(),
+                    recipient: t.recipient.card_number.clone(),
+                    amount: t.amount,
+                    kind: t.kind,
+                    datetime: t.datetime,
+                })
+                .collect(),
+            holds: inner
+                .holds
+                .iter()
+                .map(|hold| SnapshotHold {
+                    id: hold.id,
+                    card_number: hold.card_number.clone(),
+                    amount: hold.amount,
+                    datetime: hold.datetime,
+                })
+                .collect(),
+            loans: inner
+                .loans
+                .iter()
+                .map(|loan| SnapshotLoan {
+                    id: loan.id,
+                    card_number: loan.card_number.clone(),
+                    principal: loan.principal,
+                    interest_rate: loan.interest_rate.to_string(),
+                    term: loan.term,
+                    period_secs: loan.period.whole_seconds(),
+                    opened_at: loan.opened_at,
+                    repaid: loan.repaid,
+                })
+                .collect(),
+            transfer_orders: inner
+                .transfer_orders
+                .iter()
+                .map(|order| SnapshotTransferOrder {
+                    id: order.id,
+                    sender: order.sender.clone(),
+                    recipient: order.recipient.clone(),
+                    amount: order.amount,
+                    schedule: order.schedule.clone(),
+                    created_at: order.created_at,
+                    next_run_at: order.next_run_at,
+                    cancelled_at: order.cancelled_at,
+                    executions: order
+                        .executions
+                        .iter()
+                        .map(|execution| SnapshotOrderExecution {
+                            executed_at: execution.executed_at,
+                            transaction_id: execution.transaction_id,
+                            error: execution.error.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            idempotency_keys: inner
+                .idempotency_keys
+                .iter()
+                .filter_map(|(key, record)| {
+                    let response = record.response.as_ref()?;
+                    Some(SnapshotIdempotencyKey {
+                        key: key.clone(),
+                        request_hash: record.request_hash.clone(),
+                        status: response.status,
+                        content_type: response.content_type.clone(),
+                        body: response.body.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Read snapshot, `None` if the file doesn't exist yet
+    pub(super) fn load(path: &Path) -> Result<Option<Self>, anyhow::Error> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(None)
+            }
+            Err(e) => {
+                return Err(e).context("Failed to read memory snapshot file")
+            }
+        };
+        let snapshot: Snapshot = serde_json::from_slice(&data)
+            .context("Failed to parse memory snapshot")?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported memory snapshot version: {}",
+                snapshot.version
+            ));
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Replace plain passwords and CVVs with their hashes. Hashing is
+    /// slow, so it's done out of the storage lock.
+    pub(super) async fn hash_secrets(
+        &mut self,
+    ) -> Result<HashedSecrets, BankOperationError> {
+        let mut hashed = HashedSecrets::default();
+        let accounts = [&mut self.emission_account, &mut self.revenue_account]
+            .into_iter()
+            .chain(self.stores.values_mut())
+            .chain(self.accounts.iter_mut());
+        for acc in accounts {
+            if acc.password_hash.is_none() {
+                let password = std::mem::take(&mut acc.password);
+                let hash = hash_secret(password.clone(), None).await?;
+                acc.password_hash = Some(hash.clone());
+                hashed.passwords.push((
+                    acc.card_number.clone(),
+                    password,
+                    hash,
+                ));
+            }
+            let Some(card) = acc.card.as_mut() else {
+                continue;
+            };
+            if card.cvv_hash.is_none() {
+                let cvv = std::mem::take(&mut card.cvv);
+                let hash = hash_secret(cvv.clone(), None).await?;
+                card.cvv_hash = Some(hash.clone());
+                hashed.cvvs.push((acc.card_number.clone(), cvv, hash));
+            }
+        }
+        Ok(hashed)
+    }
+
+    /// Write snapshot to the temporary file first, so the previous
+    /// one is not corrupted if the write fails. The file is readable
+    /// by the owner only.
+    pub(super) async fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let data = serde_json::to_vec(self)
+            .context("Failed to serialize memory snapshot")?;
+        let tmp_path = path.with_extension("tmp");
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp_path)
+            .await
+            .context("Failed to create memory snapshot file")?;
+        // Mode is applied only to the new file, the stale one is fixed
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .await
+            .context("Failed to set memory snapshot file permissions")?;
+        file.write_all(&data)
+            .await
+            .context("Failed to write memory snapshot file")?;
+        file.sync_all()
+            .await
+            .context("Failed to write memory snapshot file")?;
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .context("Failed to replace memory snapshot file")?;
+        Ok(())
+    }
+
+    /// Build storage state, store accounts are created for
+    /// terminals, which were added since the snapshot
+    pub(super) fn restore(
+        self,
+        settings: &Settings,
+        notifier: Sender<()>,
+    ) -> Result<Inner, anyhow::Error> {
+        let mut inner = Inner {
+            tokens: HashMap::new(),
+            accounts: Vec::new(),
+            transactions: Vec::new(),
+            holds: Vec::new(),
+            loans: Vec::new(),
+            transfer_orders: Vec::new(),
+            overdraft_limits: HashMap::new(),
+            statuses: HashMap::new(),
+            deleted_at: HashMap::new(),
+            cards: HashMap::new(),
+            password_hashes: HashMap::new(),
+            idempotency_keys: HashMap::new(),
+            emission_account: self.emission_account.account(),
+            revenue_account: self.revenue_account.account(),
+            stores: HashMap::new(),
+            card_settings: settings.card_settings.clone(),
+            notifier,
+        };
+
+        for acc in [self.emission_account, self.revenue_account] {
+            inner.restore_account(acc);
+        }
+        for (key, acc) in self.stores {
+            let acc = inner.restore_account(acc);
+            inner.stores.insert(key, acc);
+        }
+        for acc in self.accounts {
+            let acc = inner.restore_account(acc);
+            inner.accounts.push(acc);
+        }
+
+        // System passwords are taken from the current settings
+        inner.emission_account.password =
+            settings.terminal_settings.password.clone();
+        inner.revenue_account.password =
+            settings.terminal_settings.password.clone();
+        for acc in [&inner.emission_account, &inner.revenue_account] {
+            inner.password_hashes.remove(&acc.card_number);
+        }
+        for terminal in settings.all_terminals() {
+            match inner.stores.get_mut(&terminal.terminal_key) {
+                Some(store) => {
+                    store.password = terminal.password.clone();
+                    inner.password_hashes.remove(&store.card_number);
+                }
+                None => {
+                    inner.stores.insert(
+                        terminal.terminal_key,
+                        Account {
+                            card_number: CardNumber::generate(
+                                &settings.card_settings,
+                            ),
+                            password: terminal.password.clone(),
+                            is_existing: true,
+                            username: settings.store_username(terminal),
+                        },
+                    );
+                }
+            }
+        }
+
+        for token in self.tokens {
+            inner.tokens.insert(
+                token.token.clone(),
+                CardToken {
+                    token: token.token,
+                    card_number: token.card_number,
+                    created_at: token.created_at,
+                    expires_at: token.expires_at,
+                    revoked_at: token.revoked_at,
+                    terminal_key: token.terminal_key,
+                },
+            );
+        }
+        for t in self.transactions {
+            let transaction = Transaction {
+                id: t.id,
+                sender: inner.snapshot_account(&t.sender)?,
+                recipient: inner.snapshot_account(&t.recipient)?,
+                amount: t.amount,
+                kind: t.kind,
+                datetime: t.datetime,
+            };
+            inner.transactions.push(transaction);
+        }
+        inner.holds = self
+            .holds
+            .into_iter()
+            .map(|hold| Hold {
+                id: hold.id,
+                card_number: hold.card_number,
+                amount: hold.amount,
+                datetime: hold.datetime,
+                expires_at: hold.datetime + HOLD_LIFETIME,
+            })
+            .collect();
+        for loan in self.loans {
+            inner.loans.push(Loan {
+                id: loan.id,
+                card_number: loan.card_number,
+                principal: loan.principal,
+                interest_rate: Decimal::from_str(&loan.interest_rate)
+                    .context("Bad loan interest rate in memory snapshot")?,
+                term: loan.term,
+                period: Duration::seconds(loan.period_secs),
+                opened_at: loan.opened_at,
+                repaid: loan.repaid,
+            });
+        }
+        inner.transfer_orders = self
+            .transfer_orders
+            .into_iter()
+            .map(|order| TransferOrder {
+                id: order.id,
+                sender: order.sender,
+                recipient: order.recipient,
+                amount: order.amount,
+                schedule: order.schedule,
+                created_at: order.created_at,
+                next_run_at: order.next_run_at,
+                cancelled_at: order.cancelled_at,
+                executions: order
+                    .executions
+                    .into_iter()
+                    .map(|execution| OrderExecution {
+                        executed_at: execution.executed_at,
+                        transaction_id: execution.transaction_id,
+                        error: execution.error,
+                    })
+                    .collect(),
+            })
+            .collect();
+        inner.idempotency_keys = self
+            .idempotency_keys
+            .into_iter()
+            .map(|record| {
+                (
+                    record.key,
+                    IdempotencyRecord {
+                        request_hash: record.request_hash,
+                        response: Some(IdempotentResponse {
+                            status: record.status,
+                            content_type: record.content_type,
+                            body: record.body,
+                        }),
+                    },
+                )
+            })
+            .collect();
+        Ok(inner)
+    }
+}
+
+impl Snapshot {
+    /// Imported state, which is restored as the usual snapshot
+    pub(super) fn from_dump(
+        dump: &BankDump,
+        settings: &Settings,
+    ) -> Result<Self, BankOperationError> {
+        let mut emission_account = None;
+        let mut revenue_account = None;
+        let mut stores = HashMap::new();
+        let mut accounts = Vec::new();
+        for acc in dump.accounts.iter() {
+            let account = SnapshotAccount::from_dump(acc, settings);
+            match acc.role {
+                AccountRole::Customer => accounts.push(account),
+                AccountRole::Emission => emission_account = Some(account),
+                AccountRole::Revenue => revenue_account = Some(account),
+                AccountRole::Store { terminal_key } => {
+                    stores.insert(terminal_key, account);
+                }
+            }
+        }
+        let (Some(emission_account), Some(revenue_account)) =
+            (emission_account, revenue_account)
+        else {
+            return Err(BankOperationError::BadOperation(
+                "Dump has no system accounts".to_string(),
+            ));
+        };
+
+        Ok(Snapshot {
+            version: SNAPSHOT_VERSION,
+            emission_account,
+            revenue_account,
+            stores,
+            accounts,
+            tokens: dump
+                .tokens
+                .iter()
+                .map(|token| SnapshotToken {
+                    token: token.token.clone(),
+                    card_number: token.card_number.clone(),
+                    created_at: token.created_at,
+                    expires_at: token.expires_at,
+                    revoked_at: token.revoked_at,
+                    terminal_key: token.terminal_key,
+                })
+                .collect(),
+            transactions: dump
+                .transactions
+                .iter()
+                .map(|t| SnapshotTransaction {
+                    id: t.id,
+                    sender: t.sender.clone(),
+                    recipient: t.recipient.clone(),
+                    amount: t.amount,
+                    kind: t.kind,
+                    datetime: t.datetime,
+                })
+                .collect(),
+            holds: Vec::new(),
+            loans: Vec::new(),
+            transfer_orders: Vec::new(),
+            idempotency_keys: Vec::new(),
+        })
+    }
+
+    /// Dump with hashed secrets, hashing is slow,
+    /// so it is done out of the storage lock
+    pub(super) async fn into_dump(
+        self,
+    ) -> Result<BankDump, BankOperationError> {
+        let accounts = [
+            (self.emission_account, AccountRole::Emission),
+            (self.revenue_account, AccountRole::Revenue),
+        ]
+        .into_iter()
+        .chain(self.stores.into_iter().map(|(terminal_key, acc)| {
+            (acc, AccountRole::Store { terminal_key })
+        }))
+        .chain(
+            self.accounts
+                .into_iter()
+                .map(|acc| (acc, AccountRole::Customer)),
+        );
+        let mut dumped = Vec::new();
+        for (acc, role) in accounts {
+            dumped.push(acc.into_dump(role).await?);
+        }
+
+        let mut tokens: Vec<_> = self
+            .tokens
+            .into_iter()
+            .map(|token| DumpToken {
+                token: token.token,
+                card_number: token.card_number,
+                created_at: token.created_at,
+                expires_at: token.expires_at,
+                revoked_at: token.revoked_at,
+                terminal_key: token.terminal_key,
+            })
+            .collect();
+        tokens.sort_by_key(|token| token.created_at);
+
+        Ok(BankDump {
+            version: DUMP_VERSION,
+            accounts: dumped,
+            tokens,
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(|t| DumpTransaction {
+                    id: t.id,
+                    sender: t.sender,
+                    recipient: t.recipient,
+                    amount: t.amount,
+                    kind: t.kind,
+                    datetime: t.datetime,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl SnapshotAccount {
+    fn from_dump(acc: &DumpAccount, settings: &Settings) -> Self {
+        let (username, password, password_hash) =
+            match acc.system_credentials(settings) {
+                Some((username, password)) => {
+                    (username, password.expose_secret().clone(), None)
+                }
+                None => (
+                    acc.username.clone(),
+                    String::new(),
+                    Some(acc.password_hash.clone()),
+                ),
+            };
+        SnapshotAccount {
+            username,
+            card_number: acc.card_number.clone(),
+            password,
+            password_hash,
+            is_existing: acc.is_existing,
+            deleted_at: acc.deleted_at,
+            overdraft_limit: acc.overdraft_limit,
+            status: acc.status,
+            status_reason: acc.status_reason.clone(),
+            card: acc.card.as_ref().map(|card| SnapshotCard {
+                expiry: card.expiry,
+                cvv: String::new(),
+                cvv_hash: Some(card.cvv_hash.clone()),
+            }),
+        }
+    }
+
+    async fn into_dump(
+        self,
+        role: AccountRole,
+    ) -> Result<DumpAccount, BankOperationError> {
+        let card = match self.card {
+            Some(card) => Some(DumpCard {
+                expiry: card.expiry,
+                cvv_hash: hash_secret(card.cvv, card.cvv_hash).await?,
+            }),
+            None => None,
+        };
+        Ok(DumpAccount {
+            username: self.username,
+            card_number: self.card_number,
+            role,
+            password_hash: hash_secret(self.password, self.password_hash)
+                .await?,
+            is_existing: self.is_existing,
+            deleted_at: self.deleted_at,
+            overdraft_limit: self.overdraft_limit,
+            status: self.status,
+            status_reason: self.status_reason,
+            card,
+        })
+    }
+
+    fn account(&self) -> Account {
+        Account {
+            username: self.username.clone(),
+            card_number: self.card_number.clone(),
+            password: Secret::new(self.password.clone()),
+            is_existing: self.is_existing,
+        }
+    }
+}
+
+impl Inner {
+    /// Put account state into the storage maps
+    fn restore_account(&mut self, acc: SnapshotAccount) -> Account {
+        let account = acc.account();
+        if let Some(deleted_at) = acc.deleted_at {
+            self.deleted_at.insert(acc.card_number.clone(), deleted_at);
+        }
+        if acc.overdraft_limit != 0 {
+            self.overdraft_limits
+                .insert(acc.card_number.clone(), acc.overdraft_limit);
+        }
+        if acc.status != AccountStatus::Active || acc.status_reason.is_some() {
+            self.statuses.insert(
+                acc.card_number.clone(),
+                StatusRecord {
+                    status: acc.status,
+                    reason: acc.status_reason,
+                },
+            );
+        }
+        if let Some(hash) = acc.password_hash {
+            self.password_hashes
+                .insert(acc.card_number.clone(), Secret::new(hash));
+        }
+        if let Some(card) = acc.card {
+            let cvv = match card.cvv_hash {
+                Some(hash) => Credential::Hash(Secret::new(hash)),
+                None => Credential::Plain(Secret::new(card.cvv)),
+            };
+            self.cards.insert(
+                acc.card_number.clone(),
+                CardDetails {
+                    expiry: card.expiry,
+                    cvv,
+                },
+            );
+        }
+        account
+    }
+
+    /// Keep hashes of the saved secrets, so the next snapshot doesn't
+    /// hash them again. Secrets, changed since then, are left as is.
+    pub(super) fn keep_hashes(&mut self, hashed: HashedSecrets) {
+        for (card, password, hash) in hashed.passwords {
+            let unchanged = !self.password_hashes.contains_key(&card)
+                && self.snapshot_account(&card).is_ok_and(|acc| {
+                    acc.password.expose_secret().eq(&password)
+                });
+            if unchanged {
+                self.password_hashes.insert(card, Secret::new(hash));
+            }
+        }
+        for (card, cvv, hash) in hashed.cvvs {
+            let Some(details) = self.cards.get_mut(&card) else {
+                continue;
+            };
+            if matches!(
+                details.cvv,
+                Credential::Plain(ref plain) if plain.expose_secret().eq(&cvv)
+            ) {
+                details.cvv = Credential::Hash(Secret::new(hash));
+            }
+        }
+    }
+
+    fn snapshot_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Account, anyhow::Error> {
+        std::iter::once(&self.emission_account)
+            .chain(std::iter::once(&self.revenue_account))
+            .chain(self.stores.values())
+            .chain(self.accounts.iter())
+            .find(|acc| acc.card_number.eq(card))
+            .cloned()
+            .ok_or(anyhow::anyhow!(
+                "Unknown account in memory snapshot: {}",
+                card.as_ref()
+            ))
+    }
+}
+
+/// Hash of the plain secret, imported secrets are already hashed
+async fn hash_secret(
+    secret: String,
+    hash: Option<String>,
+) -> Result<String, BankOperationError> {
+    match hash {
+        Some(hash) => Ok(hash),
+        None => hash_password_blocking(argon2_obj(), Secret::new(secret)).await,
+    }
+}
@@ -3,23 +3,34 @@ use std::sync::Arc;
 
 use rand::{distributions::Alphanumeric, Rng};
 use secrecy::Secret;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use time::format_description::well_known::iso8601;
 use time::format_description::well_known::iso8601::TimePrecision;
 use time::format_description::well_known::Iso8601;
 use time::OffsetDateTime;
 use tokio::sync::TryLockError;
+use uuid::Uuid;
 
 use crate::cornucopia::queries::bank_queries::GetAccount;
 use crate::domain::card_number::CardNumber;
 use crate::error_chain_fmt;
 use crate::Settings;
 
+use self::audit::{AuditReport, AuditViolation};
 use self::backend::BankDataBackend;
+use self::dump::BankDump;
+use self::ledger::{InclusionProof, Ledger, LedgerRoot};
 
+pub mod audit;
 mod backend;
+pub mod dump;
+pub mod ledger;
+pub mod loan;
 pub mod memory;
 pub mod pg;
+pub mod split;
+pub mod sqlite;
+pub mod transfer_order;
 
 const SIMPLE_ISO: Iso8601<6651332276402088934156738804825718784> = Iso8601::<
     {
@@ -32,7 +43,15 @@ const SIMPLE_ISO: Iso8601<6651332276402088934156738804825718784> = Iso8601::<
     },
 >;
 
-time::serde::format_description!(iso_format, OffsetDateTime, SIMPLE_ISO);
+time::serde::format_description!(
+    pub(crate) iso_format,
+    OffsetDateTime,
+    SIMPLE_ISO
+);
+
+/// Holds live as long as the payment session, which is removed after
+/// an hour. Expired holds don't reserve funds anymore.
+pub const HOLD_LIFETIME: time::Duration = time::Duration::hours(1);
 
 #[derive(thiserror::Error)]
 pub enum BankOperationError {
@@ -44,14 +63,46 @@ pub enum BankOperationError {
     AccountNotFound,
     #[error("No account for token")]
     TokenNotFound,
+    #[error("Card token was revoked")]
+    TokenRevoked,
+    #[error("Card token is expired")]
+    TokenExpired,
+    #[error("No funds hold")]
+    HoldNotFound,
+    #[error("No transaction")]
+    TransactionNotFound,
+    #[error("No loan")]
+    LoanNotFound,
+    #[error("No transfer order")]
+    OrderNotFound,
+    #[error("No store account for terminal")]
+    TerminalNotFound,
     #[error("Account was deleted")]
     AccountIsDeleted,
+    #[error("Account is frozen")]
+    AccountIsFrozen,
+    #[error("Account is blocked")]
+    AccountIsBlocked,
     #[error("Not enough funds for operation")]
     NotEnoughFunds,
+    #[error("Operation exceeds account overdraft limit")]
+    OverdraftLimitExceeded,
     #[error("Account is not authorized")]
     NotAuthorized,
+    #[error("Card is expired")]
+    CardExpired,
+    #[error("Wrong card expiry date")]
+    InvalidExpiry,
+    #[error("Wrong card CVV")]
+    InvalidCvv,
+    #[error("Card number has wrong Luhn check digit")]
+    InvalidCardNumber,
     #[error("Can't perform transaction")]
     BadTransaction,
+    #[error("Acquiring fee should be less than the payment amount")]
+    FeeExceedsAmount,
+    #[error("Stored transactions don't match the ledger")]
+    LedgerTampered,
     #[error("Mutex lock error: {0}")]
     MutexLockError(#[from] TryLockError),
     #[error("Attempt to perform not allowed operation: {0}")]
@@ -68,6 +119,10 @@ impl BankOperationError {
     pub fn str_reason_for_client(&self) -> String {
         match self {
             BankOperationError::BadTransaction => "bad_transaction".to_string(),
+            BankOperationError::FeeExceedsAmount => {
+                "fee_exceeds_amount".to_string()
+            }
+            BankOperationError::LedgerTampered => "ledger_tampered".to_string(),
             BankOperationError::BadOperation(_) => "bad_operation".to_string(),
             BankOperationError::InternalError(_)
             | Self::UnexpectedError
@@ -76,13 +131,39 @@ impl BankOperationError {
                 "account_not_found".to_string()
             }
             BankOperationError::TokenNotFound => "token_not_found".to_string(),
+            BankOperationError::TokenRevoked => "token_revoked".to_string(),
+            BankOperationError::TokenExpired => "token_expired".to_string(),
+            BankOperationError::HoldNotFound => "hold_not_found".to_string(),
+            BankOperationError::TransactionNotFound => {
+                "transaction_not_found".to_string()
+            }
+            BankOperationError::LoanNotFound => "loan_not_found".to_string(),
+            BankOperationError::OrderNotFound => "order_not_found".to_string(),
+            BankOperationError::TerminalNotFound => {
+                "terminal_not_found".to_string()
+            }
             BankOperationError::AccountIsDeleted => {
                 "account_is_deleted".to_string()
             }
+            BankOperationError::AccountIsFrozen => {
+                "account_is_frozen".to_string()
+            }
+            BankOperationError::AccountIsBlocked => {
+                "account_is_blocked".to_string()
+            }
             BankOperationError::NotEnoughFunds => {
                 "not_enough_funds".to_string()
             }
+            BankOperationError::OverdraftLimitExceeded => {
+                "overdraft_limit_exceeded".to_string()
+            }
             BankOperationError::NotAuthorized => "not_authorized".to_string(),
+            BankOperationError::CardExpired => "card_expired".to_string(),
+            BankOperationError::InvalidExpiry => "invalid_expiry".to_string(),
+            BankOperationError::InvalidCvv => "invalid_cvv".to_string(),
+            BankOperationError::InvalidCardNumber => {
+                "invalid_card_number".to_string()
+            }
         }
     }
 }
@@ -90,6 +171,7 @@ impl BankOperationError {
 #[derive(Clone, Debug)]
 pub struct Bank {
     inner: Arc<dyn BankDataBackend + Send + Sync>,
+    ledger: Arc<tokio::sync::Mutex<Ledger>>,
 }
 
 impl Deref for Bank {
@@ -103,19 +185,293 @@ impl Bank {
     pub fn new<T: backend::InitBankDataBackend>(settings: &Settings) -> Self {
         let (tx, _) = tokio::sync::watch::channel(());
         let bank = T::new(settings, tx);
-        Bank { inner: bank }
+        Bank {
+            inner: bank,
+            ledger: Arc::new(tokio::sync::Mutex::new(Ledger::default())),
+        }
+    }
+
+    /// Rebuild the ledger from stored transactions and keep it
+    /// up to date with new ones.
+    pub fn run_ledger(&self) {
+        let bank = self.clone();
+        tokio::spawn(async move {
+            let mut notifier = bank.subscribe().await;
+            loop {
+                if let Err(e) = bank.sync_ledger().await {
+                    tracing::error!("Failed to update ledger: {e}");
+                }
+                if notifier.changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub async fn ledger_root(&self) -> Result<LedgerRoot, BankOperationError> {
+        Ok(self.sync_ledger().await?.root())
+    }
+
+    pub async fn ledger_proof(
+        &self,
+        transaction_id: &Uuid,
+    ) -> Result<InclusionProof, BankOperationError> {
+        self.sync_ledger().await?.proof(transaction_id)
+    }
+
+    /// Replace the whole bank state with the dump, ledger is rebuilt
+    /// from the imported transactions
+    pub async fn import(
+        &self,
+        dump: &BankDump,
+    ) -> Result<(), BankOperationError> {
+        dump.validate()?;
+        let mut ledger = self.ledger.lock().await;
+        self.import_dump(dump).await?;
+        *ledger = Ledger::default();
+        Ok(())
+    }
+
+    /// Check bank invariants, all stored transactions are also
+    /// checked against the ledger
+    pub async fn audit(&self) -> Result<AuditReport, BankOperationError> {
+        let mut ledger = self.sync_ledger().await?;
+        let mut report = self.inner.audit().await?;
+        let transactions = self.list_transactions().await?;
+        if ledger.verify(&transactions).is_err() {
+            report.violations.push(AuditViolation::LedgerTampered);
+            report.consistent = false;
+        }
+        Ok(report)
+    }
+
+    /// Append transactions, stored after the last hashed one
+    async fn sync_ledger(
+        &self,
+    ) -> Result<tokio::sync::MutexGuard<'_, Ledger>, BankOperationError> {
+        let mut ledger = self.ledger.lock().await;
+        let transactions = self
+            .list_transactions_after(ledger.last_transaction())
+            .await?;
+        ledger.append(&transactions)?;
+        Ok(ledger)
     }
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub struct Transaction {
+    id: Uuid,
     sender: Account,
     recipient: Account,
     amount: i64,
+    kind: TransactionKind,
     #[serde(with = "iso_format")]
     datetime: OffsetDateTime,
 }
 
+impl Transaction {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    #[default]
+    Transfer,
+    /// Fee, withheld from the payment to the bank revenue account
+    AcquiringFee,
+    /// Part of the acquiring fee, returned to the payer with a refund
+    FeeRefund,
+}
+
+impl TransactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionKind::Transfer => "transfer",
+            TransactionKind::AcquiringFee => "acquiring_fee",
+            TransactionKind::FeeRefund => "fee_refund",
+        }
+    }
+}
+
+impl std::str::FromStr for TransactionKind {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transfer" => Ok(TransactionKind::Transfer),
+            "acquiring_fee" => Ok(TransactionKind::AcquiringFee),
+            "fee_refund" => Ok(TransactionKind::FeeRefund),
+            other => Err(anyhow::anyhow!("Unknown transaction kind: {other}")),
+        }
+    }
+}
+
+/// Stored response of the request, performed with idempotency key
+#[derive(Clone, Debug)]
+pub struct IdempotentResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// State of the idempotency key at the beginning of the request
+#[derive(Debug)]
+pub enum IdempotencyStatus {
+    /// Key is new and now reserved for the request
+    New,
+    /// Request with this key is still running
+    InProgress,
+    /// The same request was already performed with this key
+    Completed(IdempotentResponse),
+    /// Key was already used with a different request
+    KeyReused,
+}
+
+/// Token, which stores use instead of the card number
+#[derive(Serialize, Clone, Debug)]
+pub struct CardToken {
+    pub token: String,
+    pub card_number: CardNumber,
+    #[serde(with = "iso_format")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "iso_format::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "iso_format::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    /// Terminal, which registered the token; only it can charge the token.
+    /// Tokens created through the system api may be unbound.
+    pub terminal_key: Option<Uuid>,
+}
+
+impl CardToken {
+    /// Only tokens, which are not revoked or expired, can be used
+    pub fn check_active(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<(), BankOperationError> {
+        if self.revoked_at.is_some() {
+            return Err(BankOperationError::TokenRevoked);
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(BankOperationError::TokenExpired);
+        }
+        Ok(())
+    }
+}
+
+/// Funds reserved on the account, which are not available for spending
+/// until the hold is captured or released.
+#[derive(Serialize, Clone, Debug)]
+pub struct Hold {
+    id: Uuid,
+    card_number: CardNumber,
+    amount: i64,
+    #[serde(with = "iso_format")]
+    datetime: OffsetDateTime,
+    #[serde(with = "iso_format")]
+    expires_at: OffsetDateTime,
+}
+
+impl Hold {
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Status of the existing account, deletion is tracked separately
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    /// Account can receive money, but can't send it
+    Frozen,
+    /// Account can't send or receive money
+    Blocked,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Frozen => "frozen",
+            AccountStatus::Blocked => "blocked",
+        }
+    }
+
+    pub fn check_can_send(&self) -> Result<(), BankOperationError> {
+        match self {
+            AccountStatus::Active => Ok(()),
+            AccountStatus::Frozen => Err(BankOperationError::AccountIsFrozen),
+            AccountStatus::Blocked => Err(BankOperationError::AccountIsBlocked),
+        }
+    }
+
+    pub fn check_can_receive(&self) -> Result<(), BankOperationError> {
+        match self {
+            AccountStatus::Blocked => Err(BankOperationError::AccountIsBlocked),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for AccountStatus {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(AccountStatus::Active),
+            "frozen" => Ok(AccountStatus::Frozen),
+            "blocked" => Ok(AccountStatus::Blocked),
+            other => Err(anyhow::anyhow!("Unknown account status: {other}")),
+        }
+    }
+}
+
+/// Card is valid until the end of the expiry month
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CardExpiry {
+    pub month: u8,
+    pub year: u16,
+}
+
+impl CardExpiry {
+    pub fn new(month: u8, year: u16) -> Result<Self, BankOperationError> {
+        if !(1..=12).contains(&month) {
+            return Err(BankOperationError::BadOperation(
+                "Expiry month should be in 1..=12".to_string(),
+            ));
+        }
+        Ok(CardExpiry { month, year })
+    }
+
+    /// Expiry of the card, issued at `date` for `months`
+    pub fn after_months(date: OffsetDateTime, months: u32) -> Self {
+        let months = date.year() as u32 * 12 + date.month() as u32 - 1 + months;
+        CardExpiry {
+            month: (months % 12 + 1) as u8,
+            year: (months / 12) as u16,
+        }
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        (self.year, self.month) < (now.year() as u16, now.month() as u8)
+    }
+}
+
+/// Details of the issued card, CVV is shown only once
+#[derive(Debug)]
+pub struct IssuedCard {
+    pub card_number: CardNumber,
+    pub expiry: CardExpiry,
+    pub cvv: Secret<String>,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct Account {
     username: String,
@@ -149,6 +505,54 @@ impl TryFrom<GetAccount> for Account {
     }
 }
 
+/// Largest acquiring fee for the payment `amount`, the recipient always
+/// gets at least one unit of the payment.
+pub fn max_fee(amount: i64) -> i64 {
+    amount.saturating_sub(1).max(0)
+}
+
+/// Part of the acquiring `fee`, returned with the refund of `amount`,
+/// when `refunded` of the `captured` payment is already returned. The fee
+/// is returned proportionally, so the full refund returns the whole fee.
+pub fn refund_fee(captured: i64, fee: i64, refunded: i64, amount: i64) -> i64 {
+    if captured <= 0 {
+        return 0;
+    }
+    let returned = |refunded: i64| {
+        i128::from(fee) * i128::from(refunded) / i128::from(captured)
+    };
+    (returned(refunded + amount) - returned(refunded)) as i64
+}
+
+/// Check that acquiring `fee` can be withheld from the payment `amount`.
+fn check_fee(amount: i64, fee: i64) -> Result<(), BankOperationError> {
+    if amount <= 0 {
+        return Err(BankOperationError::BadTransaction);
+    }
+    if fee < 0 || fee > max_fee(amount) {
+        return Err(BankOperationError::FeeExceedsAmount);
+    }
+    Ok(())
+}
+
+/// Check that returned acquiring `fee` is a part of the refund `amount`,
+/// which can consist of the fee only.
+fn check_refund_fee(amount: i64, fee: i64) -> Result<(), BankOperationError> {
+    if amount <= 0 {
+        return Err(BankOperationError::BadTransaction);
+    }
+    if fee < 0 || fee > amount {
+        return Err(BankOperationError::FeeExceedsAmount);
+    }
+    Ok(())
+}
+
+/// Generate three digits card CVV.
+fn generate_cvv() -> Secret<String> {
+    let cvv: u16 = rand::thread_rng().gen_range(0..1000);
+    Secret::new(format!("{cvv:03}"))
+}
+
 /// Generate card token.
 fn generate_token() -> String {
     rand::thread_rng()
@@ -160,26 +564,34 @@ fn generate_token() -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::config::TerminalSettings;
+    use crate::config::{CardSettings, FeeSchedule, TerminalSettings};
 
+    use self::audit::{AuditedAccount, AuditedToken};
     use self::memory::MemoryStorage;
 
     use super::*;
+    use crate::domain::requests::system_api::{Direction, StatementRequest};
     use banksim_api::init_payment::beneficiaries::Beneficiaries;
-    use rs_merkle::{Hasher, MerkleTree};
     use rust_decimal::{prelude::FromPrimitive, Decimal};
+    use secrecy::ExposeSecret;
+    use time::macros::datetime;
+    use time::Duration;
     use url::Url;
     use uuid::Uuid;
 
-    fn make_bank() -> Bank {
+    const TERMINAL_KEY: Uuid = Uuid::from_u128(1);
+
+    fn make_settings() -> Settings {
         let url: Url = "http://google.com".parse().unwrap();
-        let settings = Settings {
+        Settings {
             data_backend_type: crate::config::DataBackendType::Mem,
             database_settings: None,
+            sqlite_settings: None,
+            memory_settings: None,
             port: 15100,
             addr: "localhost".to_string(),
             terminal_settings: TerminalSettings {
-                terminal_key: Uuid::new_v4(),
+                terminal_key: TERMINAL_KEY,
                 success_url: url.clone(),
                 fail_url: url.clone(),
                 success_add_card_url: url.clone(),
@@ -189,10 +601,89 @@ mod tests {
                 send_notification_finish_authorize: false,
                 send_notification_completed: false,
                 send_notification_reversed: false,
+                fee: FeeSchedule::default(),
             },
+            terminals: Vec::new(),
             bank_username: "test_bank".to_string(),
-        };
-        Bank::new::<MemoryStorage>(&settings)
+            frontend_path: String::new(),
+            card_settings: CardSettings::default(),
+        }
+    }
+
+    fn make_bank() -> Bank {
+        Bank::new::<MemoryStorage>(&make_settings())
+    }
+
+    /// Run concurrent debits of all kinds from a single account
+    async fn check_concurrent_debits_never_overdraw(bank: Bank) {
+        let username = |name: &str| format!("{name}_{}", Uuid::new_v4());
+        let pass = Secret::new("pass".to_string());
+        let payer_card = bank
+            .add_account(&username("payer"), &pass)
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 100).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+        let bfc1 = bank
+            .add_account(&username("bfc1"), &pass)
+            .await
+            .unwrap()
+            .card_number;
+        let bfc2 = bank
+            .add_account(&username("bfc2"), &pass)
+            .await
+            .unwrap()
+            .card_number;
+        let bfc = Beneficiaries::builder(
+            bank.new_card_token(&bfc1, None, None).await.unwrap(),
+            Decimal::from_f32(0.5).unwrap(),
+        )
+        .add(
+            bank.new_card_token(&bfc2, None, None).await.unwrap(),
+            Decimal::from_f32(0.5).unwrap(),
+        )
+        .build()
+        .unwrap();
+        let bfc = Arc::new(bfc);
+
+        let tasks = (0..60).map(|i| {
+            let bank = bank.clone();
+            let payer_card = payer_card.clone();
+            let store = store.clone();
+            let bfc = bfc.clone();
+            tokio::spawn(async move {
+                let result = match i % 3 {
+                    0 => bank
+                        .new_transaction(&payer_card, &store, 10)
+                        .await
+                        .map(|_| ()),
+                    1 => bank
+                        .new_split_transaction(&payer_card, 10, 0, &bfc)
+                        .await
+                        .map(|_| ()),
+                    _ => match bank.new_hold(&payer_card, 10).await {
+                        Ok(hold) => {
+                            bank.capture_hold(hold, &store, 10, 0).await
+                        }
+                        Err(e) => Err(e),
+                    },
+                };
+                match result {
+                    Ok(()) => 10,
+                    Err(BankOperationError::NotEnoughFunds) => 0,
+                    Err(e) => panic!("Unexpected error: {e}"),
+                }
+            })
+        });
+        let debited: i64 = futures::future::try_join_all(tasks)
+            .await
+            .unwrap()
+            .into_iter()
+            .sum();
+
+        assert_eq!(debited, 100);
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 0);
     }
 
     #[tokio::test]
@@ -201,22 +692,31 @@ mod tests {
         let payer_card = bank
             .add_account("payer", &Secret::new("pass".to_string()))
             .await
-            .unwrap();
+            .unwrap()
+            .card_number;
         bank.open_credit(&payer_card, 500).await.unwrap();
 
-        let store = bank.get_store_account().await.unwrap().card();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
         let bfc1 = bank
             .add_account("bfc1", &Secret::new("pass".to_string()))
             .await
-            .unwrap();
+            .unwrap()
+            .card_number;
         let bfc2 = bank
             .add_account("bfc2", &Secret::new("pass".to_string()))
             .await
-            .unwrap();
+            .unwrap()
+            .card_number;
 
-        let store_tok = bank.new_card_token(&store).await.unwrap();
-        let bfc1_tok = bank.new_card_token(&bfc1.clone()).await.unwrap();
-        let bfc2_tok = bank.new_card_token(&bfc2.clone()).await.unwrap();
+        let store_tok = bank.new_card_token(&store, None, None).await.unwrap();
+        let bfc1_tok = bank
+            .new_card_token(&bfc1.clone(), None, None)
+            .await
+            .unwrap();
+        let bfc2_tok = bank
+            .new_card_token(&bfc2.clone(), None, None)
+            .await
+            .unwrap();
 
         let bfc =
             Beneficiaries::builder(store_tok, Decimal::from_f32(0.37).unwrap())
@@ -225,14 +725,16 @@ mod tests {
                 .build()
                 .unwrap();
 
-        bank.new_split_transaction(&payer_card, 256, &bfc)
+        bank.new_split_transaction(&payer_card, 256, 0, &bfc)
             .await
             .unwrap();
 
         assert_eq!(
-            bank.balance(&bank.get_store_account().await.unwrap().card())
-                .await
-                .unwrap(),
+            bank.balance(
+                &bank.get_store_account(&TERMINAL_KEY).await.unwrap().card()
+            )
+            .await
+            .unwrap(),
             95
         );
         assert_eq!(bank.balance(&bfc1).await.unwrap(), 79);
@@ -240,29 +742,1222 @@ mod tests {
         assert_eq!(bank.balance(&payer_card).await.unwrap(), 244);
     }
 
+    #[tokio::test]
+    async fn split_refund_transaction_success() {
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 500).await.unwrap();
+
+        let bfc1 = bank
+            .add_account("bfc1", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let bfc2 = bank
+            .add_account("bfc2", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+
+        let bfc1_tok = bank
+            .new_card_token(&bfc1.clone(), None, None)
+            .await
+            .unwrap();
+        let bfc2_tok = bank
+            .new_card_token(&bfc2.clone(), None, None)
+            .await
+            .unwrap();
+
+        let bfc =
+            Beneficiaries::builder(bfc1_tok, Decimal::from_f32(0.5).unwrap())
+                .add(bfc2_tok, Decimal::from_f32(0.5).unwrap())
+                .build()
+                .unwrap();
+
+        bank.new_split_transaction(&payer_card, 200, 0, &bfc)
+            .await
+            .unwrap();
+        bank.new_split_refund_transaction(&payer_card, 100, 0, &bfc)
+            .await
+            .unwrap();
+
+        assert_eq!(bank.balance(&bfc1).await.unwrap(), 50);
+        assert_eq!(bank.balance(&bfc2).await.unwrap(), 50);
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 400);
+
+        // Can't refund more than beneficiaries have
+        assert!(matches!(
+            bank.new_split_refund_transaction(&payer_card, 200, 0, &bfc)
+                .await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 400);
+    }
+
+    #[tokio::test]
+    async fn hold_reserves_funds_until_capture() {
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 500).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+
+        let hold = bank.new_hold(&payer_card, 400).await.unwrap();
+
+        // Held funds can't be spent elsewhere
+        assert!(matches!(
+            bank.new_transaction(&payer_card, &store, 200).await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+        assert!(matches!(
+            bank.new_hold(&payer_card, 200).await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+
+        // Capture less than held, the rest becomes available again
+        bank.capture_hold(hold, &store, 300, 0).await.unwrap();
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 200);
+        assert!(matches!(
+            bank.release_hold(hold).await,
+            Err(BankOperationError::HoldNotFound)
+        ));
+
+        let hold = bank.new_hold(&payer_card, 200).await.unwrap();
+        bank.release_hold(hold).await.unwrap();
+
+        // Holds of lost sessions are released on startup
+        let hold = bank.new_hold(&payer_card, 200).await.unwrap();
+        bank.release_all_holds().await.unwrap();
+        assert!(matches!(
+            bank.capture_hold(hold, &store, 200, 0).await,
+            Err(BankOperationError::HoldNotFound)
+        ));
+        bank.new_transaction(&payer_card, &store, 200)
+            .await
+            .unwrap();
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn payment_session_captures_part_of_authorized_amount() {
+        use crate::routes::html_pages_and_triggers::{
+            CardCredentials, Credentials,
+        };
+        use crate::session::payment::{Event, PaymentSession, State};
+        use banksim_api::init_payment::InitPaymentRequest;
+        use banksim_api::OperationError;
+
+        // Collect webhooks of the session
+        let (tx, mut webhooks) = tokio::sync::mpsc::unbounded_channel();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    let _ = tx.send(body);
+                },
+            ),
+        );
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: Url = format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let bank = make_bank();
+        let pass = Secret::new("pass".to_string());
+        let payer = bank.add_account("payer", &pass).await.unwrap();
+        bank.open_credit(&payer.card_number, 1000).await.unwrap();
+        let bfc = bank.add_account("bfc", &pass).await.unwrap().card_number;
+        let split = Beneficiaries::builder(
+            bank.new_card_token(&bfc, None, None).await.unwrap(),
+            Decimal::ONE,
+        )
+        .build()
+        .unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+
+        let (watcher, _) = tokio::sync::oneshot::channel();
+        let session = PaymentSession::new(
+            InitPaymentRequest {
+                notification_url: url.clone(),
+                success_url: url.clone(),
+                fail_url: url,
+                amount: 1000,
+                beneficiaries: split,
+                token: String::new(),
+            },
+            Credentials {
+                card_number: store,
+                password: pass.clone(),
+                terminal_key: TERMINAL_KEY,
+            },
+            reqwest::Client::new(),
+            watcher,
+        );
+        let mut guard = session.state.lock().await;
+        guard
+            .handle(&Event::Submit {
+                bank: bank.clone(),
+                creds: CardCredentials {
+                    card_number: payer.card_number.clone(),
+                    password: pass,
+                    expiry_month: payer.expiry.month,
+                    expiry_year: payer.expiry.year,
+                    cvv: payer.cvv,
+                },
+            })
+            .await;
+        guard.handle(&Event::ConfirmRequest).await;
+        assert!(matches!(guard.state(), State::ReadyToCapture {}));
+
+        // Capture can't exceed the authorized amount
+        assert!(matches!(
+            guard.capture_amount(Some(1001)),
+            Err(OperationError::Failed { reason })
+                if reason == "capture_exceeds_authorized_amount"
+        ));
+        assert_eq!(guard.capture_amount(None).unwrap(), 1000);
+
+        // Capture less than authorized, the rest of the hold is released
+        let amount = guard.capture_amount(Some(600)).unwrap();
+        guard
+            .handle(&Event::CaptureRequest {
+                bank: bank.clone(),
+                amount,
+                fee: 0,
+            })
+            .await;
+        assert!(matches!(guard.state(), State::Successed { .. }));
+        assert_eq!(guard.refundable_amount(), 600);
+        assert_eq!(bank.balance(&bfc).await.unwrap(), 600);
+        assert_eq!(bank.balance(&payer.card_number).await.unwrap(), 400);
+        bank.new_hold(&payer.card_number, 400).await.unwrap();
+
+        // Store is notified about the captured amount
+        let finished = loop {
+            let body = tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                webhooks.recv(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            if let Some(finished) =
+                body.pointer("/PaymentNotification/PaymentFinished")
+            {
+                break finished.clone();
+            }
+        };
+        assert_eq!(finished["captured_amount"], 600);
+        assert_eq!(finished["status"], "Success");
+    }
+
+    #[tokio::test]
+    async fn acquiring_fee_goes_to_revenue_account() {
+        let schedule = FeeSchedule {
+            percent: Decimal::new(25, 1),
+            fixed: 5,
+            min: 20,
+        };
+        assert_eq!(schedule.fee(1000), 30);
+        assert_eq!(schedule.fee(100), 20);
+        assert_eq!(schedule.fee(10), 9);
+
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 2000).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+        let bfc = bank
+            .add_account("bfc", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let bfc_tok = bank.new_card_token(&bfc, None, None).await.unwrap();
+
+        let hold = bank.new_hold(&payer_card, 1000).await.unwrap();
+        bank.capture_hold(hold, &store, 1000, 30).await.unwrap();
+        assert_eq!(bank.balance(&store).await.unwrap(), 970);
+
+        let split = Beneficiaries::builder(bfc_tok, Decimal::ONE)
+            .build()
+            .unwrap();
+        bank.new_split_transaction(&payer_card, 500, 20, &split)
+            .await
+            .unwrap();
+        assert_eq!(bank.balance(&bfc).await.unwrap(), 480);
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 500);
+        assert_eq!(bank.bank_revenue().await.unwrap(), 50);
+
+        // Fee can't take the whole payment
+        assert!(matches!(
+            bank.new_payment(&payer_card, &store, 10, 10).await,
+            Err(BankOperationError::FeeExceedsAmount)
+        ));
+
+        let fees: Vec<_> = bank
+            .list_transactions()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.kind == TransactionKind::AcquiringFee)
+            .map(|t| (t.sender.card(), t.amount))
+            .collect();
+        assert_eq!(fees, vec![(payer_card.clone(), 30), (payer_card, 20)]);
+        assert!(bank.audit().await.unwrap().consistent);
+    }
+
+    /// Capture payments with a fee and refund them in full
+    async fn check_refund_returns_acquiring_fee(bank: Bank) {
+        // Fee is returned proportionally, the last refund returns the rest
+        assert_eq!(refund_fee(1000, 30, 0, 1000), 30);
+        assert_eq!(refund_fee(1000, 30, 0, 333), 9);
+        assert_eq!(refund_fee(1000, 30, 333, 333), 10);
+        assert_eq!(refund_fee(1000, 30, 666, 334), 11);
+        assert_eq!(refund_fee(100, 99, 50, 50), 50);
+
+        let pass = Secret::new("pass".to_string());
+        let payer_card =
+            bank.add_account("payer", &pass).await.unwrap().card_number;
+        bank.open_credit(&payer_card, 2000).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+        let bfc = bank.add_account("bfc", &pass).await.unwrap().card_number;
+        let split = Beneficiaries::builder(
+            bank.new_card_token(&bfc, None, None).await.unwrap(),
+            Decimal::ONE,
+        )
+        .build()
+        .unwrap();
+
+        let hold = bank.new_hold(&payer_card, 1000).await.unwrap();
+        bank.capture_hold(hold, &store, 1000, 30).await.unwrap();
+        let hold = bank.new_hold(&payer_card, 1000).await.unwrap();
+        bank.capture_split_hold(hold, 1000, 30, &split)
+            .await
+            .unwrap();
+        assert_eq!(bank.bank_revenue().await.unwrap(), 60);
+
+        // Store returns what it got, the fee comes from the revenue
+        bank.new_refund(&store, &payer_card, 400, refund_fee(1000, 30, 0, 400))
+            .await
+            .unwrap();
+        bank.new_refund(
+            &store,
+            &payer_card,
+            600,
+            refund_fee(1000, 30, 400, 600),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bank.balance(&store).await.unwrap(), 0);
+        assert_eq!(bank.bank_revenue().await.unwrap(), 30);
+
+        bank.new_split_refund_transaction(&payer_card, 1000, 30, &split)
+            .await
+            .unwrap();
+        assert_eq!(bank.balance(&bfc).await.unwrap(), 0);
+        assert_eq!(bank.bank_revenue().await.unwrap(), 0);
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), 2000);
+
+        // Revenue can't return more than it has
+        assert!(matches!(
+            bank.new_refund(&store, &payer_card, 10, 10).await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+        let returned: i64 = bank
+            .list_transactions()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.kind == TransactionKind::FeeRefund)
+            .map(|t| t.amount)
+            .sum();
+        assert_eq!(returned, 60);
+        assert!(bank.audit().await.unwrap().consistent);
+    }
+
+    #[tokio::test]
+    async fn refund_returns_acquiring_fee() {
+        check_refund_returns_acquiring_fee(make_bank()).await;
+    }
+
+    #[tokio::test]
+    async fn overdraft_limit_allows_negative_balance() {
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 100).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+
+        assert!(matches!(
+            bank.new_transaction(&payer_card, &store, 150).await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+        bank.set_overdraft_limit(&payer_card, 100).await.unwrap();
+        bank.new_transaction(&payer_card, &store, 150)
+            .await
+            .unwrap();
+        assert_eq!(bank.balance(&payer_card).await.unwrap(), -50);
+        assert!(matches!(
+            bank.new_transaction(&payer_card, &store, 60).await,
+            Err(BankOperationError::OverdraftLimitExceeded)
+        ));
+
+        let accounts = bank.list_accounts().await.unwrap();
+        assert_eq!(accounts[0].debt, 50);
+        assert_eq!(accounts[0].overdraft_limit, 100);
+    }
+
+    #[tokio::test]
+    async fn frozen_and_blocked_accounts_restrict_transfers() {
+        let bank = make_bank();
+        let card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&card, 100).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+
+        bank.set_account_status(&card, AccountStatus::Frozen, Some("fraud"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            bank.new_transaction(&card, &store, 10).await,
+            Err(BankOperationError::AccountIsFrozen)
+        ));
+        assert!(matches!(
+            bank.new_hold(&card, 10).await,
+            Err(BankOperationError::AccountIsFrozen)
+        ));
+        bank.open_credit(&card, 10).await.unwrap();
+
+        bank.set_account_status(&card, AccountStatus::Blocked, None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            bank.open_credit(&card, 10).await,
+            Err(BankOperationError::AccountIsBlocked)
+        ));
+        assert_eq!(bank.balance(&card).await.unwrap(), 110);
+
+        bank.set_account_status(&card, AccountStatus::Active, None)
+            .await
+            .unwrap();
+        bank.new_transaction(&card, &store, 10).await.unwrap();
+
+        bank.delete_account(&card).await.unwrap();
+        bank.restore_account(&card).await.unwrap();
+        let accounts = bank.list_accounts().await.unwrap();
+        assert!(accounts[0].exists);
+        assert_eq!(accounts[0].status, AccountStatus::Active);
+        assert_eq!(bank.balance(&card).await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn card_expiry_and_cvv_are_verified() {
+        let bank = make_bank();
+        let pass = Secret::new("pass".to_string());
+        let card = bank.add_account("payer", &pass).await.unwrap();
+        let wrong_cvv = Secret::new(if card.cvv.expose_secret() == "000" {
+            "001".to_string()
+        } else {
+            "000".to_string()
+        });
+        let card_number = &card.card_number;
+
+        bank.authorize_card(card_number, &pass, card.expiry, &card.cvv)
+            .await
+            .unwrap();
+        assert_eq!(
+            bank.card_expiry(card_number).await.unwrap(),
+            Some(card.expiry)
+        );
+        let other_expiry =
+            CardExpiry::new(card.expiry.month % 12 + 1, 2000).unwrap();
+        assert!(matches!(
+            bank.authorize_card(card_number, &pass, other_expiry, &card.cvv)
+                .await,
+            Err(BankOperationError::InvalidExpiry)
+        ));
+        assert!(matches!(
+            bank.authorize_card(card_number, &pass, card.expiry, &wrong_cvv)
+                .await,
+            Err(BankOperationError::InvalidCvv)
+        ));
+
+        let now = datetime!(2024-11-30 23:59 UTC);
+        let expiry = CardExpiry::after_months(now, 2);
+        assert_eq!(expiry, CardExpiry::new(1, 2025).unwrap());
+        assert!(!expiry.is_expired(datetime!(2025-01-31 23:59 UTC)));
+        assert!(expiry.is_expired(datetime!(2025-02-01 0:00 UTC)));
+
+        let expired = CardExpiry::new(1, 2020).unwrap();
+        let card = bank.reissue_card(card_number, Some(expired)).await.unwrap();
+        assert!(matches!(
+            bank.authorize_card(card_number, &pass, expired, &card.cvv)
+                .await,
+            Err(BankOperationError::CardExpired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn revoked_and_expired_tokens_are_rejected() {
+        let bank = make_bank();
+        let card = bank
+            .add_account("holder", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let token = bank.new_card_token(&card, None, None).await.unwrap();
+        let expired = bank
+            .new_card_token(&card, Some(OffsetDateTime::now_utc()), None)
+            .await
+            .unwrap();
+
+        bank.get_account_by_token(&token).await.unwrap();
+        assert!(matches!(
+            bank.get_account_by_token(&expired).await,
+            Err(BankOperationError::TokenExpired)
+        ));
+
+        bank.revoke_card_token(&token).await.unwrap();
+        assert!(matches!(
+            bank.get_account_by_token(&token).await,
+            Err(BankOperationError::TokenRevoked)
+        ));
+        assert!(matches!(
+            bank.revoke_card_token(&token).await,
+            Err(BankOperationError::TokenRevoked)
+        ));
+
+        let tokens = bank.list_card_tokens(&card).await.unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|t| t.card_number == card));
+        let accounts = bank.list_accounts().await.unwrap();
+        assert!(accounts[0].tokens.is_empty());
+    }
+
+    #[tokio::test]
+    async fn loan_is_repaid_to_emission_account() {
+        let bank = make_bank();
+        let card = bank
+            .add_account("borrower", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let loan = bank
+            .open_loan(&card, 1000, Decimal::new(1, 2), 12, Duration::days(30))
+            .await
+            .unwrap();
+        assert_eq!(bank.balance(&card).await.unwrap(), 1000);
+        assert_eq!(bank.bank_emission().await.unwrap(), -1000);
+
+        bank.repay_loan(&loan.id, 89).await.unwrap();
+        assert_eq!(bank.balance(&card).await.unwrap(), 911);
+        assert_eq!(bank.bank_emission().await.unwrap(), -911);
+
+        let loan = bank.get_loan(&loan.id).await.unwrap();
+        assert_eq!(loan.repaid, 89);
+        assert!(matches!(
+            bank.repay_loan(&loan.id, loan.outstanding().unwrap() + 1)
+                .await,
+            Err(BankOperationError::BadOperation(_))
+        ));
+        // Interest can't be paid without funds
+        assert!(matches!(
+            bank.repay_loan(&loan.id, loan.outstanding().unwrap()).await,
+            Err(BankOperationError::NotEnoughFunds)
+        ));
+        assert!(matches!(
+            bank.get_loan(&Uuid::new_v4()).await,
+            Err(BankOperationError::LoanNotFound)
+        ));
+    }
+
+    async fn check_transfer_orders_run_by_schedule(bank: Bank) {
+        use crate::scheduler::execute_due_orders;
+        use transfer_order::OrderSchedule;
+
+        let pass = Secret::new("pass".to_string());
+        let employer = bank
+            .add_account("employer", &pass)
+            .await
+            .unwrap()
+            .card_number;
+        let worker =
+            bank.add_account("worker", &pass).await.unwrap().card_number;
+        bank.open_credit(&employer, 100).await.unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let once = bank
+            .new_transfer_order(
+                &employer,
+                &worker,
+                60,
+                OrderSchedule::Once(now - Duration::minutes(1)),
+            )
+            .await
+            .unwrap();
+        let salary = bank
+            .new_transfer_order(
+                &employer,
+                &worker,
+                60,
+                OrderSchedule::Cron("* * * * *".parse().unwrap()),
+            )
+            .await
+            .unwrap();
+        assert!(salary.next_run_at.unwrap() > now);
+
+        execute_due_orders(&bank, now).await.unwrap();
+        assert_eq!(bank.balance(&worker).await.unwrap(), 60);
+        // Order, which has already run, is not run again
+        assert!(bank
+            .run_transfer_order(&once.id, now)
+            .await
+            .unwrap()
+            .is_none());
+        // Second run fails, but the order is rescheduled
+        let later = now + Duration::minutes(2);
+        execute_due_orders(&bank, later).await.unwrap();
+        assert_eq!(bank.balance(&worker).await.unwrap(), 60);
+
+        let orders = bank.list_transfer_orders().await.unwrap();
+        let once = orders.iter().find(|o| o.id == once.id).unwrap();
+        assert_eq!(once.next_run_at, None);
+        assert!(once.executions[0].transaction_id.is_some());
+        let salary = orders.iter().find(|o| o.id == salary.id).unwrap();
+        assert!(salary.next_run_at.unwrap() > later);
+        assert_eq!(
+            salary.executions[0].error.as_deref(),
+            Some("not_enough_funds")
+        );
+
+        bank.cancel_transfer_order(&salary.id).await.unwrap();
+        assert!(bank
+            .due_transfer_orders(later + Duration::days(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            bank.cancel_transfer_order(&salary.id).await,
+            Err(BankOperationError::BadOperation(_))
+        ));
+        assert!(matches!(
+            bank.cancel_transfer_order(&Uuid::new_v4()).await,
+            Err(BankOperationError::OrderNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn transfer_orders_run_by_schedule() {
+        check_transfer_orders_run_by_schedule(make_bank()).await;
+    }
+
+    #[tokio::test]
+    async fn transaction_lookup_by_id() {
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let credit = bank.open_credit(&payer_card, 500).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+        let id = bank
+            .new_transaction(&payer_card, &store, 200)
+            .await
+            .unwrap();
+        assert_ne!(credit, id);
+
+        let transaction = bank.get_transaction(&id).await.unwrap();
+        assert_eq!(transaction.id, id);
+        assert_eq!(transaction.amount, 200);
+        assert!(matches!(
+            bank.get_transaction(&Uuid::new_v4()).await,
+            Err(BankOperationError::TransactionNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn account_statement_pages_and_filters() {
+        let bank = make_bank();
+        let payer_card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&payer_card, 500).await.unwrap();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap().card();
+        for amount in [10, 20, 30, 40] {
+            bank.new_transaction(&payer_card, &store, amount)
+                .await
+                .unwrap();
+        }
+        let request = |cursor| StatementRequest {
+            card_number: payer_card.clone(),
+            cursor,
+            limit: Some(3),
+            from: None,
+            to: None,
+            direction: Some(Direction::Outgoing),
+            counterparty: None,
+            min_amount: Some(20),
+            max_amount: None,
+        };
+
+        let page = bank.account_statement(&request(None)).await.unwrap();
+        let amounts: Vec<_> =
+            page.transactions.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![20, 30, 40]);
+        assert!(page.next_cursor.is_none());
+
+        let mut req = request(None);
+        req.limit = Some(2);
+        let page = bank.account_statement(&req).await.unwrap();
+        assert_eq!(page.transactions.len(), 2);
+        let page = bank
+            .account_statement(&request(page.next_cursor))
+            .await
+            .unwrap();
+        let amounts: Vec<_> =
+            page.transactions.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![40]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_debits_never_overdraw() {
+        check_concurrent_debits_never_overdraw(make_bank()).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    #[ignore = "requires postgres with `banksim_test` database on localhost"]
+    async fn pg_concurrent_debits_never_overdraw() {
+        let mut settings = make_settings();
+        settings.data_backend_type = crate::config::DataBackendType::Pg;
+        settings.database_settings = Some(crate::config::DatabaseSettings {
+            username: "postgres".to_string(),
+            database_name: "banksim_test".to_string(),
+            host: "localhost".to_string(),
+            password: Secret::new("postgres".to_string()),
+        });
+        let bank = Bank::new::<pg::PostgresStorage>(&settings);
+
+        // Wait for migrations and store accounts
+        for _ in 0..100 {
+            if bank.get_store_account(&TERMINAL_KEY).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        check_concurrent_debits_never_overdraw(bank).await;
+    }
+
+    fn make_sqlite_bank(path: &std::path::Path) -> Bank {
+        let mut settings = make_settings();
+        settings.data_backend_type = crate::config::DataBackendType::Sqlite;
+        settings.sqlite_settings = Some(crate::config::SqliteSettings {
+            path: path.to_path_buf(),
+        });
+        Bank::new::<sqlite::SqliteStorage>(&settings)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn sqlite_concurrent_debits_never_overdraw() {
+        let path = std::env::temp_dir()
+            .join(format!("banksim_{}.sqlite", Uuid::new_v4()));
+        check_concurrent_debits_never_overdraw(make_sqlite_bank(&path)).await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sqlite_refund_returns_acquiring_fee() {
+        let path = std::env::temp_dir()
+            .join(format!("banksim_{}.sqlite", Uuid::new_v4()));
+        check_refund_returns_acquiring_fee(make_sqlite_bank(&path)).await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sqlite_transfer_orders_run_by_schedule() {
+        let path = std::env::temp_dir()
+            .join(format!("banksim_{}.sqlite", Uuid::new_v4()));
+        check_transfer_orders_run_by_schedule(make_sqlite_bank(&path)).await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sqlite_storage_keeps_data_between_restarts() {
+        let path = std::env::temp_dir()
+            .join(format!("banksim_{}.sqlite", Uuid::new_v4()));
+        let pass = Secret::new("pass".to_string());
+
+        let bank = make_sqlite_bank(&path);
+        let store_card = bank.get_store_account(&TERMINAL_KEY).await.unwrap();
+        let card1 = bank.add_account("user1", &pass).await.unwrap();
+        let card2 = bank.add_account("user2", &pass).await.unwrap();
+        bank.open_credit(&card1.card_number, 100).await.unwrap();
+        let id = bank
+            .new_transaction(&card1.card_number, &card2.card_number, 30)
+            .await
+            .unwrap();
+        let token = bank
+            .new_card_token(&card2.card_number, None, Some(TERMINAL_KEY))
+            .await
+            .unwrap();
+        drop(bank);
+
+        let bank = make_sqlite_bank(&path);
+        assert_eq!(
+            bank.get_store_account(&TERMINAL_KEY).await.unwrap().card(),
+            store_card.card()
+        );
+        assert_eq!(bank.balance(&card1.card_number).await.unwrap(), 70);
+        assert_eq!(bank.balance(&card2.card_number).await.unwrap(), 30);
+        assert_eq!(bank.bank_emission().await.unwrap(), -100);
+        assert_eq!(bank.get_transaction(&id).await.unwrap().amount, 30);
+        assert_eq!(
+            bank.get_account_by_token(&token).await.unwrap().card_number,
+            card2.card_number
+        );
+        assert_eq!(
+            bank.get_card_token(&token).await.unwrap().terminal_key,
+            Some(TERMINAL_KEY)
+        );
+        bank.authorize_account(&card1.card_number, &pass)
+            .await
+            .unwrap();
+        bank.authorize_card(
+            &card1.card_number,
+            &pass,
+            card1.expiry,
+            &card1.cvv,
+        )
+        .await
+        .unwrap();
+        assert!(bank
+            .authorize_account(
+                &card1.card_number,
+                &Secret::new("wrong".to_string())
+            )
+            .await
+            .is_err());
+        // Only the import replaces the history
+        let dump = bank.export_dump().await.unwrap();
+        bank.import(&dump).await.unwrap();
+        assert_eq!(bank.balance(&card1.card_number).await.unwrap(), 70);
+        assert_eq!(
+            bank.get_card_token(&token).await.unwrap().terminal_key,
+            Some(TERMINAL_KEY)
+        );
+        drop(bank);
+
+        // History can't be rewritten even outside of the bank
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        assert!(connection.execute("DELETE FROM transactions", []).is_err());
+        drop(connection);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_snapshot_keeps_data_between_restarts() {
+        let path = std::env::temp_dir()
+            .join(format!("banksim_{}.json", Uuid::new_v4()));
+        let mut settings = make_settings();
+        settings.memory_settings = Some(crate::config::MemorySettings {
+            snapshot_path: path.clone(),
+            snapshot_interval_secs: 3600,
+        });
+        let pass = Secret::new("pass".to_string());
+
+        let bank = Bank::new::<MemoryStorage>(&settings);
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap();
+        let card1 = bank.add_account("user1", &pass).await.unwrap();
+        let card2 = bank.add_account("user2", &pass).await.unwrap();
+        bank.open_credit(&card1.card_number, 100).await.unwrap();
+        let id = bank
+            .new_transaction(&card1.card_number, &card2.card_number, 30)
+            .await
+            .unwrap();
+        bank.new_hold(&card1.card_number, 20).await.unwrap();
+        bank.set_overdraft_limit(&card2.card_number, 50)
+            .await
+            .unwrap();
+        let token = bank
+            .new_card_token(&card2.card_number, None, Some(TERMINAL_KEY))
+            .await
+            .unwrap();
+        let emission = bank.bank_emission().await.unwrap();
+        let root = bank.ledger_root().await.unwrap().root;
+        // Secrets stay hashed after the first save, the system
+        // accounts share the same password
+        use std::os::unix::fs::PermissionsExt;
+        for _ in 0..2 {
+            bank.save_snapshot().await.unwrap();
+            let metadata = std::fs::metadata(&path).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+            let data = std::fs::read_to_string(&path).unwrap();
+            assert!(!data.contains("\"password\":\"pass\""));
+            assert!(!data.contains(&format!(
+                "\"cvv\":\"{}\"",
+                card1.cvv.expose_secret()
+            )));
+        }
+        drop(bank);
+
+        let bank = Bank::new::<MemoryStorage>(&settings);
+        assert_eq!(
+            bank.get_store_account(&TERMINAL_KEY).await.unwrap().card(),
+            store.card()
+        );
+        assert_eq!(bank.bank_emission().await.unwrap(), emission);
+        assert_eq!(bank.balance(&card1.card_number).await.unwrap(), 70);
+        assert_eq!(bank.get_transaction(&id).await.unwrap().amount, 30);
+        assert_eq!(bank.ledger_root().await.unwrap().root, root);
+        assert_eq!(
+            bank.get_account_by_token(&token).await.unwrap().card_number,
+            card2.card_number
+        );
+        assert_eq!(
+            bank.get_card_token(&token).await.unwrap().terminal_key,
+            Some(TERMINAL_KEY)
+        );
+        let accounts = bank.list_accounts().await.unwrap();
+        let acc1 = accounts
+            .iter()
+            .find(|acc| acc.card_number.eq(&card1.card_number))
+            .unwrap();
+        assert_eq!(acc1.holds.len(), 1);
+        let acc2 = accounts
+            .iter()
+            .find(|acc| acc.card_number.eq(&card2.card_number))
+            .unwrap();
+        assert_eq!(acc2.overdraft_limit, 50);
+        bank.authorize_card(
+            &card1.card_number,
+            &pass,
+            card1.expiry,
+            &card1.cvv,
+        )
+        .await
+        .unwrap();
+        drop(bank);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn bank_dump_moves_state_to_another_bank() {
+        let pass = Secret::new("pass".to_string());
+        let bank = make_bank();
+        let store = bank.get_store_account(&TERMINAL_KEY).await.unwrap();
+        let card1 = bank.add_account("user1", &pass).await.unwrap();
+        let card2 = bank.add_account("user2", &pass).await.unwrap();
+        bank.open_credit(&card1.card_number, 100).await.unwrap();
+        let id = bank
+            .new_transaction(&card1.card_number, &card2.card_number, 30)
+            .await
+            .unwrap();
+        bank.set_account_status(
+            &card2.card_number,
+            AccountStatus::Frozen,
+            Some("check"),
+        )
+        .await
+        .unwrap();
+        let token = bank
+            .new_card_token(&card2.card_number, None, Some(TERMINAL_KEY))
+            .await
+            .unwrap();
+        let root = bank.ledger_root().await.unwrap().root;
+        let dump = bank.export_dump().await.unwrap();
+        let dump: BankDump =
+            serde_json::from_str(&serde_json::to_string(&dump).unwrap())
+                .unwrap();
+
+        let other = make_bank();
+        let other_card = other.add_account("other", &pass).await.unwrap();
+        other.import(&dump).await.unwrap();
+        assert!(matches!(
+            other.find_account(&other_card.card_number).await,
+            Err(BankOperationError::AccountNotFound)
+        ));
+        assert_eq!(
+            other.get_store_account(&TERMINAL_KEY).await.unwrap().card(),
+            store.card()
+        );
+        assert_eq!(other.balance(&card1.card_number).await.unwrap(), 70);
+        assert_eq!(other.balance(&card2.card_number).await.unwrap(), 30);
+        assert_eq!(other.bank_emission().await.unwrap(), -100);
+        assert_eq!(other.get_transaction(&id).await.unwrap().amount, 30);
+        assert_eq!(other.ledger_root().await.unwrap().root, root);
+        assert_eq!(
+            other.account_status(&card2.card_number).await.unwrap(),
+            AccountStatus::Frozen
+        );
+        assert_eq!(
+            other
+                .get_account_by_token(&token)
+                .await
+                .unwrap()
+                .card_number,
+            card2.card_number
+        );
+        assert_eq!(
+            other.get_card_token(&token).await.unwrap().terminal_key,
+            Some(TERMINAL_KEY)
+        );
+
+        // Secrets are imported as hashes
+        other
+            .authorize_card(&card1.card_number, &pass, card1.expiry, &card1.cvv)
+            .await
+            .unwrap();
+        assert!(other
+            .authorize_account(
+                &card1.card_number,
+                &Secret::new("wrong".to_string())
+            )
+            .await
+            .is_err());
+        let exported = other.export_dump().await.unwrap();
+        assert_eq!(
+            exported
+                .accounts
+                .iter()
+                .find(|acc| acc.card_number.eq(&card1.card_number))
+                .unwrap()
+                .password_hash,
+            dump.accounts
+                .iter()
+                .find(|acc| acc.card_number.eq(&card1.card_number))
+                .unwrap()
+                .password_hash
+        );
+
+        let mut dump = dump;
+        dump.version += 1;
+        assert!(matches!(
+            other.import(&dump).await,
+            Err(BankOperationError::BadOperation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn idempotency_key_replays_stored_response() {
+        let bank = make_bank();
+        assert!(matches!(
+            bank.begin_idempotent_request("key", "hash").await,
+            Ok(IdempotencyStatus::New)
+        ));
+        assert!(matches!(
+            bank.begin_idempotent_request("key", "hash").await,
+            Ok(IdempotencyStatus::InProgress)
+        ));
+
+        let response = IdempotentResponse {
+            status: 200,
+            content_type: None,
+            body: b"ok".to_vec(),
+        };
+        bank.complete_idempotent_request("key", &response)
+            .await
+            .unwrap();
+        assert!(matches!(
+            bank.begin_idempotent_request("key", "hash").await,
+            Ok(IdempotencyStatus::Completed(stored)) if stored.body == b"ok"
+        ));
+        assert!(matches!(
+            bank.begin_idempotent_request("key", "other_hash").await,
+            Ok(IdempotencyStatus::KeyReused)
+        ));
+
+        // Aborted key can be used again
+        bank.begin_idempotent_request("key2", "hash").await.unwrap();
+        bank.abort_idempotent_request("key2").await.unwrap();
+        assert!(matches!(
+            bank.begin_idempotent_request("key2", "other_hash").await,
+            Ok(IdempotencyStatus::New)
+        ));
+    }
+
+    #[tokio::test]
+    async fn ledger_proves_transaction_inclusion() {
+        let bank = make_bank();
+        let card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        let empty = bank.ledger_root().await.unwrap();
+        assert_eq!((empty.root, empty.leaves), (None, 0));
+
+        let mut ids = Vec::new();
+        for amount in 1..=5 {
+            ids.push(bank.open_credit(&card, amount).await.unwrap());
+        }
+        let root = bank.ledger_root().await.unwrap();
+        assert_eq!(root.leaves, 5);
+        for id in ids.iter() {
+            let proof = bank.ledger_proof(id).await.unwrap();
+            assert_eq!(Some(&proof.root), root.root.as_ref());
+            assert!(proof.verify());
+        }
+
+        // Proof of another transaction doesn't fit
+        let mut proof = bank.ledger_proof(&ids[0]).await.unwrap();
+        proof.leaf_hash = bank.ledger_proof(&ids[1]).await.unwrap().leaf_hash;
+        assert!(!proof.verify());
+
+        // Appending changes the root, but not the leaves
+        bank.open_credit(&card, 6).await.unwrap();
+        let new_root = bank.ledger_root().await.unwrap();
+        assert_ne!(new_root.root, root.root);
+        assert!(bank.ledger_proof(&ids[2]).await.unwrap().verify());
+        assert!(matches!(
+            bank.ledger_proof(&Uuid::new_v4()).await,
+            Err(BankOperationError::TransactionNotFound)
+        ));
+    }
+
     #[test]
-    #[ignore]
-    fn learn_merkle_tree_on_practice() {
-        use rs_merkle::algorithms::Sha256;
-
-        let mut tree: MerkleTree<Sha256> = MerkleTree::new();
-        let mut leaves =
-            vec![Sha256::hash("a".as_bytes()), Sha256::hash("b".as_bytes())];
-        tree.append(&mut leaves);
-        let root = tree.root().unwrap_or_default();
-        println!("No leaves: {}", hex::encode(root));
-        tree.commit();
-        let root = tree.root().unwrap();
-        println!("After commit, a, b leaves: {}", hex::encode(root));
-
-        dbg!(tree.leaves());
-
-        let mut leaves = vec![Sha256::hash("c".as_bytes())];
-        tree.append(&mut leaves);
-        let root = tree.root().unwrap_or_default();
-        println!("Before commit with c: {}", hex::encode(root));
-        tree.commit();
-        let root = tree.root().unwrap();
-        println!("After commit with c: {}", hex::encode(root));
+    fn rewritten_history_is_detected() {
+        let account = |name: &str| Account {
+            username: name.to_string(),
+            card_number: CardNumber::generate(&CardSettings::default()),
+            password: Secret::new(String::new()),
+            is_existing: true,
+        };
+        let (a, b) = (account("a"), account("b"));
+        let mut transactions: Vec<_> = (1..=3)
+            .map(|amount| Transaction {
+                id: Uuid::new_v4(),
+                sender: a.clone(),
+                recipient: b.clone(),
+                amount,
+                kind: TransactionKind::Transfer,
+                datetime: OffsetDateTime::now_utc(),
+            })
+            .collect();
+        let mut ledger = Ledger::default();
+        ledger.append(&transactions[..2]).unwrap();
+        ledger.append(&transactions[2..]).unwrap();
+        assert_eq!(ledger.last_transaction(), Some(transactions[2].id));
+        ledger.verify(&transactions).unwrap();
+
+        // Already hashed transactions are returned again,
+        // when the last one was removed
+        let mut other = Ledger::default();
+        other.append(&transactions).unwrap();
+        assert!(matches!(
+            other.append(&transactions[..2]),
+            Err(BankOperationError::LedgerTampered)
+        ));
+
+        transactions[1].kind = TransactionKind::AcquiringFee;
+        assert!(matches!(
+            ledger.verify(&transactions),
+            Err(BankOperationError::LedgerTampered)
+        ));
+        // Ledger stays tampered
+        transactions[1].kind = TransactionKind::Transfer;
+        assert!(matches!(
+            ledger.append(&[]),
+            Err(BankOperationError::LedgerTampered)
+        ));
+    }
+
+    #[tokio::test]
+    async fn audit_reports_violations() {
+        let bank = make_bank();
+        let card = bank
+            .add_account("payer", &Secret::new("pass".to_string()))
+            .await
+            .unwrap()
+            .card_number;
+        bank.open_credit(&card, 100).await.unwrap();
+        bank.new_card_token(&card, None, None).await.unwrap();
+        bank.delete_account(&card).await.unwrap();
+        let report = bank.audit().await.unwrap();
+        assert!(report.consistent, "{:?}", report.violations);
+        assert_eq!((report.transactions, report.tokens), (1, 1));
+
+        let account = |name: &str| Account {
+            username: name.to_string(),
+            card_number: CardNumber::generate(&CardSettings::default()),
+            password: Secret::new(String::new()),
+            is_existing: true,
+        };
+        let (a, b) = (account("a"), account("b"));
+        let now = OffsetDateTime::now_utc();
+        let transactions = vec![Transaction {
+            id: Uuid::new_v4(),
+            sender: a.clone(),
+            recipient: b.clone(),
+            amount: 10,
+            kind: TransactionKind::Transfer,
+            datetime: now,
+        }];
+        let accounts = [
+            AuditedAccount {
+                card_number: a.card(),
+                balance: -10,
+                overdraft_limit: 5,
+                is_emission: false,
+                deleted_at: None,
+            },
+            AuditedAccount {
+                card_number: b.card(),
+                balance: 15,
+                overdraft_limit: 0,
+                is_emission: false,
+                deleted_at: Some(now - Duration::minutes(1)),
+            },
+        ];
+        let tokens = [AuditedToken {
+            token: "lost".to_string(),
+            card_number: None,
+        }];
+        let report = audit::audit(&accounts, &transactions, &tokens);
+        assert!(!report.consistent);
+        assert_eq!(
+            report.violations,
+            vec![
+                AuditViolation::NonZeroSum { total: 5 },
+                AuditViolation::NegativeBalance {
+                    card_number: a.card(),
+                    balance: -10,
+                    overdraft_limit: 5,
+                },
+                AuditViolation::BalanceMismatch {
+                    card_number: b.card(),
+                    stored: 15,
+                    computed: 10,
+                },
+                AuditViolation::TransactionAfterDeletion {
+                    transaction_id: transactions[0].id,
+                    card_number: b.card(),
+                    deleted_at: now - Duration::minutes(1),
+                    datetime: now,
+                },
+                AuditViolation::DanglingToken {
+                    token: "lost".to_string(),
+                },
+            ]
+        );
     }
 }
@@ -1,4 +1,3 @@
-use anyhow::anyhow;
 use anyhow::Context;
 use argon2::password_hash::SaltString;
 use argon2::PasswordHash;
@@ -6,33 +5,59 @@ use argon2::PasswordHasher;
 use argon2::PasswordVerifier;
 use axum::async_trait;
 use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use cornucopia_async::GenericClient;
 use deadpool::managed::Object;
 use deadpool_postgres::Manager;
 use deadpool_postgres::ManagerConfig;
 use deadpool_postgres::Pool;
 use futures::future::try_join_all;
-use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use secrecy::ExposeSecret;
 use secrecy::Secret;
+use std::collections::HashMap;
 use std::sync::Arc;
+use time::Duration;
+use time::OffsetDateTime;
 use tokio::sync::watch::{Receiver, Sender};
 use tokio::task::JoinHandle;
+use tokio_postgres::IsolationLevel;
 use tokio_postgres::NoTls;
 use tracing::Level;
+use uuid::Uuid;
 
 use crate::config::DatabaseSettings;
 use crate::cornucopia::queries::bank_queries;
 use crate::domain::card_number::CardNumber;
+use crate::domain::requests::system_api::StatementRequest;
+use crate::domain::responses::system_api::StatementResponse;
 use crate::middleware::Credentials;
 use crate::Settings;
 
+use super::audit::{self, AuditReport, AuditedAccount, AuditedToken};
 use super::backend::{BankDataBackend, InitBankDataBackend};
+use super::check_fee;
+use super::check_refund_fee;
+use super::dump::{
+    AccountRole, BankDump, DumpAccount, DumpCard, DumpToken, DumpTransaction,
+    DUMP_VERSION,
+};
+use super::generate_cvv;
 use super::generate_token;
+use super::loan::Loan;
+use super::split;
+use super::transfer_order::{OrderExecution, OrderSchedule, TransferOrder};
 use super::Account;
+use super::AccountStatus;
 use super::BankOperationError;
+use super::CardExpiry;
+use super::CardToken;
+use super::Hold;
+use super::IdempotencyStatus;
+use super::IdempotentResponse;
+use super::IssuedCard;
 use super::Transaction;
+use super::HOLD_LIFETIME;
 
 mod db_migration;
 
@@ -45,20 +70,256 @@ pub struct PostgresStorage {
 }
 
 impl PostgresStorage {
-    async fn balance(
+    async fn balance<C: GenericClient>(
         &self,
-        db_client: &Object<Manager>,
+        db_client: &C,
         card: &CardNumber,
     ) -> Result<i64, BankOperationError> {
         let balance = bank_queries::get_account_balance()
             .bind(db_client, &card.as_ref())
             .one()
             .await
-            .context("Failed to get balance from pg for an account")
-            .map(|value| value.to_i64())?;
-        balance.ok_or(BankOperationError::InternalError(anyhow::anyhow!(
-            "Failed to parse Decimal to i64"
-        )))
+            .context("Failed to get balance from pg for an account")?;
+        Ok(balance)
+    }
+
+    /// Balance without funds reserved by holds
+    async fn available_balance<C: GenericClient>(
+        &self,
+        db_client: &C,
+        card: &CardNumber,
+    ) -> Result<i64, BankOperationError> {
+        let balance = self.balance(db_client, card).await?;
+        let held = bank_queries::get_account_held_amount()
+            .bind(db_client, &card.as_ref())
+            .one()
+            .await
+            .context("Failed to get held amount from pg for an account")?
+            .to_i64()
+            .ok_or(BankOperationError::InternalError(anyhow::anyhow!(
+                "Failed to parse Decimal to i64"
+            )))?;
+        Ok(balance - held)
+    }
+
+    /// Account can go negative down to it's overdraft limit
+    async fn check_funds<C: GenericClient>(
+        &self,
+        db_client: &C,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<(), BankOperationError> {
+        let limit = bank_queries::get_account_overdraft_limit()
+            .bind(db_client, &card.as_ref())
+            .one()
+            .await
+            .context("Failed to get overdraft limit from pg for an account")?;
+        if self.available_balance(db_client, card).await? + limit >= amount {
+            Ok(())
+        } else if limit > 0 {
+            Err(BankOperationError::OverdraftLimitExceeded)
+        } else {
+            Err(BankOperationError::NotEnoughFunds)
+        }
+    }
+
+    async fn account_status<C: GenericClient>(
+        &self,
+        db_client: &C,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError> {
+        let status = bank_queries::get_account_status()
+            .bind(db_client, &card.as_ref())
+            .opt()
+            .await
+            .context("Failed to get account status from pg")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        Ok(status.parse()?)
+    }
+
+    /// Generate expiry date, if it is not set, and CVV for the card
+    async fn issue_card<C: GenericClient>(
+        &self,
+        db_client: &C,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let expiry = expiry.unwrap_or_else(|| {
+            CardExpiry::after_months(
+                OffsetDateTime::now_utc(),
+                self.settings.card_settings.validity_months,
+            )
+        });
+        let cvv = generate_cvv();
+        let cvv_hash =
+            hash_password_blocking(self.argon2_obj.clone(), cvv.clone())
+                .await?;
+        bank_queries::set_card_details()
+            .bind(
+                db_client,
+                &(expiry.month as i16),
+                &(expiry.year as i16),
+                &cvv_hash,
+                &card.as_ref(),
+            )
+            .await
+            .context("Failed to set card details in pg")?;
+        Ok(IssuedCard {
+            card_number: card.clone(),
+            expiry,
+            cvv,
+        })
+    }
+
+    /// Lock accounts rows until the end of pg transaction. Every
+    /// debit locks rows in the same order, so they can't deadlock.
+    async fn lock_accounts<C: GenericClient>(
+        &self,
+        db_client: &C,
+        cards: &[&CardNumber],
+    ) -> Result<(), BankOperationError> {
+        let cards: Vec<&str> = cards.iter().map(|card| card.as_ref()).collect();
+        bank_queries::lock_accounts()
+            .bind(db_client, &cards)
+            .all()
+            .await
+            .context("Failed to lock accounts in pg")?;
+        Ok(())
+    }
+
+    /// Move acquiring fee from the payer to the bank revenue account
+    async fn fee_transaction<C: GenericClient>(
+        &self,
+        db_client: &C,
+        payer: &str,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        bank_queries::create_fee_transaction()
+            .bind(db_client, &Uuid::new_v4(), &payer, &fee)
+            .await
+            .map_err(map_transaction_error)?;
+        Ok(())
+    }
+
+    /// Return acquiring fee from the bank revenue account to the payer
+    async fn fee_refund_transaction<C: GenericClient>(
+        &self,
+        db_client: &C,
+        payer: &str,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        bank_queries::create_fee_refund_transaction()
+            .bind(db_client, &Uuid::new_v4(), &payer, &fee)
+            .await
+            .map_err(map_transaction_error)?;
+        Ok(())
+    }
+
+    async fn loan<C: GenericClient>(
+        &self,
+        db_client: &C,
+        id: &Uuid,
+    ) -> Result<Loan, BankOperationError> {
+        let loan = bank_queries::get_loan()
+            .bind(db_client, id)
+            .opt()
+            .await
+            .context("Failed to get loan from pg")?
+            .ok_or(BankOperationError::LoanNotFound)?;
+        Ok(Loan {
+            id: loan.id,
+            card_number: loan.card_number.parse()?,
+            principal: loan.principal,
+            interest_rate: loan.interest_rate,
+            term: u32::try_from(loan.term).context("Bad loan term in pg")?,
+            period: Duration::seconds(loan.period_secs),
+            opened_at: loan.created_at,
+            repaid: loan.repaid,
+        })
+    }
+
+    /// All orders or only due ones, if `due_at` is set
+    async fn transfer_orders<C: GenericClient>(
+        &self,
+        db_client: &C,
+        due_at: Option<OffsetDateTime>,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let rows = bank_queries::get_transfer_orders()
+            .bind(db_client, &due_at)
+            .all()
+            .await
+            .context("Failed to get transfer orders from pg")?;
+        let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let mut executions: HashMap<Uuid, Vec<OrderExecution>> = HashMap::new();
+        for execution in bank_queries::get_order_executions()
+            .bind(db_client, &ids)
+            .all()
+            .await
+            .context("Failed to get order executions from pg")?
+        {
+            executions.entry(execution.order_id).or_default().push(
+                OrderExecution {
+                    executed_at: execution.executed_at,
+                    transaction_id: execution.transaction_id,
+                    error: execution.error,
+                },
+            );
+        }
+
+        let mut orders = Vec::with_capacity(rows.len());
+        for row in rows {
+            let schedule = match (row.run_at, row.cron) {
+                (Some(at), _) => OrderSchedule::Once(at),
+                (None, Some(cron)) => OrderSchedule::Cron(
+                    cron.parse().context("Bad order cron in pg")?,
+                ),
+                (None, None) => {
+                    return Err(anyhow::anyhow!(
+                        "Transfer order without schedule in pg"
+                    )
+                    .into())
+                }
+            };
+            orders.push(TransferOrder {
+                id: row.id,
+                sender: row.sender.parse()?,
+                recipient: row.recipient.parse()?,
+                amount: row.amount,
+                schedule,
+                created_at: row.created_at,
+                next_run_at: row.next_run_at,
+                cancelled_at: row.cancelled_at,
+                executions: executions.remove(&row.id).unwrap_or_default(),
+            });
+        }
+        Ok(orders)
+    }
+
+    async fn account_holds(
+        &self,
+        db_client: &Object<Manager>,
+        card: &CardNumber,
+    ) -> Result<Vec<Hold>, BankOperationError> {
+        Ok(bank_queries::list_account_holds()
+            .bind(db_client, &card.as_ref())
+            .all()
+            .await
+            .context("Failed to get account holds list from the pg")?
+            .into_iter()
+            .map(|h| Hold {
+                id: h.id,
+                card_number: card.clone(),
+                amount: h.amount,
+                datetime: h.created_at,
+                expires_at: h.expires_at,
+            })
+            .collect())
     }
 
     async fn account_transactions(
@@ -74,6 +335,7 @@ impl PostgresStorage {
             .into_iter()
             .map(|t| {
                 Ok(Transaction {
+                    id: t.transaction_id,
                     sender: Account {
                         username: t.sender_username,
                         card_number: t.sender_card_number.parse()?,
@@ -87,6 +349,7 @@ impl PostgresStorage {
                         is_existing: t.recipient_is_existing,
                     },
                     amount: t.amount,
+                    kind: t.kind.parse()?,
                     datetime: t.created_at,
                 })
             })
@@ -124,17 +387,41 @@ impl PostgresStorage {
             })?
     }
 
+    async fn card_token(
+        &self,
+        db_client: &Object<Manager>,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError> {
+        let token = bank_queries::get_token()
+            .bind(db_client, &token)
+            .opt()
+            .await
+            .context("Failed to get card token from pg")?
+            .ok_or(BankOperationError::TokenNotFound)?;
+        Ok(CardToken {
+            token: token.token,
+            card_number: token.card_number.parse()?,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            revoked_at: token.revoked_at,
+            terminal_key: token.terminal_key,
+        })
+    }
+
     async fn get_account_by_token(
         &self,
         db_client: &Object<Manager>,
         token: &str,
     ) -> Result<Account, BankOperationError> {
+        self.card_token(db_client, token)
+            .await?
+            .check_active(OffsetDateTime::now_utc())?;
         bank_queries::get_account_by_token()
             .bind(db_client, &token)
             .opt()
             .await
             .context("Failed to find and account by token in pg")?
-            .ok_or(BankOperationError::AccountNotFound)
+            .ok_or(BankOperationError::TokenNotFound)
             .map(|acc| {
                 Ok(Account {
                     username: acc.username,
@@ -145,6 +432,41 @@ impl PostgresStorage {
             })?
     }
 
+    /// Transactions, stored after the `last` one, or all of them
+    async fn transactions<C: GenericClient>(
+        &self,
+        db_client: &C,
+        last: Option<Uuid>,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let mut transactions = Vec::new();
+        for t in bank_queries::list_transactions()
+            .bind(db_client, &last)
+            .all()
+            .await
+            .context("Failed to get transactions from the pg")?
+        {
+            transactions.push(Transaction {
+                id: t.transaction_id,
+                sender: Account {
+                    username: t.sender_username,
+                    card_number: t.sender_card_number.parse()?,
+                    password: Secret::new(String::new()),
+                    is_existing: t.sender_is_existing,
+                },
+                recipient: Account {
+                    username: t.recipient_username,
+                    card_number: t.recipient_card_number.parse()?,
+                    password: Secret::new(String::new()),
+                    is_existing: t.recipient_is_existing,
+                },
+                amount: t.amount,
+                kind: t.kind.parse()?,
+                datetime: t.created_at,
+            });
+        }
+        Ok(transactions)
+    }
+
     async fn emission_account(
         &self,
         db_client: &Object<Manager>,
@@ -174,12 +496,7 @@ impl InitBankDataBackend for PostgresStorage {
             settings.database_settings.as_ref().unwrap(),
         );
 
-        let argon2_obj = argon2::Argon2::new(
-            argon2::Algorithm::Argon2id,
-            argon2::Version::V0x13,
-            // Params are good
-            argon2::Params::new(15000, 2, 1, None).unwrap(),
-        );
+        let argon2_obj = argon2_obj();
 
         let pg_pool_copy = pg_pool.clone();
         let settings_copy = settings.clone();
@@ -197,7 +514,9 @@ impl InitBankDataBackend for PostgresStorage {
             if accounts_count == 0 {
                 tracing::info!("No system accounts found, creating ones...");
                 let emission_account = Account {
-                    card_number: CardNumber::generate(),
+                    card_number: CardNumber::generate(
+                        &settings_copy.card_settings,
+                    ),
                     password: Secret::new(
                         hash_password_blocking(
                             argon2_obj_copy.clone(),
@@ -211,10 +530,12 @@ impl InitBankDataBackend for PostgresStorage {
                 };
 
                 let store_account = Account {
-                    card_number: CardNumber::generate(),
+                    card_number: CardNumber::generate(
+                        &settings_copy.card_settings,
+                    ),
                     password: Secret::new(
                         hash_password_blocking(
-                            argon2_obj_copy,
+                            argon2_obj_copy.clone(),
                             settings_copy.terminal_settings.password.clone(),
                         )
                         .await
@@ -246,6 +567,112 @@ impl InitBankDataBackend for PostgresStorage {
             } else {
                 tracing::info!("System accounts already exists in db!");
             }
+
+            if bank_queries::get_revenue_account()
+                .bind(&connection)
+                .opt()
+                .await
+                .expect("Failed to check revenue account")
+                .is_none()
+            {
+                // Revenue account, created before system accounts
+                // were marked, is found by it's username
+                let revenue_username = settings_copy.revenue_username();
+                let legacy_revenue = bank_queries::get_account_by_username()
+                    .bind(&connection, &revenue_username)
+                    .opt()
+                    .await
+                    .expect("Failed to check legacy revenue account");
+                let card_number = match legacy_revenue {
+                    Some(card_number) => card_number,
+                    None => {
+                        let password_hash = hash_password_blocking(
+                            argon2_obj_copy.clone(),
+                            settings_copy.terminal_settings.password.clone(),
+                        )
+                        .await
+                        .unwrap();
+                        let card_number =
+                            CardNumber::generate(&settings_copy.card_settings);
+                        bank_queries::insert_account()
+                            .bind(
+                                &connection,
+                                &revenue_username,
+                                &card_number.as_ref(),
+                                &password_hash,
+                            )
+                            .await
+                            .unwrap();
+                        card_number.as_ref().to_string()
+                    }
+                };
+                bank_queries::mark_revenue_account()
+                    .bind(&connection, &card_number)
+                    .await
+                    .unwrap();
+                tracing::info!("Revenue account is ready");
+            }
+
+            // Every terminal should have it's own store account
+            for terminal in settings_copy.all_terminals() {
+                if bank_queries::get_terminal_store_account()
+                    .bind(&connection, &terminal.terminal_key)
+                    .opt()
+                    .await
+                    .expect("Failed to check terminal store account")
+                    .is_some()
+                {
+                    continue;
+                }
+
+                // Default terminal takes the store account, created
+                // before terminals were introduced
+                let legacy_store = if terminal
+                    .terminal_key
+                    .eq(&settings_copy.terminal_settings.terminal_key)
+                {
+                    bank_queries::get_legacy_store_account()
+                        .bind(&connection)
+                        .opt()
+                        .await
+                        .expect("Failed to check legacy store account")
+                } else {
+                    None
+                };
+
+                let card_number = match legacy_store {
+                    Some(acc) => acc.card_number,
+                    None => {
+                        let card_number =
+                            CardNumber::generate(&settings_copy.card_settings);
+                        let password_hash = hash_password_blocking(
+                            argon2_obj_copy.clone(),
+                            terminal.password.clone(),
+                        )
+                        .await
+                        .unwrap();
+                        bank_queries::insert_account()
+                            .bind(
+                                &connection,
+                                &settings_copy.store_username(terminal),
+                                &card_number.as_ref(),
+                                &password_hash,
+                            )
+                            .await
+                            .unwrap();
+                        card_number.as_ref().to_string()
+                    }
+                };
+
+                bank_queries::insert_terminal()
+                    .bind(&connection, &terminal.terminal_key, &card_number)
+                    .await
+                    .unwrap();
+                tracing::info!(
+                    "Store account for terminal {} is ready",
+                    terminal.terminal_key
+                );
+            }
         });
 
         Arc::new(PostgresStorage {
@@ -298,25 +725,98 @@ impl BankDataBackend for PostgresStorage {
         &self,
         username: &str,
         password: &Secret<String>,
-    ) -> Result<CardNumber, BankOperationError> {
+    ) -> Result<IssuedCard, BankOperationError> {
         let argon2_obj = self.argon2_obj.clone();
         let password = password.clone();
         let password_hash =
             hash_password_blocking(argon2_obj, password).await?;
 
-        let db_client = self
+        let mut db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
-        let card_number = CardNumber::generate();
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let card_number = CardNumber::generate(&self.settings.card_settings);
         bank_queries::insert_account()
-            .bind(&db_client, &username, &card_number.as_ref(), &password_hash)
+            .bind(
+                &transaction,
+                &username,
+                &card_number.as_ref(),
+                &password_hash,
+            )
             .await
             .context("Failed to insert a new account to pg")?;
+        let card = self.issue_card(&transaction, &card_number, None).await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(card)
+    }
+
+    async fn reissue_card(
+        &self,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        let account = self.find_account(&db_client, card).await?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
+        }
+        // System accounts are hidden from clients
+        if account.username.eq(&self.settings.bank_username)
+            || account.username.eq(&self.settings.revenue_username())
+        {
+            return Err(BankOperationError::AccountNotFound);
+        }
+        if bank_queries::is_store_account()
+            .bind(&db_client, &card.as_ref())
+            .one()
+            .await
+            .context("Failed to check store account in pg")?
+        {
+            return Err(BankOperationError::BadOperation(
+                "Can't issue card for store account".to_string(),
+            ));
+        }
 
+        let card = self.issue_card(&db_client, card, expiry).await?;
         self.notify();
-        Ok(card_number)
+        Ok(card)
+    }
+
+    async fn card_expiry(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Option<CardExpiry>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        let account = bank_queries::get_account()
+            .bind(&db_client, &card.as_ref())
+            .opt()
+            .await
+            .context("Failed to get account from pg")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        Ok(account.card_expiry_month.zip(account.card_expiry_year).map(
+            |(month, year)| CardExpiry {
+                month: month as u8,
+                year: year as u16,
+            },
+        ))
     }
 
     async fn delete_account(
@@ -337,6 +837,17 @@ impl BankDataBackend for PostgresStorage {
             return Err(BankOperationError::AccountNotFound);
         }
 
+        if bank_queries::is_store_account()
+            .bind(&db_client, &card.as_ref())
+            .one()
+            .await
+            .context("Failed to check store account in pg")?
+        {
+            return Err(BankOperationError::BadOperation(
+                "Can't delete store account".to_string(),
+            ));
+        }
+
         bank_queries::mark_account_as_deleted()
             .bind(&db_client, &card.as_ref())
             .await
@@ -345,6 +856,75 @@ impl BankDataBackend for PostgresStorage {
         Ok(())
     }
 
+    async fn restore_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        let is_existing = bank_queries::is_account_exists()
+            .bind(&db_client, &card.as_ref())
+            .opt()
+            .await
+            .context("Failed to fetch account info from pg")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        if is_existing {
+            return Err(BankOperationError::BadOperation(
+                "Account is not deleted".to_string(),
+            ));
+        }
+
+        bank_queries::mark_account_as_restored()
+            .bind(&db_client, &card.as_ref())
+            .await
+            .context("Failed to restore account in pg")?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn set_account_status(
+        &self,
+        card: &CardNumber,
+        status: AccountStatus,
+        reason: Option<&str>,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        let account = self.find_account(&db_client, card).await?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
+        }
+        // System accounts are hidden from clients
+        if account.username.eq(&self.settings.bank_username)
+            || account.username.eq(&self.settings.revenue_username())
+        {
+            return Err(BankOperationError::AccountNotFound);
+        }
+        if bank_queries::is_store_account()
+            .bind(&db_client, &card.as_ref())
+            .one()
+            .await
+            .context("Failed to check store account in pg")?
+        {
+            return Err(BankOperationError::BadOperation(
+                "Can't change status of store account".to_string(),
+            ));
+        }
+
+        bank_queries::set_account_status()
+            .bind(&db_client, &status.as_str(), &reason, &card.as_ref())
+            .await
+            .context("Failed to set account status in pg")?;
+        self.notify();
+        Ok(())
+    }
+
     async fn list_accounts(
         &self,
     ) -> Result<
@@ -370,13 +950,14 @@ impl BankDataBackend for PostgresStorage {
                 >(
                     crate::domain::responses::system_api::Account {
                         card_number,
-                        balance: acc.balance.to_i64().ok_or(
-                            BankOperationError::InternalError(anyhow!(
-                                "Failed to parse Decimal into i64"
-                            )),
-                        )?,
+                        balance: acc.balance,
+                        overdraft_limit: acc.overdraft_limit,
+                        debt: (-acc.balance).max(0),
                         transactions: Vec::new(),
+                        holds: Vec::new(),
                         exists: acc.is_existing,
+                        status: acc.status.parse()?,
+                        status_reason: acc.status_reason,
                         tokens: acc.tokens.into_iter().flatten().collect(),
                         username: acc.username,
                     },
@@ -385,16 +966,17 @@ impl BankDataBackend for PostgresStorage {
         let accounts = try_join_all(accounts).await?;
         let mut result = Vec::with_capacity(accounts.len());
         for mut account in accounts.into_iter() {
-            // Skip store and emission accounts
-            if account.username.eq(&self.settings.bank_username)
-                || account.username.eq("store")
-            {
+            // Skip emission account, store and revenue accounts
+            // are already filtered out by the query
+            if account.username.eq(&self.settings.bank_username) {
                 continue;
             }
             let transactions = self
                 .account_transactions(&db_client, &account.card_number)
                 .await?;
             account.transactions = transactions;
+            account.holds =
+                self.account_holds(&db_client, &account.card_number).await?;
             result.push(account);
         }
         result.sort_by(|acc1, acc2| acc1.username.cmp(&acc2.username));
@@ -430,28 +1012,105 @@ impl BankDataBackend for PostgresStorage {
         Ok(account)
     }
 
-    async fn find_account(
+    async fn authorize_card(
         &self,
         card: &CardNumber,
+        password: &Secret<String>,
+        expiry: CardExpiry,
+        cvv: &Secret<String>,
     ) -> Result<Account, BankOperationError> {
         let db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
-        self.find_account(&db_client, card).await
+        let account = bank_queries::get_account()
+            .bind(&db_client, &card.as_ref())
+            .opt()
+            .await
+            .context("Failed to get account from pg")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
+        }
+
+        verify_password_hash_blocking(
+            Secret::new(account.password_hash.clone()),
+            password.clone(),
+            self.argon2_obj.clone(),
+        )
+        .await?;
+
+        let (Some(month), Some(year), Some(cvv_hash)) = (
+            account.card_expiry_month,
+            account.card_expiry_year,
+            account.cvv_hash.clone(),
+        ) else {
+            return Err(BankOperationError::BadOperation(
+                "Card details are not issued".to_string(),
+            ));
+        };
+        let card_expiry = CardExpiry {
+            month: month as u8,
+            year: year as u16,
+        };
+        if card_expiry != expiry {
+            return Err(BankOperationError::InvalidExpiry);
+        }
+        if card_expiry.is_expired(OffsetDateTime::now_utc()) {
+            return Err(BankOperationError::CardExpired);
+        }
+        verify_password_hash_blocking(
+            Secret::new(cvv_hash),
+            cvv.clone(),
+            self.argon2_obj.clone(),
+        )
+        .await
+        .map_err(|_| BankOperationError::InvalidCvv)?;
+
+        let account = account.try_into()?;
+        Ok(account)
     }
 
-    async fn get_store_account(&self) -> Result<Account, BankOperationError> {
-        let db_client = self
-            .pg_pool
-            .get()
+    async fn find_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Account, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
-        bank_queries::get_store_account()
-            .bind(&db_client)
-            .one()
+        self.find_account(&db_client, card).await
+    }
+
+    async fn account_status(
+        &self,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        self.account_status(&db_client, card).await
+    }
+
+    async fn get_store_account(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<Account, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        bank_queries::get_terminal_store_account()
+            .bind(&db_client, terminal_key)
+            .opt()
             .await
+            .context("Failed to find terminal store account in pg")?
+            .ok_or(BankOperationError::TerminalNotFound)
             .map(|acc| {
                 Ok(Account {
                     username: acc.username,
@@ -459,12 +1118,14 @@ impl BankDataBackend for PostgresStorage {
                     password: Secret::new(String::new()),
                     is_existing: acc.is_existing,
                 })
-            })
-            .context("Failed to find an account by card number in pg")?
+            })?
     }
 
-    async fn store_balance(&self) -> Result<i64, BankOperationError> {
-        let store_acc = self.get_store_account().await?;
+    async fn store_balance(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<i64, BankOperationError> {
+        let store_acc = self.get_store_account(terminal_key).await?;
         let db_client = self
             .pg_pool
             .get()
@@ -494,37 +1155,71 @@ impl BankDataBackend for PostgresStorage {
         sender: &CardNumber,
         recipient: &CardNumber,
         amount: i64,
-    ) -> Result<(), BankOperationError> {
+    ) -> Result<Uuid, BankOperationError> {
         let db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
-        match bank_queries::create_transaction()
-            .bind(&db_client, &sender.as_ref(), &recipient.as_ref(), &amount)
+        let id = Uuid::new_v4();
+        bank_queries::create_transaction()
+            .bind(
+                &db_client,
+                &id,
+                &sender.as_ref(),
+                &recipient.as_ref(),
+                &amount,
+            )
             .await
-        {
-            Ok(_) => (),
-            Err(e) => {
-                tracing::error!("Failed to create transaction: {e}");
-                if let Some(db_error) = e.as_db_error() {
-                    if db_error.message().eq("Not enough funds") {
-                        return Err(BankOperationError::NotEnoughFunds);
-                    }
-                    match db_error.message() {
-                        "Not enough funds" => return Err(BankOperationError::NotEnoughFunds),
-                        "Amount must be greater than 0" => return Err(BankOperationError::BadTransaction),
-                        "Sender and recipient cannot be the same" => return Err(BankOperationError::BadTransaction),
-                        "Sender or recipient account does not exist or is not active" => return Err(BankOperationError::AccountNotFound),
-                        _ => ()
-                    }
-                }
-                return Err(BankOperationError::UnexpectedError);
-            }
-        }
+            .map_err(map_transaction_error)?;
 
         self.notify();
-        Ok(())
+        Ok(id)
+    }
+
+    async fn new_payment(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        check_fee(amount, fee)?;
+
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        // Fee is taken only with the payment itself
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        self.lock_accounts(&transaction, &[sender, recipient])
+            .await?;
+        self.check_funds(&transaction, sender, amount).await?;
+        let id = Uuid::new_v4();
+        bank_queries::create_transaction()
+            .bind(
+                &transaction,
+                &id,
+                &sender.as_ref(),
+                &recipient.as_ref(),
+                &(amount - fee),
+            )
+            .await
+            .map_err(map_transaction_error)?;
+        self.fee_transaction(&transaction, sender.as_ref(), fee)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(id)
     }
 
     #[tracing::instrument(name = "Try create new split transaction", skip_all)]
@@ -532,22 +1227,27 @@ impl BankDataBackend for PostgresStorage {
         &self,
         sender: &CardNumber,
         amount: i64,
+        fee: i64,
         beneficiaries: &Beneficiaries,
-    ) -> Result<(), BankOperationError> {
+    ) -> Result<Vec<Uuid>, BankOperationError> {
         beneficiaries
             .validate()
             .map_err(|_| BankOperationError::BadTransaction)?;
 
-        let db_client = self
+        let mut db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
 
+        check_fee(amount, fee)?;
+
+        // Beneficiaries share the amount without the fee
         let mut bfc = Vec::with_capacity(beneficiaries.count());
-        for (token, part) in beneficiaries.iter_tokens() {
+        for (token, amount) in split::split_amount(amount - fee, beneficiaries)?
+        {
             let acc = self.get_account_by_token(&db_client, token).await?;
-            bfc.push((acc, part));
+            bfc.push((acc, amount));
         }
 
         if bfc
@@ -562,60 +1262,385 @@ impl BankDataBackend for PostgresStorage {
         // Find sender
         let _ = self.find_account(&db_client, sender).await?;
 
-        if self.balance(&db_client, &sender).await? < amount {
-            return Err(BankOperationError::NotEnoughFunds);
+        // Either every beneficiary gets it's part, or nobody does
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let mut cards: Vec<_> =
+            bfc.iter().map(|(acc, _)| &acc.card_number).collect();
+        cards.push(sender);
+        self.lock_accounts(&transaction, &cards).await?;
+
+        self.check_funds(&transaction, sender, amount).await?;
+
+        let mut ids = Vec::with_capacity(bfc.len());
+        for (recipient, amount) in bfc.iter() {
+            let id = Uuid::new_v4();
+            bank_queries::create_transaction()
+                .bind(
+                    &transaction,
+                    &id,
+                    &sender.as_ref(),
+                    &recipient.card_number.as_ref(),
+                    amount,
+                )
+                .await
+                .map_err(map_transaction_error)?;
+            ids.push(id);
+        }
+        self.fee_transaction(&transaction, sender.as_ref(), fee)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(ids)
+    }
+
+    async fn new_refund(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        check_refund_fee(amount, fee)?;
+
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        // Fee is returned only with the refund itself
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        self.lock_accounts(&transaction, &[sender, recipient])
+            .await?;
+        if amount > fee {
+            bank_queries::create_transaction()
+                .bind(
+                    &transaction,
+                    &Uuid::new_v4(),
+                    &sender.as_ref(),
+                    &recipient.as_ref(),
+                    &(amount - fee),
+                )
+                .await
+                .map_err(map_transaction_error)?;
+        }
+        self.fee_refund_transaction(&transaction, recipient.as_ref(), fee)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "Try create new split refund transaction",
+        skip_all
+    )]
+    async fn new_split_refund_transaction(
+        &self,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        // Find recipient
+        let _ = self.find_account(&db_client, recipient).await?;
+
+        check_refund_fee(amount, fee)?;
+
+        // Beneficiaries return the amount without the fee
+        let mut bfc = Vec::with_capacity(beneficiaries.count());
+        for (token, amount) in split::split_amount(amount - fee, beneficiaries)?
+        {
+            let acc = self.get_account_by_token(&db_client, token).await?;
+            bfc.push((acc, amount));
+        }
+
+        // Either every beneficiary returns it's part, or nobody does
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let mut cards: Vec<_> =
+            bfc.iter().map(|(acc, _)| &acc.card_number).collect();
+        cards.push(recipient);
+        self.lock_accounts(&transaction, &cards).await?;
+        for (sender, amount) in bfc.iter() {
+            bank_queries::create_transaction()
+                .bind(
+                    &transaction,
+                    &Uuid::new_v4(),
+                    &sender.card_number.as_ref(),
+                    &recipient.as_ref(),
+                    amount,
+                )
+                .await
+                .map_err(map_transaction_error)?;
+        }
+        self.fee_refund_transaction(&transaction, recipient.as_ref(), fee)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Try to create new funds hold", skip(self))]
+    async fn new_hold(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let account = self.find_account(&db_client, card).await?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
         }
 
         if amount <= 0 {
             return Err(BankOperationError::BadTransaction);
         }
 
-        let amount = Decimal::from_i64(amount).ok_or(
-            BankOperationError::BadOperation(
-                "Can't convert money correctly".to_string(),
-            ),
-        )?;
+        // Check and reserve funds under the account lock
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        self.lock_accounts(&transaction, &[card]).await?;
+        self.account_status(&transaction, card)
+            .await?
+            .check_can_send()?;
+        self.check_funds(&transaction, card, amount).await?;
 
-        for (recipient, part) in bfc.into_iter() {
-            let amount = (amount * part).round().to_i64().ok_or(
-                BankOperationError::BadOperation(
-                    "Can't convert money correctly".to_string(),
-                ),
-            )?;
-            self.new_transaction(sender, &recipient.card_number, amount)
-                .await?;
+        let id = Uuid::new_v4();
+        let expires_at = OffsetDateTime::now_utc() + HOLD_LIFETIME;
+        bank_queries::insert_hold()
+            .bind(&transaction, &id, &card.as_ref(), &amount, &expires_at)
+            .await
+            .context("Failed to insert funds hold into pg")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+        self.notify();
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "Try to release funds hold", skip(self))]
+    async fn release_hold(&self, hold: Uuid) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let deleted = bank_queries::delete_hold()
+            .bind(&db_client, &hold)
+            .await
+            .context("Failed to delete funds hold from pg")?;
+        if deleted == 0 {
+            return Err(BankOperationError::HoldNotFound);
         }
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Release all funds holds", skip(self))]
+    async fn release_all_holds(&self) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
 
+        bank_queries::delete_all_holds()
+            .bind(&db_client)
+            .await
+            .context("Failed to delete funds holds from pg")?;
         self.notify();
         Ok(())
     }
 
-    async fn open_credit(
+    #[tracing::instrument(name = "Try to capture funds hold", skip(self))]
+    async fn capture_hold(
         &self,
-        card: &CardNumber,
+        hold: Uuid,
+        recipient: &CardNumber,
         amount: i64,
+        fee: i64,
     ) -> Result<(), BankOperationError> {
-        let db_client = self
+        check_fee(amount, fee)?;
+
+        let mut db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
 
-        let emission_account = self.emission_account(&db_client).await?;
-        let _ = self
-            .new_transaction(&emission_account.card_number, card, amount)
+        // Hold is removed only if the transaction succeeds
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let held = bank_queries::get_hold()
+            .bind(&transaction, &hold)
+            .opt()
+            .await
+            .context("Failed to get funds hold from pg")?
+            .ok_or(BankOperationError::HoldNotFound)?;
+        if amount > held.amount {
+            return Err(BankOperationError::BadTransaction);
+        }
+        let payer: CardNumber = held.card_number.parse()?;
+        self.lock_accounts(&transaction, &[&payer, recipient])
+            .await?;
+        // Concurrent capture could take the hold first
+        let deleted = bank_queries::delete_hold()
+            .bind(&transaction, &hold)
+            .await
+            .context("Failed to delete funds hold from pg")?;
+        if deleted == 0 {
+            return Err(BankOperationError::HoldNotFound);
+        }
+        bank_queries::create_transaction()
+            .bind(
+                &transaction,
+                &Uuid::new_v4(),
+                &held.card_number,
+                &recipient.as_ref(),
+                &(amount - fee),
+            )
+            .await
+            .map_err(map_transaction_error)?;
+        self.fee_transaction(&transaction, &held.card_number, fee)
             .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
         self.notify();
         Ok(())
     }
 
-    async fn list_transactions(
+    #[tracing::instrument(name = "Try to capture split funds hold", skip_all)]
+    async fn capture_split_hold(
         &self,
-    ) -> Result<Vec<Transaction>, BankOperationError> {
-        Ok(Vec::new())
+        hold: Uuid,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        check_fee(amount, fee)?;
+
+        // Beneficiaries share the amount without the fee
+        let mut bfc = Vec::with_capacity(beneficiaries.count());
+        for (token, amount) in split::split_amount(amount - fee, beneficiaries)?
+        {
+            let acc = self.get_account_by_token(&db_client, token).await?;
+            bfc.push((acc, amount));
+        }
+
+        // Hold is removed only if all transactions succeed
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let held = bank_queries::get_hold()
+            .bind(&transaction, &hold)
+            .opt()
+            .await
+            .context("Failed to get funds hold from pg")?
+            .ok_or(BankOperationError::HoldNotFound)?;
+        if amount > held.amount
+            || bfc
+                .iter()
+                .any(|(acc, _)| acc.card_number.as_ref().eq(&held.card_number))
+        {
+            return Err(BankOperationError::BadTransaction);
+        }
+        let payer: CardNumber = held.card_number.parse()?;
+        let mut cards: Vec<_> =
+            bfc.iter().map(|(acc, _)| &acc.card_number).collect();
+        cards.push(&payer);
+        self.lock_accounts(&transaction, &cards).await?;
+        // Concurrent capture could take the hold first
+        let deleted = bank_queries::delete_hold()
+            .bind(&transaction, &hold)
+            .await
+            .context("Failed to delete funds hold from pg")?;
+        if deleted == 0 {
+            return Err(BankOperationError::HoldNotFound);
+        }
+        for (recipient, amount) in bfc.iter() {
+            bank_queries::create_transaction()
+                .bind(
+                    &transaction,
+                    &Uuid::new_v4(),
+                    &held.card_number,
+                    &recipient.card_number.as_ref(),
+                    amount,
+                )
+                .await
+                .map_err(map_transaction_error)?;
+        }
+        self.fee_transaction(&transaction, &held.card_number, fee)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(())
     }
 
-    async fn bank_emission(&self) -> Result<i64, BankOperationError> {
+    async fn open_credit(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
         let db_client = self
             .pg_pool
             .get()
@@ -623,43 +1648,1060 @@ impl BankDataBackend for PostgresStorage {
             .context("Failed to get a pg client from pg pool")?;
 
         let emission_account = self.emission_account(&db_client).await?;
-        self.balance(&db_client, &emission_account.card_number)
-            .await
+        let id = self
+            .new_transaction(&emission_account.card_number, card, amount)
+            .await?;
+        self.notify();
+        Ok(id)
     }
 
-    async fn new_card_token(
+    #[tracing::instrument(name = "Try to open a new loan", skip(self))]
+    async fn open_loan(
         &self,
         card: &CardNumber,
-    ) -> Result<String, BankOperationError> {
-        let db_client = self
+        principal: i64,
+        interest_rate: Decimal,
+        term: u32,
+        period: Duration,
+    ) -> Result<Loan, BankOperationError> {
+        let mut db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
 
-        let token = generate_token();
+        let account = self.find_account(&db_client, card).await?;
+        let loan = Loan {
+            id: Uuid::new_v4(),
+            card_number: account.card_number,
+            principal,
+            interest_rate,
+            term,
+            period,
+            opened_at: OffsetDateTime::now_utc(),
+            repaid: 0,
+        };
+        loan.validate()?;
+        let term = i32::try_from(term).map_err(|_| {
+            BankOperationError::BadOperation(
+                "Loan term is too long".to_string(),
+            )
+        })?;
+        let emission_account = self.emission_account(&db_client).await?;
 
-        // Check that card exists
-        let _ = self.find_account(&db_client, card).await?;
-        bank_queries::insert_token()
-            .bind(&db_client, &card.as_ref(), &token)
+        // Loan is recorded only with it's funds transaction
+        let transaction = db_client
+            .transaction()
             .await
-            .context("Failed to insert new card token into pg")?;
+            .context("Failed to begin pg transaction")?;
+        bank_queries::create_transaction()
+            .bind(
+                &transaction,
+                &Uuid::new_v4(),
+                &emission_account.card_number.as_ref(),
+                &card.as_ref(),
+                &principal,
+            )
+            .await
+            .map_err(map_transaction_error)?;
+        bank_queries::insert_loan()
+            .bind(
+                &transaction,
+                &loan.id,
+                &loan.opened_at,
+                &card.as_ref(),
+                &principal,
+                &interest_rate,
+                &term,
+                &period.whole_seconds(),
+            )
+            .await
+            .context("Failed to insert loan into pg")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
         self.notify();
-        Ok(token)
+        Ok(loan)
     }
 
-    async fn get_account_by_token(
+    #[tracing::instrument(name = "Try to repay a loan", skip(self))]
+    async fn repay_loan(
         &self,
-        token: &str,
-    ) -> Result<Account, BankOperationError> {
-        let db_client = self
+        id: &Uuid,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let mut db_client = self
             .pg_pool
             .get()
             .await
             .context("Failed to get a pg client from pg pool")?;
-        self.get_account_by_token(&db_client, token).await
-    }
+
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+        let emission_account = self.emission_account(&db_client).await?;
+
+        // Concurrent repayments of the loan wait for each other
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        bank_queries::lock_loan()
+            .bind(&transaction, id)
+            .opt()
+            .await
+            .context("Failed to lock loan in pg")?
+            .ok_or(BankOperationError::LoanNotFound)?;
+        let loan = self.loan(&transaction, id).await?;
+        if amount > loan.outstanding()? {
+            return Err(BankOperationError::BadOperation(
+                "Repayment exceeds outstanding loan amount".to_string(),
+            ));
+        }
+
+        let transaction_id = Uuid::new_v4();
+        bank_queries::create_transaction()
+            .bind(
+                &transaction,
+                &transaction_id,
+                &loan.card_number.as_ref(),
+                &emission_account.card_number.as_ref(),
+                &amount,
+            )
+            .await
+            .map_err(map_transaction_error)?;
+        bank_queries::add_loan_repayment()
+            .bind(&transaction, &amount, id)
+            .await
+            .context("Failed to update loan in pg")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(transaction_id)
+    }
+
+    async fn get_loan(&self, id: &Uuid) -> Result<Loan, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        self.loan(&db_client, id).await
+    }
+
+    async fn new_transfer_order(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        schedule: OrderSchedule,
+    ) -> Result<TransferOrder, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        for card in [sender, recipient] {
+            if !self.find_account(&db_client, card).await?.is_existing {
+                return Err(BankOperationError::AccountIsDeleted);
+            }
+        }
+        let order = TransferOrder::new(
+            sender.clone(),
+            recipient.clone(),
+            amount,
+            schedule,
+            OffsetDateTime::now_utc(),
+        )?;
+        let (run_at, cron) = match order.schedule {
+            OrderSchedule::Once(at) => (Some(at), None),
+            OrderSchedule::Cron(ref cron) => (None, Some(cron.as_str())),
+        };
+        bank_queries::insert_transfer_order()
+            .bind(
+                &db_client,
+                &order.id,
+                &order.created_at,
+                &sender.as_ref(),
+                &recipient.as_ref(),
+                &amount,
+                &run_at,
+                &cron,
+                &order.next_run_at,
+            )
+            .await
+            .context("Failed to insert transfer order into pg")?;
+
+        self.notify();
+        Ok(order)
+    }
+
+    async fn list_transfer_orders(
+        &self,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        self.transfer_orders(&db_client, None).await
+    }
+
+    async fn cancel_transfer_order(
+        &self,
+        id: &Uuid,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let cancelled = bank_queries::cancel_transfer_order()
+            .bind(&db_client, id)
+            .await
+            .context("Failed to cancel transfer order in pg")?;
+        if cancelled == 0 {
+            if bank_queries::is_transfer_order_exists()
+                .bind(&db_client, id)
+                .one()
+                .await
+                .context("Failed to check transfer order in pg")?
+            {
+                return Err(BankOperationError::BadOperation(
+                    "Order is already cancelled".to_string(),
+                ));
+            }
+            return Err(BankOperationError::OrderNotFound);
+        }
+
+        self.notify();
+        Ok(())
+    }
+
+    async fn due_transfer_orders(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        self.transfer_orders(&db_client, Some(now)).await
+    }
+
+    async fn run_transfer_order(
+        &self,
+        id: &Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Option<OrderExecution>, BankOperationError> {
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let mut transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        // Concurrent run waits for the lock and finds the order rescheduled
+        if bank_queries::lock_transfer_order()
+            .bind(&transaction, id, &now)
+            .opt()
+            .await
+            .context("Failed to lock transfer order in pg")?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let order = self
+            .transfer_orders(&transaction, Some(now))
+            .await?
+            .into_iter()
+            .find(|order| order.id.eq(id))
+            .ok_or(BankOperationError::OrderNotFound)?;
+        // Failed transfer is rolled back to the savepoint, the run
+        // outcome is saved anyway
+        let savepoint = transaction
+            .transaction()
+            .await
+            .context("Failed to create pg savepoint")?;
+        let transaction_id = Uuid::new_v4();
+        let result = bank_queries::create_transaction()
+            .bind(
+                &savepoint,
+                &transaction_id,
+                &order.sender.as_ref(),
+                &order.recipient.as_ref(),
+                &order.amount,
+            )
+            .await
+            .map(|_| transaction_id)
+            .map_err(map_transaction_error);
+        match result {
+            Ok(_) => savepoint.commit().await,
+            Err(_) => savepoint.rollback().await,
+        }
+        .context("Failed to finish pg savepoint")?;
+        let execution = OrderExecution::new(&result);
+        bank_queries::insert_order_execution()
+            .bind(
+                &transaction,
+                id,
+                &execution.executed_at,
+                &execution.transaction_id,
+                &execution.error.as_deref(),
+            )
+            .await
+            .context("Failed to insert order execution into pg")?;
+        bank_queries::set_order_next_run()
+            .bind(&transaction, &order.schedule.next_run(now), id)
+            .await
+            .context("Failed to reschedule transfer order in pg")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(Some(execution))
+    }
+
+    async fn set_overdraft_limit(
+        &self,
+        card: &CardNumber,
+        limit: i64,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        if limit < 0 {
+            return Err(BankOperationError::BadOperation(
+                "Overdraft limit can't be negative".to_string(),
+            ));
+        }
+        let _ = self.find_account(&db_client, card).await?;
+        bank_queries::set_account_overdraft_limit()
+            .bind(&db_client, &limit, &card.as_ref())
+            .await
+            .context("Failed to set overdraft limit in pg")?;
+
+        self.notify();
+        Ok(())
+    }
+
+    async fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        self.transactions(&db_client, None).await
+    }
+
+    async fn list_transactions_after(
+        &self,
+        last: Option<Uuid>,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        self.transactions(&db_client, last).await
+    }
+
+    async fn get_transaction(
+        &self,
+        id: &Uuid,
+    ) -> Result<Transaction, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let t = bank_queries::get_transaction()
+            .bind(&db_client, id)
+            .opt()
+            .await
+            .context("Failed to get transaction from the pg")?
+            .ok_or(BankOperationError::TransactionNotFound)?;
+        Ok(Transaction {
+            id: t.transaction_id,
+            sender: Account {
+                username: t.sender_username,
+                card_number: t.sender_card_number.parse()?,
+                password: Secret::new(String::new()),
+                is_existing: t.sender_is_existing,
+            },
+            recipient: Account {
+                username: t.recipient_username,
+                card_number: t.recipient_card_number.parse()?,
+                password: Secret::new(String::new()),
+                is_existing: t.recipient_is_existing,
+            },
+            amount: t.amount,
+            kind: t.kind.parse()?,
+            datetime: t.created_at,
+        })
+    }
+
+    async fn account_statement(
+        &self,
+        req: &StatementRequest,
+    ) -> Result<StatementResponse, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let _ = self.find_account(&db_client, &req.card_number).await?;
+        if let Some(cursor) = req.cursor {
+            let exists = bank_queries::is_transaction_exists()
+                .bind(&db_client, &cursor)
+                .one()
+                .await
+                .context("Failed to check transaction existence in pg")?;
+            if !exists {
+                return Err(BankOperationError::TransactionNotFound);
+            }
+        }
+
+        let limit = req.limit();
+        let transactions = bank_queries::account_statement()
+            .bind(
+                &db_client,
+                &req.card_number.as_ref(),
+                &req.cursor,
+                &req.from,
+                &req.to,
+                &req.direction.map(|d| d.as_str()),
+                &req.counterparty.as_ref().map(|c| c.as_ref()),
+                &req.min_amount,
+                &req.max_amount,
+                &(limit as i64 + 1),
+            )
+            .all()
+            .await
+            .context("Failed to get account statement from the pg")?
+            .into_iter()
+            .map(|t| {
+                Ok(Transaction {
+                    id: t.transaction_id,
+                    sender: Account {
+                        username: t.sender_username,
+                        card_number: t.sender_card_number.parse()?,
+                        password: Secret::new(String::new()),
+                        is_existing: t.sender_is_existing,
+                    },
+                    recipient: Account {
+                        username: t.recipient_username,
+                        card_number: t.recipient_card_number.parse()?,
+                        password: Secret::new(String::new()),
+                        is_existing: t.recipient_is_existing,
+                    },
+                    amount: t.amount,
+                    kind: t.kind.parse()?,
+                    datetime: t.created_at,
+                })
+            })
+            .collect::<Result<Vec<_>, BankOperationError>>()?;
+
+        Ok(StatementResponse::from_transactions(transactions, limit))
+    }
+
+    async fn bank_emission(&self) -> Result<i64, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let emission_account = self.emission_account(&db_client).await?;
+        self.balance(&db_client, &emission_account.card_number)
+            .await
+    }
+
+    async fn bank_revenue(&self) -> Result<i64, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let card: CardNumber = bank_queries::get_revenue_account()
+            .bind(&db_client)
+            .one()
+            .await
+            .context("Failed to fetch revenue account from pg")?
+            .parse()?;
+        self.balance(&db_client, &card).await
+    }
+
+    async fn audit(&self) -> Result<AuditReport, BankOperationError> {
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        // Read everything from the same snapshot, so concurrent
+        // operations don't look like violations
+        let transaction = db_client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let accounts = bank_queries::audit_accounts()
+            .bind(&transaction)
+            .all()
+            .await
+            .context("Failed to get accounts from the pg")?
+            .into_iter()
+            .map(|acc| {
+                Ok(AuditedAccount {
+                    card_number: acc.card_number.parse()?,
+                    balance: acc.balance,
+                    overdraft_limit: acc.overdraft_limit,
+                    is_emission: acc.id == 1,
+                    deleted_at: acc.deleted_at,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let tokens = bank_queries::audit_tokens()
+            .bind(&transaction)
+            .all()
+            .await
+            .context("Failed to get tokens from the pg")?
+            .into_iter()
+            .map(|t| {
+                Ok(AuditedToken {
+                    token: t.token,
+                    card_number: t
+                        .card_number
+                        .map(|c| c.parse())
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let transactions = self.transactions(&transaction, None).await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        Ok(audit::audit(&accounts, &transactions, &tokens))
+    }
+
+    async fn new_card_token(
+        &self,
+        card: &CardNumber,
+        expires_at: Option<OffsetDateTime>,
+        terminal_key: Option<Uuid>,
+    ) -> Result<String, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let token = generate_token();
+
+        // Check that card exists
+        let _ = self.find_account(&db_client, card).await?;
+        bank_queries::insert_token()
+            .bind(
+                &db_client,
+                &card.as_ref(),
+                &token,
+                &expires_at,
+                &terminal_key,
+            )
+            .await
+            .context("Failed to insert new card token into pg")?;
+        self.notify();
+        Ok(token)
+    }
+
+    async fn revoke_card_token(
+        &self,
+        token: &str,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let revoked = bank_queries::revoke_token()
+            .bind(&db_client, &token)
+            .await
+            .context("Failed to revoke card token in pg")?;
+        if revoked == 0 {
+            // Token is either missing or already revoked
+            self.card_token(&db_client, token).await?;
+            return Err(BankOperationError::TokenRevoked);
+        }
+        self.notify();
+        Ok(())
+    }
+
+    async fn list_card_tokens(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Vec<CardToken>, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let _ = self.find_account(&db_client, card).await?;
+        bank_queries::list_account_tokens()
+            .bind(&db_client, &card.as_ref())
+            .all()
+            .await
+            .context("Failed to list card tokens from pg")?
+            .into_iter()
+            .map(|token| {
+                Ok(CardToken {
+                    token: token.token,
+                    card_number: token.card_number.parse()?,
+                    created_at: token.created_at,
+                    expires_at: token.expires_at,
+                    revoked_at: token.revoked_at,
+                    terminal_key: token.terminal_key,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_card_token(
+        &self,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        self.card_token(&db_client, token).await
+    }
+
+    async fn get_account_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Account, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        self.get_account_by_token(&db_client, token).await
+    }
+
+    async fn begin_idempotent_request(
+        &self,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyStatus, BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        let inserted = bank_queries::insert_idempotency_key()
+            .bind(&db_client, &key, &request_hash)
+            .await
+            .context("Failed to insert idempotency key into pg")?;
+        if inserted == 1 {
+            return Ok(IdempotencyStatus::New);
+        }
+
+        let record = bank_queries::get_idempotency_key()
+            .bind(&db_client, &key)
+            .opt()
+            .await
+            .context("Failed to get idempotency key from pg")?;
+        Ok(match record {
+            Some(record) if !record.request_hash.eq(request_hash) => {
+                IdempotencyStatus::KeyReused
+            }
+            Some(bank_queries::GetIdempotencyKey {
+                response_status: Some(status),
+                response_content_type,
+                response_body: Some(body),
+                ..
+            }) => IdempotencyStatus::Completed(IdempotentResponse {
+                status: status as u16,
+                content_type: response_content_type,
+                body,
+            }),
+            // Key was aborted by the concurrent request, or still in use
+            _ => IdempotencyStatus::InProgress,
+        })
+    }
+
+    async fn complete_idempotent_request(
+        &self,
+        key: &str,
+        response: &IdempotentResponse,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        bank_queries::save_idempotent_response()
+            .bind(
+                &db_client,
+                &(response.status as i16),
+                &response.content_type,
+                &response.body,
+                &key,
+            )
+            .await
+            .context("Failed to save idempotent response into pg")?;
+        Ok(())
+    }
+
+    async fn abort_idempotent_request(
+        &self,
+        key: &str,
+    ) -> Result<(), BankOperationError> {
+        let db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        bank_queries::delete_idempotency_key()
+            .bind(&db_client, &key)
+            .await
+            .context("Failed to delete idempotency key from pg")?;
+        Ok(())
+    }
+
+    async fn export_dump(&self) -> Result<BankDump, BankOperationError> {
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+
+        // Read everything from the same snapshot,
+        // so the dump is consistent
+        let transaction = db_client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await
+            .context("Failed to begin pg transaction")?;
+        let revenue_card = bank_queries::get_revenue_account()
+            .bind(&transaction)
+            .one()
+            .await
+            .context("Failed to fetch revenue account from pg")?;
+        let accounts = bank_queries::export_accounts()
+            .bind(&transaction)
+            .all()
+            .await
+            .context("Failed to get accounts from the pg")?
+            .into_iter()
+            .map(|acc| {
+                let role = if acc.id == 1 {
+                    AccountRole::Emission
+                } else if acc.card_number.eq(&revenue_card) {
+                    AccountRole::Revenue
+                } else if let Some(terminal_key) = acc.terminal_key {
+                    AccountRole::Store { terminal_key }
+                } else {
+                    AccountRole::Customer
+                };
+                let card = match (
+                    acc.card_expiry_month,
+                    acc.card_expiry_year,
+                    acc.cvv_hash,
+                ) {
+                    (Some(month), Some(year), Some(cvv_hash)) => {
+                        Some(DumpCard {
+                            expiry: CardExpiry {
+                                month: month as u8,
+                                year: year as u16,
+                            },
+                            cvv_hash,
+                        })
+                    }
+                    _ => None,
+                };
+                Ok(DumpAccount {
+                    username: acc.username,
+                    card_number: acc.card_number.parse()?,
+                    role,
+                    password_hash: acc.password_hash,
+                    is_existing: acc.is_existing,
+                    deleted_at: acc.deleted_at,
+                    overdraft_limit: acc.overdraft_limit,
+                    status: acc.status.parse()?,
+                    status_reason: acc.status_reason,
+                    card,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let tokens = bank_queries::export_tokens()
+            .bind(&transaction)
+            .all()
+            .await
+            .context("Failed to get tokens from the pg")?
+            .into_iter()
+            .map(|t| {
+                Ok(DumpToken {
+                    token: t.token,
+                    card_number: t.card_number.parse()?,
+                    created_at: t.created_at,
+                    expires_at: t.expires_at,
+                    revoked_at: t.revoked_at,
+                    terminal_key: t.terminal_key,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let transactions = self
+            .transactions(&transaction, None)
+            .await?
+            .iter()
+            .map(DumpTransaction::from)
+            .collect();
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        Ok(BankDump {
+            version: DUMP_VERSION,
+            accounts,
+            tokens,
+            transactions,
+        })
+    }
+
+    async fn import_dump(
+        &self,
+        dump: &BankDump,
+    ) -> Result<(), BankOperationError> {
+        // Hashing is slow, so system passwords are hashed
+        // before the db transaction
+        let mut accounts = Vec::new();
+        for acc in dump.import_order() {
+            let (username, password_hash) =
+                match acc.system_credentials(&self.settings) {
+                    Some((username, password)) => (
+                        username,
+                        hash_password_blocking(
+                            self.argon2_obj.clone(),
+                            password.clone(),
+                        )
+                        .await?,
+                    ),
+                    None => (acc.username.clone(), acc.password_hash.clone()),
+                };
+            accounts.push((acc, username, password_hash));
+        }
+        let mut new_stores = Vec::new();
+        for terminal in dump.missing_terminals(&self.settings) {
+            let password_hash = hash_password_blocking(
+                self.argon2_obj.clone(),
+                terminal.password.clone(),
+            )
+            .await?;
+            new_stores.push((
+                terminal,
+                CardNumber::generate(&self.settings.card_settings),
+                password_hash,
+            ));
+        }
+
+        let mut db_client = self
+            .pg_pool
+            .get()
+            .await
+            .context("Failed to get a pg client from pg pool")?;
+        let transaction = db_client
+            .transaction()
+            .await
+            .context("Failed to begin pg transaction")?;
+        bank_queries::clear_bank()
+            .bind(&transaction)
+            .await
+            .context("Failed to clear bank data in pg")?;
+        // History is imported as is, balances are
+        // still updated by the trigger
+        bank_queries::disable_balance_check()
+            .bind(&transaction)
+            .await
+            .context("Failed to disable balance check in pg")?;
+
+        for (acc, username, password_hash) in accounts.iter() {
+            let card = acc.card.as_ref();
+            bank_queries::import_account()
+                .bind(
+                    &transaction,
+                    username,
+                    &acc.card_number.as_ref(),
+                    password_hash,
+                    &acc.is_existing,
+                    &acc.deleted_at,
+                    &acc.overdraft_limit,
+                    &acc.status.as_str(),
+                    &acc.status_reason.as_deref(),
+                    &card.map(|card| card.expiry.month as i16),
+                    &card.map(|card| card.expiry.year as i16),
+                    &card.map(|card| card.cvv_hash.as_str()),
+                )
+                .await
+                .context("Failed to import account to pg")?;
+            match acc.role {
+                AccountRole::Store { terminal_key } => {
+                    bank_queries::insert_terminal()
+                        .bind(
+                            &transaction,
+                            &terminal_key,
+                            &acc.card_number.as_ref(),
+                        )
+                        .await
+                        .context("Failed to import terminal to pg")?;
+                }
+                AccountRole::Revenue => {
+                    bank_queries::mark_revenue_account()
+                        .bind(&transaction, &acc.card_number.as_ref())
+                        .await
+                        .context("Failed to import revenue account to pg")?;
+                }
+                AccountRole::Emission | AccountRole::Customer => (),
+            }
+        }
+        for (terminal, card_number, password_hash) in new_stores.iter() {
+            bank_queries::insert_account()
+                .bind(
+                    &transaction,
+                    &self.settings.store_username(terminal),
+                    &card_number.as_ref(),
+                    password_hash,
+                )
+                .await
+                .context("Failed to insert store account to pg")?;
+            bank_queries::insert_terminal()
+                .bind(
+                    &transaction,
+                    &terminal.terminal_key,
+                    &card_number.as_ref(),
+                )
+                .await
+                .context("Failed to insert terminal to pg")?;
+        }
+        for token in dump.tokens.iter() {
+            bank_queries::import_token()
+                .bind(
+                    &transaction,
+                    &token.card_number.as_ref(),
+                    &token.token,
+                    &token.created_at,
+                    &token.expires_at,
+                    &token.revoked_at,
+                    &token.terminal_key,
+                )
+                .await
+                .context("Failed to import card token to pg")?;
+        }
+        for t in dump.transactions.iter() {
+            bank_queries::import_transaction()
+                .bind(
+                    &transaction,
+                    &t.id,
+                    &t.datetime,
+                    &t.sender.as_ref(),
+                    &t.recipient.as_ref(),
+                    &t.amount,
+                    &t.kind.as_str(),
+                )
+                .await
+                .context("Failed to import transaction to pg")?;
+        }
+
+        bank_queries::enable_balance_check()
+            .bind(&transaction)
+            .await
+            .context("Failed to enable balance check in pg")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit pg transaction")?;
+
+        self.notify();
+        Ok(())
+    }
+}
+
+/// Map errors raised by the `check_balance_before_transaction` trigger
+fn map_transaction_error(e: tokio_postgres::Error) -> BankOperationError {
+    tracing::error!("Failed to create transaction: {e}");
+    if let Some(db_error) = e.as_db_error() {
+        match db_error.message() {
+            "Not enough funds" => return BankOperationError::NotEnoughFunds,
+            "Overdraft limit exceeded" => {
+                return BankOperationError::OverdraftLimitExceeded
+            }
+            "Amount must be greater than 0" => {
+                return BankOperationError::BadTransaction
+            }
+            "Sender and recipient cannot be the same" => {
+                return BankOperationError::BadTransaction
+            }
+            "Sender or recipient account does not exist or is not active" => {
+                return BankOperationError::AccountNotFound
+            }
+            "Account is frozen" => return BankOperationError::AccountIsFrozen,
+            "Account is blocked" => {
+                return BankOperationError::AccountIsBlocked
+            }
+            _ => (),
+        }
+    }
+    BankOperationError::UnexpectedError
+}
+
+/// Hasher of passwords and CVVs, shared by all backends
+pub fn argon2_obj() -> argon2::Argon2<'static> {
+    argon2::Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        // Params are good
+        argon2::Params::new(15000, 2, 1, None).unwrap(),
+    )
 }
 
 pub async fn verify_password_hash_blocking(
@@ -703,7 +2745,7 @@ where
 }
 
 #[tracing::instrument(name = "Performing hashing of password", skip_all)]
-fn hash_password(
+pub(crate) fn hash_password(
     password: &Secret<String>,
     argon2: argon2::Argon2,
 ) -> Result<String, BankOperationError> {
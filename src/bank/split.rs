@@ -0,0 +1,144 @@
+use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use super::BankOperationError;
+
+/// Split `amount` proportionally to `parts` with the largest remainder
+/// method: every share is rounded down, then the rest is given by one
+/// to the shares with the largest fractional parts, the earlier part
+/// wins a tie. Shares always sum to `amount` exactly.
+pub fn allocate(
+    amount: i64,
+    parts: &[Decimal],
+) -> Result<Vec<i64>, BankOperationError> {
+    let total: Decimal = parts.iter().sum();
+    if amount < 0
+        || total <= Decimal::ZERO
+        || parts.iter().any(|part| part.is_sign_negative())
+    {
+        return Err(BankOperationError::BadTransaction);
+    }
+
+    let amount_dec = Decimal::from(amount);
+    let exact: Vec<Decimal> = parts
+        .iter()
+        .map(|part| {
+            amount_dec
+                .checked_mul(*part)
+                .and_then(|share| share.checked_div(total))
+        })
+        .collect::<Option<_>>()
+        .ok_or(BankOperationError::BadTransaction)?;
+    let mut shares = exact
+        .iter()
+        .map(|share| share.floor().to_i64())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(BankOperationError::BadTransaction)?;
+
+    let rest = usize::try_from(amount - shares.iter().sum::<i64>())
+        .map_err(|_| BankOperationError::BadTransaction)?;
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    // Stable sort keeps the earlier part first on a tie
+    order.sort_by(|&a, &b| exact[b].fract().cmp(&exact[a].fract()));
+    for &idx in order.iter().cycle().take(rest) {
+        shares[idx] += 1;
+    }
+    Ok(shares)
+}
+
+/// Share of the `amount` for each beneficiary token, in the order
+/// of beneficiaries. Beneficiaries with zero share are skipped.
+pub fn split_amount(
+    amount: i64,
+    beneficiaries: &Beneficiaries,
+) -> Result<Vec<(&String, i64)>, BankOperationError> {
+    let (tokens, parts): (Vec<_>, Vec<_>) = beneficiaries
+        .iter_tokens()
+        .map(|(token, part)| (token, *part))
+        .unzip();
+    let shares = allocate(amount, &parts)?;
+    Ok(tokens
+        .into_iter()
+        .zip(shares)
+        .filter(|(_, share)| *share > 0)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Parts in basis points, which sum to 1 like in real payments
+    fn parts() -> impl Strategy<Value = Vec<Decimal>> {
+        prop::collection::btree_set(1u32..10_000, 0..8).prop_map(|cuts| {
+            let mut bounds: Vec<u32> = vec![0];
+            bounds.extend(cuts);
+            bounds.push(10_000);
+            bounds
+                .windows(2)
+                .map(|w| Decimal::new((w[1] - w[0]) as i64, 4))
+                .collect()
+        })
+    }
+
+    fn beneficiaries(parts: &[Decimal]) -> Beneficiaries {
+        let mut builder =
+            Beneficiaries::builder("token_0".to_string(), parts[0]);
+        for (i, part) in parts.iter().enumerate().skip(1) {
+            builder = builder.add(format!("token_{i}"), *part);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn remainder_goes_to_largest_fractions() {
+        let thirds = [Decimal::ONE; 3];
+        assert_eq!(allocate(100, &thirds).unwrap(), vec![34, 33, 33]);
+        assert_eq!(allocate(2, &thirds).unwrap(), vec![1, 1, 0]);
+        let parts = [
+            Decimal::new(37, 2),
+            Decimal::new(31, 2),
+            Decimal::new(32, 2),
+        ];
+        assert_eq!(allocate(256, &parts).unwrap(), vec![95, 79, 82]);
+        // Tie goes to the earlier part
+        let parts = [Decimal::new(15, 2), Decimal::new(85, 2)];
+        assert_eq!(allocate(10, &parts).unwrap(), vec![2, 8]);
+        assert!(allocate(10, &[]).is_err());
+        assert!(allocate(10, &[Decimal::ONE, -Decimal::ONE]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn shares_sum_to_amount(
+            amount in 0i64..1_000_000_000,
+            parts in parts(),
+        ) {
+            let shares = allocate(amount, &parts).unwrap();
+            prop_assert_eq!(shares.iter().sum::<i64>(), amount);
+            // Every share differs from the exact one by less than unit
+            for (share, part) in shares.iter().zip(parts.iter()) {
+                let diff = Decimal::from(*share) - Decimal::from(amount) * part;
+                prop_assert!(diff.abs() < Decimal::ONE);
+            }
+            prop_assert_eq!(allocate(amount, &parts).unwrap(), shares);
+        }
+
+        #[test]
+        fn beneficiaries_get_whole_amount(
+            amount in 1i64..1_000_000,
+            parts in parts(),
+        ) {
+            let beneficiaries = beneficiaries(&parts);
+            let shares = split_amount(amount, &beneficiaries).unwrap();
+            let total: i64 = shares.iter().map(|(_, share)| share).sum();
+            prop_assert_eq!(total, amount);
+            prop_assert!(shares.iter().all(|(_, share)| *share > 0));
+            // Order of beneficiaries is kept
+            prop_assert!(shares.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+}
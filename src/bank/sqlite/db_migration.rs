@@ -0,0 +1,19 @@
+use refinery::embed_migrations;
+use rusqlite::Connection;
+
+embed_migrations!("./sqlite_migrations");
+
+pub fn run_migration(connection: &mut Connection) {
+    // Storage can't work with the outdated schema
+    let report = migrations::runner()
+        .run(connection)
+        .expect("Failed to run migrations on sqlite db");
+
+    if report.applied_migrations().is_empty() {
+        tracing::info!("No migrations applied");
+    }
+
+    for migration in report.applied_migrations() {
+        tracing::info!("Migration: {}", migration);
+    }
+}
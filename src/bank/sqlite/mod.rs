@@ -0,0 +1,2961 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Context;
+use axum::async_trait;
+use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use rusqlite::{
+    named_params, params, Connection, OptionalExtension, Params, Row,
+};
+use rust_decimal::Decimal;
+use secrecy::Secret;
+use time::{Duration, OffsetDateTime, UtcOffset};
+use tokio::sync::watch::{Receiver, Sender};
+use uuid::Uuid;
+
+use crate::domain::card_number::CardNumber;
+use crate::domain::requests::system_api::StatementRequest;
+use crate::domain::responses::system_api::StatementResponse;
+use crate::middleware::Credentials;
+use crate::Settings;
+
+use super::audit::{self, AuditReport, AuditedAccount, AuditedToken};
+use super::backend::{BankDataBackend, InitBankDataBackend};
+use super::dump::{
+    AccountRole, BankDump, DumpAccount, DumpCard, DumpToken, DumpTransaction,
+    DUMP_VERSION,
+};
+use super::loan::Loan;
+use super::pg::{
+    argon2_obj, hash_password, hash_password_blocking,
+    spawn_blocking_with_tracing, verify_password_hash_blocking,
+};
+use super::split;
+use super::transfer_order::{OrderExecution, OrderSchedule, TransferOrder};
+use super::{
+    check_fee, check_refund_fee, generate_cvv, generate_token, Account,
+    AccountStatus, BankOperationError, CardExpiry, CardToken, Hold,
+    IdempotencyStatus, IdempotentResponse, IssuedCard, Transaction,
+    HOLD_LIFETIME,
+};
+
+mod db_migration;
+
+/// Transactions with their sender and recipient accounts,
+/// rows are read by `transaction_from_row`
+const TRANSACTIONS_QUERY: &str = "
+SELECT
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    s.username,
+    s.card_number,
+    s.is_existing,
+    r.username,
+    r.card_number,
+    r.is_existing
+FROM transactions t
+JOIN accounts s ON t.sender = s.id
+JOIN accounts r ON t.recipient = r.id";
+
+/// Triggers, which check every transaction and forbid to delete them
+const TRANSACTION_GUARDS: [&str; 2] = [
+    "check_balance_before_transaction",
+    "forbid_transaction_delete",
+];
+
+/// Embedded storage in a single SQLite file. Queries are short, so
+/// the only connection is shared under the lock, which also
+/// serializes debits instead of the Postgres row locks.
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Arc<Mutex<Connection>>,
+    notifier: Sender<()>,
+    argon2_obj: argon2::Argon2<'static>,
+    settings: Settings,
+}
+
+impl SqliteStorage {
+    /// Run `f` with the locked connection on the blocking pool, rusqlite
+    /// calls are synchronous and would stall the async workers
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, BankOperationError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, BankOperationError>
+            + Send
+            + 'static,
+        T: Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        spawn_blocking_with_tracing(move || {
+            // Sqlite transaction of the panicked call is rolled back
+            // on drop, so the connection is still consistent
+            let mut connection =
+                connection.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut connection)
+        })
+        .await
+        .context("Failed to run sqlite query")?
+    }
+
+    fn balance(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<i64, BankOperationError> {
+        let balance = connection
+            .query_row(
+                "SELECT balance FROM accounts WHERE card_number = ?1",
+                [card.as_ref()],
+                |row| row.get(0),
+            )
+            .context("Failed to get balance from sqlite for an account")?;
+        Ok(balance)
+    }
+
+    /// Balance without funds reserved by holds
+    fn available_balance(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<i64, BankOperationError> {
+        let balance = Self::balance(connection, card)?;
+        let held: i64 = connection
+            .query_row(
+                "SELECT COALESCE(SUM(holds.amount), 0)
+                FROM holds
+                JOIN accounts a ON holds.account = a.id
+                WHERE a.card_number = ?1
+                    AND julianday(holds.expires_at) > julianday('now')",
+                [card.as_ref()],
+                |row| row.get(0),
+            )
+            .context("Failed to get held amount from sqlite for an account")?;
+        Ok(balance - held)
+    }
+
+    /// Account can go negative down to it's overdraft limit
+    fn check_funds(
+        connection: &Connection,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<(), BankOperationError> {
+        let limit: i64 = connection
+            .query_row(
+                "SELECT overdraft_limit FROM accounts WHERE card_number = ?1",
+                [card.as_ref()],
+                |row| row.get(0),
+            )
+            .context("Failed to get overdraft limit from sqlite")?;
+        if Self::available_balance(connection, card)? + limit >= amount {
+            Ok(())
+        } else if limit > 0 {
+            Err(BankOperationError::OverdraftLimitExceeded)
+        } else {
+            Err(BankOperationError::NotEnoughFunds)
+        }
+    }
+
+    fn account_status(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError> {
+        let status: String = connection
+            .query_row(
+                "SELECT status FROM accounts WHERE card_number = ?1",
+                [card.as_ref()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to get account status from sqlite")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        Ok(status.parse()?)
+    }
+
+    fn is_store_account(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<bool, BankOperationError> {
+        let is_store = connection
+            .query_row(
+                "SELECT EXISTS (
+                    SELECT 1
+                    FROM terminals
+                    JOIN accounts a ON terminals.account = a.id
+                    WHERE a.card_number = ?1
+                )",
+                [card.as_ref()],
+                |row| row.get(0),
+            )
+            .context("Failed to check store account in sqlite")?;
+        Ok(is_store)
+    }
+
+    /// Generate expiry date, if it is not set, and CVV with it's hash.
+    /// Hashing is slow, so it is done before taking the connection.
+    async fn generate_card_details(
+        &self,
+        expiry: Option<CardExpiry>,
+    ) -> Result<(CardExpiry, Secret<String>, String), BankOperationError> {
+        let expiry = expiry.unwrap_or_else(|| {
+            CardExpiry::after_months(
+                OffsetDateTime::now_utc(),
+                self.settings.card_settings.validity_months,
+            )
+        });
+        let cvv = generate_cvv();
+        let cvv_hash =
+            hash_password_blocking(self.argon2_obj.clone(), cvv.clone())
+                .await?;
+        Ok((expiry, cvv, cvv_hash))
+    }
+
+    fn set_card_details(
+        connection: &Connection,
+        card: &CardNumber,
+        expiry: CardExpiry,
+        cvv_hash: &str,
+    ) -> Result<(), BankOperationError> {
+        connection
+            .execute(
+                "UPDATE accounts
+                SET card_expiry_month = ?1,
+                    card_expiry_year = ?2,
+                    cvv_hash = ?3
+                WHERE card_number = ?4",
+                params![expiry.month, expiry.year, cvv_hash, card.as_ref()],
+            )
+            .context("Failed to set card details in sqlite")?;
+        Ok(())
+    }
+
+    /// Insert transaction, funds and statuses are checked by the
+    /// `check_balance_before_transaction` trigger
+    fn create_transaction(
+        connection: &Connection,
+        sender: &str,
+        recipient: &str,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let id = Uuid::new_v4();
+        connection
+            .execute(
+                "INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount)
+                VALUES (
+                    ?1,
+                    ?2,
+                    (SELECT id FROM accounts WHERE card_number = ?3),
+                    (SELECT id FROM accounts WHERE card_number = ?4),
+                    ?5
+                )",
+                params![id, OffsetDateTime::now_utc(), sender, recipient, amount],
+            )
+            .map_err(map_transaction_error)?;
+        Ok(id)
+    }
+
+    /// Move acquiring fee from the payer to the bank revenue account
+    fn fee_transaction(
+        connection: &Connection,
+        payer: &str,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        connection
+            .execute(
+                "INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount, kind)
+                VALUES (
+                    ?1,
+                    ?2,
+                    (SELECT id FROM accounts WHERE card_number = ?3),
+                    (SELECT account FROM system_accounts WHERE role = 'revenue'),
+                    ?4,
+                    'acquiring_fee'
+                )",
+                params![Uuid::new_v4(), OffsetDateTime::now_utc(), payer, fee],
+            )
+            .map_err(map_transaction_error)?;
+        Ok(())
+    }
+
+    /// Return acquiring fee from the bank revenue account to the payer
+    fn fee_refund_transaction(
+        connection: &Connection,
+        payer: &str,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        if fee == 0 {
+            return Ok(());
+        }
+        connection
+            .execute(
+                "INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount, kind)
+                VALUES (
+                    ?1,
+                    ?2,
+                    (SELECT account FROM system_accounts WHERE role = 'revenue'),
+                    (SELECT id FROM accounts WHERE card_number = ?3),
+                    ?4,
+                    'fee_refund'
+                )",
+                params![Uuid::new_v4(), OffsetDateTime::now_utc(), payer, fee],
+            )
+            .map_err(map_transaction_error)?;
+        Ok(())
+    }
+
+    fn loan(
+        connection: &Connection,
+        id: &Uuid,
+    ) -> Result<Loan, BankOperationError> {
+        connection
+            .query_row(
+                "SELECT
+                    loans.id,
+                    loans.created_at,
+                    a.card_number,
+                    loans.principal,
+                    loans.interest_rate,
+                    loans.term,
+                    loans.period_secs,
+                    loans.repaid
+                FROM loans
+                JOIN accounts a ON loans.account = a.id
+                WHERE loans.id = ?1",
+                [id],
+                |row| {
+                    Ok((
+                        row.get::<_, Uuid>(0)?,
+                        row.get::<_, OffsetDateTime>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, u32>(5)?,
+                        row.get::<_, i64>(6)?,
+                        row.get::<_, i64>(7)?,
+                    ))
+                },
+            )
+            .optional()
+            .context("Failed to get loan from sqlite")?
+            .ok_or(BankOperationError::LoanNotFound)
+            .and_then(
+                |(
+                    id,
+                    opened_at,
+                    card,
+                    principal,
+                    rate,
+                    term,
+                    period,
+                    repaid,
+                )| {
+                    Ok(Loan {
+                        id,
+                        card_number: card.parse()?,
+                        principal,
+                        interest_rate: Decimal::from_str(&rate)
+                            .context("Bad loan interest rate in sqlite")?,
+                        term,
+                        period: Duration::seconds(period),
+                        opened_at,
+                        repaid,
+                    })
+                },
+            )
+    }
+
+    /// All orders or only due ones, if `due_at` is set
+    fn transfer_orders(
+        connection: &Connection,
+        due_at: Option<OffsetDateTime>,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        let mut stmt = connection
+            .prepare(
+                "SELECT
+                    transfer_orders.id,
+                    transfer_orders.created_at,
+                    s.card_number,
+                    r.card_number,
+                    transfer_orders.amount,
+                    transfer_orders.run_at,
+                    transfer_orders.cron,
+                    transfer_orders.next_run_at,
+                    transfer_orders.cancelled_at
+                FROM transfer_orders
+                JOIN accounts s ON transfer_orders.sender = s.id
+                JOIN accounts r ON transfer_orders.recipient = r.id
+                WHERE ?1 IS NULL
+                    OR (transfer_orders.cancelled_at IS NULL AND transfer_orders.next_run_at <= ?1)
+                ORDER BY transfer_orders.created_at, transfer_orders.rowid",
+            )
+            .context("Failed to prepare transfer orders query for sqlite")?;
+        let mut rows = stmt
+            .query([due_at.map(utc)])
+            .context("Failed to get transfer orders from sqlite")?;
+
+        let mut orders = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .context("Failed to get transfer orders from sqlite")?
+        {
+            let id: Uuid =
+                row.get(0).context("Bad transfer order in sqlite")?;
+            let run_at: Option<OffsetDateTime> =
+                row.get(5).context("Bad transfer order in sqlite")?;
+            let cron: Option<String> =
+                row.get(6).context("Bad transfer order in sqlite")?;
+            let schedule = match (run_at, cron) {
+                (Some(at), _) => OrderSchedule::Once(at),
+                (None, Some(cron)) => OrderSchedule::Cron(
+                    cron.parse().context("Bad order cron in sqlite")?,
+                ),
+                (None, None) => {
+                    return Err(anyhow::anyhow!(
+                        "Transfer order without schedule in sqlite"
+                    )
+                    .into())
+                }
+            };
+            orders.push(TransferOrder {
+                id,
+                sender: row
+                    .get::<_, String>(2)
+                    .context("Bad transfer order in sqlite")?
+                    .parse()?,
+                recipient: row
+                    .get::<_, String>(3)
+                    .context("Bad transfer order in sqlite")?
+                    .parse()?,
+                amount: row.get(4).context("Bad transfer order in sqlite")?,
+                schedule,
+                created_at: row
+                    .get(1)
+                    .context("Bad transfer order in sqlite")?,
+                next_run_at: row
+                    .get(7)
+                    .context("Bad transfer order in sqlite")?,
+                cancelled_at: row
+                    .get(8)
+                    .context("Bad transfer order in sqlite")?,
+                executions: Self::order_executions(connection, &id)?,
+            });
+        }
+        Ok(orders)
+    }
+
+    fn order_executions(
+        connection: &Connection,
+        order_id: &Uuid,
+    ) -> Result<Vec<OrderExecution>, BankOperationError> {
+        let mut stmt = connection
+            .prepare(
+                "SELECT executed_at, transaction_id, error
+                FROM transfer_order_executions
+                WHERE order_id = ?1
+                ORDER BY id",
+            )
+            .context("Failed to prepare order executions query for sqlite")?;
+        let executions = stmt
+            .query_map([order_id], |row| {
+                Ok(OrderExecution {
+                    executed_at: row.get(0)?,
+                    transaction_id: row.get(1)?,
+                    error: row.get(2)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .context("Failed to get order executions from sqlite")?;
+        Ok(executions)
+    }
+
+    fn account_holds(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<Vec<Hold>, BankOperationError> {
+        let mut stmt = connection
+            .prepare(
+                "SELECT holds.id, holds.amount, holds.created_at,
+                    holds.expires_at
+                FROM holds
+                JOIN accounts a ON holds.account = a.id
+                WHERE a.card_number = ?1
+                    AND julianday(holds.expires_at) > julianday('now')
+                ORDER BY holds.created_at",
+            )
+            .context("Failed to prepare account holds query for sqlite")?;
+        let holds = stmt
+            .query_map([card.as_ref()], |row| {
+                Ok(Hold {
+                    id: row.get(0)?,
+                    card_number: card.clone(),
+                    amount: row.get(1)?,
+                    datetime: row.get(2)?,
+                    expires_at: row.get(3)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .context("Failed to get account holds list from the sqlite")?;
+        Ok(holds)
+    }
+
+    /// Active tokens of the account
+    fn account_tokens(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<Vec<String>, BankOperationError> {
+        let mut stmt = connection
+            .prepare(
+                "SELECT tokens.token
+                FROM tokens
+                JOIN accounts a ON tokens.account = a.id
+                WHERE a.card_number = ?1
+                    AND tokens.revoked_at IS NULL
+                    AND (tokens.expires_at IS NULL OR tokens.expires_at > ?2)
+                ORDER BY tokens.id",
+            )
+            .context("Failed to prepare account tokens query for sqlite")?;
+        let tokens = stmt
+            .query_map(
+                params![card.as_ref(), OffsetDateTime::now_utc()],
+                |row| row.get(0),
+            )
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .context("Failed to get account tokens from sqlite")?;
+        Ok(tokens)
+    }
+
+    /// Transactions in the `TRANSACTIONS_QUERY` with the `filter`
+    /// clause appended
+    fn transactions<P: Params>(
+        connection: &Connection,
+        filter: &str,
+        params: P,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        let mut stmt = connection
+            .prepare(&format!("{TRANSACTIONS_QUERY} {filter}"))
+            .context("Failed to prepare transactions query for sqlite")?;
+        let mut rows = stmt
+            .query(params)
+            .context("Failed to get transactions from the sqlite")?;
+        let mut transactions = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .context("Failed to get transactions from the sqlite")?
+        {
+            transactions.push(transaction_from_row(row)?);
+        }
+        Ok(transactions)
+    }
+
+    /// I want to notify my subscribers to update their accounts info
+    /// after every bank lock
+    fn notify(&self) {
+        if let Err(e) = self.notifier.send(()) {
+            tracing::warn!(
+                "Failed to send bank state updated notification: {e}"
+            );
+        }
+    }
+
+    fn find_account(
+        connection: &Connection,
+        card: &CardNumber,
+    ) -> Result<Account, BankOperationError> {
+        let account = connection
+            .query_row(
+                "SELECT username, card_number, is_existing
+                FROM accounts
+                WHERE card_number = ?1",
+                [card.as_ref()],
+                |row| account_from_row(row, 0),
+            )
+            .optional()
+            .context("Failed to find an account by card number in sqlite")?
+            .ok_or(BankOperationError::AccountNotFound)?;
+        Ok(account?)
+    }
+
+    fn card_token(
+        connection: &Connection,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError> {
+        let (
+            token,
+            card_number,
+            created_at,
+            expires_at,
+            revoked_at,
+            terminal_key,
+        ) = connection
+            .query_row(
+                "SELECT
+                    tokens.token,
+                    a.card_number,
+                    tokens.created_at,
+                    tokens.expires_at,
+                    tokens.revoked_at,
+                    tokens.terminal_key
+                FROM tokens
+                JOIN accounts a ON tokens.account = a.id
+                WHERE tokens.token = ?1",
+                [token],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                },
+            )
+            .optional()
+            .context("Failed to get card token from sqlite")?
+            .ok_or(BankOperationError::TokenNotFound)?;
+        Ok(CardToken {
+            token,
+            card_number: card_number.parse()?,
+            created_at,
+            expires_at,
+            revoked_at,
+            terminal_key,
+        })
+    }
+
+    fn get_account_by_token(
+        connection: &Connection,
+        token: &str,
+    ) -> Result<Account, BankOperationError> {
+        let token = Self::card_token(connection, token)?;
+        token.check_active(OffsetDateTime::now_utc())?;
+        Self::find_account(connection, &token.card_number)
+    }
+
+    fn emission_account(
+        connection: &Connection,
+    ) -> Result<Account, BankOperationError> {
+        let account = connection
+            .query_row(
+                "SELECT username, card_number, is_existing
+                FROM accounts
+                WHERE id = 1",
+                [],
+                |row| account_from_row(row, 0),
+            )
+            .context("Failed to fetch emission account from sqlite")?;
+        Ok(account?)
+    }
+}
+
+impl InitBankDataBackend for SqliteStorage {
+    fn new(
+        settings: &Settings,
+        tx: Sender<()>,
+    ) -> Arc<dyn BankDataBackend + Send + Sync> {
+        let path = &settings
+            .sqlite_settings
+            .as_ref()
+            .expect("Sqlite settings are required for sqlite backend")
+            .path;
+        let mut connection =
+            Connection::open(path).expect("Failed to open sqlite database");
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .expect("Failed to enable sqlite foreign keys");
+        db_migration::run_migration(&mut connection);
+
+        let argon2_obj = argon2_obj();
+
+        // Database is embedded, so system accounts are ready
+        // before the first request
+        let insert_account = |username: &str, password: &Secret<String>| {
+            let password_hash =
+                hash_password(password, argon2_obj.clone()).unwrap();
+            let card_number = CardNumber::generate(&settings.card_settings);
+            insert_account(&connection, username, &card_number, &password_hash)
+                .unwrap();
+            card_number
+        };
+        let account_exists = |username: &str| {
+            connection
+                .query_row(
+                    "SELECT EXISTS (SELECT 1 FROM accounts WHERE username = ?1)",
+                    [username],
+                    |row| row.get::<_, bool>(0),
+                )
+                .expect("Failed to check system accounts")
+        };
+
+        // Emission account is always the first one
+        if !account_exists(&settings.bank_username) {
+            tracing::info!("No system accounts found, creating ones...");
+            insert_account(
+                &settings.bank_username,
+                &settings.terminal_settings.password,
+            );
+        }
+        let has_revenue = connection
+            .query_row(
+                "SELECT EXISTS (
+                    SELECT 1 FROM system_accounts WHERE role = 'revenue'
+                )",
+                [],
+                |row| row.get::<_, bool>(0),
+            )
+            .expect("Failed to check revenue account");
+        if !has_revenue {
+            // Revenue account, created before system accounts
+            // were marked, is found by it's username
+            let revenue_username = settings.revenue_username();
+            let card_number = if account_exists(&revenue_username) {
+                connection
+                    .query_row(
+                        "SELECT card_number FROM accounts WHERE username = ?1",
+                        [&revenue_username],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .expect("Failed to get legacy revenue account")
+                    .parse()
+                    .expect("Failed to parse revenue card number")
+            } else {
+                insert_account(
+                    &revenue_username,
+                    &settings.terminal_settings.password,
+                )
+            };
+            mark_revenue_account(&connection, &card_number).unwrap();
+            tracing::info!("Revenue account is ready");
+        }
+
+        // Every terminal should have it's own store account
+        for terminal in settings.all_terminals() {
+            let has_store = connection
+                .query_row(
+                    "SELECT EXISTS (
+                        SELECT 1 FROM terminals WHERE terminal_key = ?1
+                    )",
+                    [terminal.terminal_key],
+                    |row| row.get::<_, bool>(0),
+                )
+                .expect("Failed to check terminal store account");
+            if has_store {
+                continue;
+            }
+            let card_number = insert_account(
+                &settings.store_username(terminal),
+                &terminal.password,
+            );
+            insert_terminal(&connection, &terminal.terminal_key, &card_number)
+                .unwrap();
+            tracing::info!(
+                "Store account for terminal {} is ready",
+                terminal.terminal_key
+            );
+        }
+
+        Arc::new(SqliteStorage {
+            connection: Arc::new(Mutex::new(connection)),
+            notifier: tx,
+            argon2_obj,
+            settings: settings.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl BankDataBackend for SqliteStorage {
+    async fn subscribe(&self) -> Receiver<()> {
+        self.notifier.subscribe()
+    }
+
+    async fn authorize_system(
+        &self,
+        credentials: Credentials,
+    ) -> Result<(), BankOperationError> {
+        let (username, password_hash) = self
+            .with_connection(|connection| {
+                let row = connection
+                    .query_row(
+                        "SELECT username, password_hash
+                        FROM accounts
+                        WHERE id = 1",
+                        [],
+                        |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, String>(1)?,
+                            ))
+                        },
+                    )
+                    .context("Failed to get emission account from sqlite")?;
+                Ok(row)
+            })
+            .await?;
+
+        verify_password_hash_blocking(
+            Secret::new(password_hash),
+            credentials.password,
+            self.argon2_obj.clone(),
+        )
+        .await?;
+
+        if username.eq(&credentials.username) {
+            Ok(())
+        } else {
+            Err(BankOperationError::NotAuthorized)
+        }
+    }
+
+    async fn add_account(
+        &self,
+        username: &str,
+        password: &Secret<String>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let password_hash =
+            hash_password_blocking(self.argon2_obj.clone(), password.clone())
+                .await?;
+        let (expiry, cvv, cvv_hash) = self.generate_card_details(None).await?;
+
+        let card_number = CardNumber::generate(&self.settings.card_settings);
+        let username = username.to_string();
+        let card_number = self
+            .with_connection(move |connection| {
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                insert_account(
+                    &transaction,
+                    &username,
+                    &card_number,
+                    &password_hash,
+                )
+                .context("Failed to insert a new account to sqlite")?;
+                Self::set_card_details(
+                    &transaction,
+                    &card_number,
+                    expiry,
+                    &cvv_hash,
+                )?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(card_number)
+            })
+            .await?;
+
+        self.notify();
+        Ok(IssuedCard {
+            card_number,
+            expiry,
+            cvv,
+        })
+    }
+
+    async fn reissue_card(
+        &self,
+        card: &CardNumber,
+        expiry: Option<CardExpiry>,
+    ) -> Result<IssuedCard, BankOperationError> {
+        let (expiry, cvv, cvv_hash) =
+            self.generate_card_details(expiry).await?;
+
+        let card = card.clone();
+        let bank_username = self.settings.bank_username.clone();
+        let revenue_username = self.settings.revenue_username();
+        let card = self
+            .with_connection(move |connection| {
+                let card = &card;
+                let account = Self::find_account(connection, card)?;
+                if !account.is_existing {
+                    return Err(BankOperationError::AccountIsDeleted);
+                }
+                // System accounts are hidden from clients
+                if account.username.eq(&bank_username)
+                    || account.username.eq(&revenue_username)
+                {
+                    return Err(BankOperationError::AccountNotFound);
+                }
+                if Self::is_store_account(connection, card)? {
+                    return Err(BankOperationError::BadOperation(
+                        "Can't issue card for store account".to_string(),
+                    ));
+                }
+
+                Self::set_card_details(connection, card, expiry, &cvv_hash)?;
+                Ok(card.clone())
+            })
+            .await?;
+        self.notify();
+        Ok(IssuedCard {
+            card_number: card,
+            expiry,
+            cvv,
+        })
+    }
+
+    async fn card_expiry(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Option<CardExpiry>, BankOperationError> {
+        let card = card.clone();
+        let (month, year) = self
+            .with_connection(move |connection| {
+                let row = connection
+                    .query_row(
+                        "SELECT card_expiry_month, card_expiry_year
+                        FROM accounts
+                        WHERE card_number = ?1",
+                        [card.as_ref()],
+                        |row| {
+                            Ok((
+                                row.get::<_, Option<u8>>(0)?,
+                                row.get::<_, Option<u16>>(1)?,
+                            ))
+                        },
+                    )
+                    .optional()
+                    .context("Failed to get account from sqlite")?
+                    .ok_or(BankOperationError::AccountNotFound)?;
+                Ok(row)
+            })
+            .await?;
+        Ok(month
+            .zip(year)
+            .map(|(month, year)| CardExpiry { month, year }))
+    }
+
+    async fn delete_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            let card = &card;
+            if !Self::find_account(connection, card)?.is_existing {
+                return Err(BankOperationError::AccountNotFound);
+            }
+
+            if Self::is_store_account(connection, card)? {
+                return Err(BankOperationError::BadOperation(
+                    "Can't delete store account".to_string(),
+                ));
+            }
+
+            connection
+                .execute(
+                    "UPDATE accounts
+                    SET is_existing = FALSE,
+                        deleted_at = ?1
+                    WHERE card_number = ?2",
+                    params![OffsetDateTime::now_utc(), card.as_ref()],
+                )
+                .context("Failed to delete account from sqlite")?;
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn restore_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<(), BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            let card = &card;
+            if Self::find_account(connection, card)?.is_existing {
+                return Err(BankOperationError::BadOperation(
+                    "Account is not deleted".to_string(),
+                ));
+            }
+
+            connection
+                .execute(
+                    "UPDATE accounts
+                    SET is_existing = TRUE,
+                        deleted_at = NULL
+                    WHERE card_number = ?1",
+                    [card.as_ref()],
+                )
+                .context("Failed to restore account in sqlite")?;
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn set_account_status(
+        &self,
+        card: &CardNumber,
+        status: AccountStatus,
+        reason: Option<&str>,
+    ) -> Result<(), BankOperationError> {
+        let card = card.clone();
+        let reason = reason.map(str::to_string);
+        let bank_username = self.settings.bank_username.clone();
+        let revenue_username = self.settings.revenue_username();
+        self.with_connection(move |connection| {
+            let card = &card;
+            let account = Self::find_account(connection, card)?;
+            if !account.is_existing {
+                return Err(BankOperationError::AccountIsDeleted);
+            }
+            // System accounts are hidden from clients
+            if account.username.eq(&bank_username)
+                || account.username.eq(&revenue_username)
+            {
+                return Err(BankOperationError::AccountNotFound);
+            }
+            if Self::is_store_account(connection, card)? {
+                return Err(BankOperationError::BadOperation(
+                    "Can't change status of store account".to_string(),
+                ));
+            }
+
+            connection
+                .execute(
+                    "UPDATE accounts
+                    SET status = ?1,
+                        status_reason = ?2,
+                        status_changed_at = ?3
+                    WHERE card_number = ?4",
+                    params![
+                        status.as_str(),
+                        reason,
+                        OffsetDateTime::now_utc(),
+                        card.as_ref()
+                    ],
+                )
+                .context("Failed to set account status in sqlite")?;
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn list_accounts(
+        &self,
+    ) -> Result<
+        Vec<crate::domain::responses::system_api::Account>,
+        BankOperationError,
+    > {
+        let bank_username = self.settings.bank_username.clone();
+        self.with_connection(move |connection| {
+            // Store and revenue accounts are not listed
+            let mut stmt = connection
+                .prepare(
+                    "SELECT
+                        username,
+                        card_number,
+                        is_existing,
+                        balance,
+                        overdraft_limit,
+                        status,
+                        status_reason
+                    FROM accounts
+                    WHERE id NOT IN (SELECT account FROM terminals)
+                        AND id NOT IN (SELECT account FROM system_accounts)",
+                )
+                .context("Failed to prepare accounts query for sqlite")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, bool>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, Option<String>>(6)?,
+                    ))
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .context("Failed to get accounts from sqlite")?;
+
+            let mut result = Vec::with_capacity(rows.len());
+            for (username, card, exists, balance, limit, status, reason) in rows
+            {
+                // Skip emission account
+                if username.eq(&bank_username) {
+                    continue;
+                }
+                let card_number: CardNumber = card.parse()?;
+                let card_filter =
+                    "WHERE s.card_number = ?1 OR r.card_number = ?1 \
+                    ORDER BY t.id";
+                result.push(crate::domain::responses::system_api::Account {
+                    balance,
+                    overdraft_limit: limit,
+                    debt: (-balance).max(0),
+                    transactions: Self::transactions(
+                        connection,
+                        card_filter,
+                        [card_number.as_ref()],
+                    )?,
+                    holds: Self::account_holds(connection, &card_number)?,
+                    exists,
+                    status: status.parse()?,
+                    status_reason: reason,
+                    tokens: Self::account_tokens(connection, &card_number)?,
+                    username,
+                    card_number,
+                });
+            }
+            result.sort_by(|acc1, acc2| acc1.username.cmp(&acc2.username));
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn authorize_account(
+        &self,
+        card: &CardNumber,
+        password: &Secret<String>,
+    ) -> Result<Account, BankOperationError> {
+        let card = card.clone();
+        let (account, password_hash) = self
+            .with_connection(move |connection| {
+                let card = &card;
+                let password_hash: String = connection
+                    .query_row(
+                        "SELECT password_hash FROM accounts WHERE card_number = ?1",
+                        [card.as_ref()],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .context("Failed to get account from sqlite")?
+                    .ok_or(BankOperationError::AccountNotFound)?;
+                Ok((Self::find_account(connection, card)?, password_hash))
+            })
+            .await?;
+
+        verify_password_hash_blocking(
+            Secret::new(password_hash),
+            password.clone(),
+            self.argon2_obj.clone(),
+        )
+        .await?;
+
+        Ok(account)
+    }
+
+    async fn authorize_card(
+        &self,
+        card: &CardNumber,
+        password: &Secret<String>,
+        expiry: CardExpiry,
+        cvv: &Secret<String>,
+    ) -> Result<Account, BankOperationError> {
+        let card = card.clone();
+        let (account, password_hash, month, year, cvv_hash) = self
+            .with_connection(move |connection| {
+                let card = &card;
+                let (password_hash, month, year, cvv_hash) = connection
+                    .query_row(
+                        "SELECT
+                            password_hash,
+                            card_expiry_month,
+                            card_expiry_year,
+                            cvv_hash
+                        FROM accounts
+                        WHERE card_number = ?1",
+                        [card.as_ref()],
+                        |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, Option<u8>>(1)?,
+                                row.get::<_, Option<u16>>(2)?,
+                                row.get::<_, Option<String>>(3)?,
+                            ))
+                        },
+                    )
+                    .optional()
+                    .context("Failed to get account from sqlite")?
+                    .ok_or(BankOperationError::AccountNotFound)?;
+                let account = Self::find_account(connection, card)?;
+                Ok((account, password_hash, month, year, cvv_hash))
+            })
+            .await?;
+        if !account.is_existing {
+            return Err(BankOperationError::AccountIsDeleted);
+        }
+
+        verify_password_hash_blocking(
+            Secret::new(password_hash),
+            password.clone(),
+            self.argon2_obj.clone(),
+        )
+        .await?;
+
+        let (Some(month), Some(year), Some(cvv_hash)) = (month, year, cvv_hash)
+        else {
+            return Err(BankOperationError::BadOperation(
+                "Card details are not issued".to_string(),
+            ));
+        };
+        let card_expiry = CardExpiry { month, year };
+        if card_expiry != expiry {
+            return Err(BankOperationError::InvalidExpiry);
+        }
+        if card_expiry.is_expired(OffsetDateTime::now_utc()) {
+            return Err(BankOperationError::CardExpired);
+        }
+        verify_password_hash_blocking(
+            Secret::new(cvv_hash),
+            cvv.clone(),
+            self.argon2_obj.clone(),
+        )
+        .await
+        .map_err(|_| BankOperationError::InvalidCvv)?;
+
+        Ok(account)
+    }
+
+    async fn find_account(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Account, BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            Self::find_account(connection, &card)
+        })
+        .await
+    }
+
+    async fn account_status(
+        &self,
+        card: &CardNumber,
+    ) -> Result<AccountStatus, BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            Self::account_status(connection, &card)
+        })
+        .await
+    }
+
+    async fn get_store_account(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<Account, BankOperationError> {
+        let terminal_key = *terminal_key;
+        let account = self
+            .with_connection(move |connection| {
+                let account = connection
+                    .query_row(
+                        "SELECT a.username, a.card_number, a.is_existing
+                        FROM terminals
+                        JOIN accounts a ON terminals.account = a.id
+                        WHERE terminals.terminal_key = ?1",
+                        [terminal_key],
+                        |row| account_from_row(row, 0),
+                    )
+                    .optional()
+                    .context("Failed to find terminal store account in sqlite")?
+                    .ok_or(BankOperationError::TerminalNotFound)?;
+                Ok(account?)
+            })
+            .await?;
+        Ok(account)
+    }
+
+    async fn store_balance(
+        &self,
+        terminal_key: &Uuid,
+    ) -> Result<i64, BankOperationError> {
+        let store_acc = self.get_store_account(terminal_key).await?;
+        self.with_connection(move |connection| {
+            Self::balance(connection, &store_acc.card())
+        })
+        .await
+    }
+
+    async fn balance(
+        &self,
+        card: &CardNumber,
+    ) -> Result<i64, BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| Self::balance(connection, &card))
+            .await
+    }
+
+    #[tracing::instrument(
+        name = "Try to create new simple transaction",
+        skip(self)
+    )]
+    async fn new_transaction(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let (sender, recipient) = (sender.clone(), recipient.clone());
+        let id = self
+            .with_connection(move |connection| {
+                Self::create_transaction(
+                    connection,
+                    sender.as_ref(),
+                    recipient.as_ref(),
+                    amount,
+                )
+            })
+            .await?;
+
+        self.notify();
+        Ok(id)
+    }
+
+    async fn new_payment(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        check_fee(amount, fee)?;
+
+        let (sender, recipient) = (sender.clone(), recipient.clone());
+        let id = self
+            .with_connection(move |connection| {
+                let sender = &sender;
+                // Fee is taken only with the payment itself
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                Self::check_funds(&transaction, sender, amount)?;
+                let id = Self::create_transaction(
+                    &transaction,
+                    sender.as_ref(),
+                    recipient.as_ref(),
+                    amount - fee,
+                )?;
+                Self::fee_transaction(&transaction, sender.as_ref(), fee)?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(id)
+            })
+            .await?;
+
+        self.notify();
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "Try create new split transaction", skip_all)]
+    async fn new_split_transaction(
+        &self,
+        sender: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<Vec<Uuid>, BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+
+        check_fee(amount, fee)?;
+
+        // Beneficiaries share the amount without the fee
+        let parts = split_parts(amount - fee, beneficiaries)?;
+        let sender = sender.clone();
+        let ids = self
+            .with_connection(move |connection| {
+                let sender = &sender;
+                let mut bfc = Vec::with_capacity(parts.len());
+                for (token, amount) in parts {
+                    let acc = Self::get_account_by_token(connection, &token)?;
+                    bfc.push((acc, amount));
+                }
+
+                if bfc.iter().any(|(acc, _)| acc.card_number.eq(sender)) {
+                    return Err(BankOperationError::BadTransaction);
+                }
+
+                // Find sender
+                let _ = Self::find_account(connection, sender)?;
+
+                // Either every beneficiary gets it's part, or nobody does
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                Self::check_funds(&transaction, sender, amount)?;
+
+                let mut ids = Vec::with_capacity(bfc.len());
+                for (recipient, amount) in bfc.iter() {
+                    ids.push(Self::create_transaction(
+                        &transaction,
+                        sender.as_ref(),
+                        recipient.card_number.as_ref(),
+                        *amount,
+                    )?);
+                }
+                Self::fee_transaction(&transaction, sender.as_ref(), fee)?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(ids)
+            })
+            .await?;
+
+        self.notify();
+        Ok(ids)
+    }
+
+    async fn new_refund(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        check_refund_fee(amount, fee)?;
+
+        let (sender, recipient) = (sender.clone(), recipient.clone());
+        self.with_connection(move |connection| {
+            let recipient = &recipient;
+            // Fee is returned only with the refund itself
+            let transaction = connection
+                .transaction()
+                .context("Failed to begin sqlite transaction")?;
+            if amount > fee {
+                Self::create_transaction(
+                    &transaction,
+                    sender.as_ref(),
+                    recipient.as_ref(),
+                    amount - fee,
+                )?;
+            }
+            Self::fee_refund_transaction(
+                &transaction,
+                recipient.as_ref(),
+                fee,
+            )?;
+            transaction
+                .commit()
+                .context("Failed to commit sqlite transaction")?;
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "Try create new split refund transaction",
+        skip_all
+    )]
+    async fn new_split_refund_transaction(
+        &self,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+
+        // Split errors are reported after the recipient checks,
+        // beneficiaries return the amount without the fee
+        let parts = split_parts(amount - fee, beneficiaries);
+        let recipient = recipient.clone();
+        self.with_connection(move |connection| {
+            let recipient = &recipient;
+            // Find recipient
+            let _ = Self::find_account(connection, recipient)?;
+
+            check_refund_fee(amount, fee)?;
+
+            let parts = parts?;
+            let mut bfc = Vec::with_capacity(parts.len());
+            for (token, amount) in parts {
+                let acc = Self::get_account_by_token(connection, &token)?;
+                bfc.push((acc, amount));
+            }
+
+            // Either every beneficiary returns it's part, or nobody does
+            let transaction = connection
+                .transaction()
+                .context("Failed to begin sqlite transaction")?;
+            for (sender, amount) in bfc.iter() {
+                Self::create_transaction(
+                    &transaction,
+                    sender.card_number.as_ref(),
+                    recipient.as_ref(),
+                    *amount,
+                )?;
+            }
+            Self::fee_refund_transaction(
+                &transaction,
+                recipient.as_ref(),
+                fee,
+            )?;
+            transaction
+                .commit()
+                .context("Failed to commit sqlite transaction")?;
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Try to create new funds hold", skip(self))]
+    async fn new_hold(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let card = card.clone();
+        let id = self
+            .with_connection(move |connection| {
+                let card = &card;
+                let account = Self::find_account(connection, card)?;
+                if !account.is_existing {
+                    return Err(BankOperationError::AccountIsDeleted);
+                }
+
+                if amount <= 0 {
+                    return Err(BankOperationError::BadTransaction);
+                }
+
+                Self::account_status(connection, card)?.check_can_send()?;
+                Self::check_funds(connection, card, amount)?;
+
+                let id = Uuid::new_v4();
+                let now = OffsetDateTime::now_utc();
+                connection
+                    .execute(
+                        "INSERT INTO holds(id, created_at, account, amount, expires_at)
+                        VALUES (
+                            ?1,
+                            ?2,
+                            (SELECT id FROM accounts WHERE card_number = ?3),
+                            ?4,
+                            ?5
+                        )",
+                        params![id, now, card.as_ref(), amount, now + HOLD_LIFETIME],
+                    )
+                    .context("Failed to insert funds hold into sqlite")?;
+                Ok(id)
+            })
+            .await?;
+        self.notify();
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "Try to release funds hold", skip(self))]
+    async fn release_hold(&self, hold: Uuid) -> Result<(), BankOperationError> {
+        let deleted = self
+            .with_connection(move |connection| {
+                let deleted = connection
+                    .execute("DELETE FROM holds WHERE id = ?1", [hold])
+                    .context("Failed to delete funds hold from sqlite")?;
+                Ok(deleted)
+            })
+            .await?;
+        if deleted == 0 {
+            return Err(BankOperationError::HoldNotFound);
+        }
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Release all funds holds", skip(self))]
+    async fn release_all_holds(&self) -> Result<(), BankOperationError> {
+        self.with_connection(|connection| {
+            connection
+                .execute("DELETE FROM holds", [])
+                .context("Failed to delete funds holds from sqlite")?;
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Try to capture funds hold", skip(self))]
+    async fn capture_hold(
+        &self,
+        hold: Uuid,
+        recipient: &CardNumber,
+        amount: i64,
+        fee: i64,
+    ) -> Result<(), BankOperationError> {
+        check_fee(amount, fee)?;
+
+        let recipient = recipient.clone();
+        self.with_connection(move |connection| {
+            // Hold is removed only if the transaction succeeds
+            let transaction = connection
+                .transaction()
+                .context("Failed to begin sqlite transaction")?;
+            let (held_amount, payer) = held_funds(&transaction, &hold)?;
+            if amount > held_amount {
+                return Err(BankOperationError::BadTransaction);
+            }
+            transaction
+                .execute("DELETE FROM holds WHERE id = ?1", [hold])
+                .context("Failed to delete funds hold from sqlite")?;
+            Self::create_transaction(
+                &transaction,
+                &payer,
+                recipient.as_ref(),
+                amount - fee,
+            )?;
+            Self::fee_transaction(&transaction, &payer, fee)?;
+            transaction
+                .commit()
+                .context("Failed to commit sqlite transaction")?;
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Try to capture split funds hold", skip_all)]
+    async fn capture_split_hold(
+        &self,
+        hold: Uuid,
+        amount: i64,
+        fee: i64,
+        beneficiaries: &Beneficiaries,
+    ) -> Result<(), BankOperationError> {
+        beneficiaries
+            .validate()
+            .map_err(|_| BankOperationError::BadTransaction)?;
+
+        check_fee(amount, fee)?;
+
+        // Beneficiaries share the amount without the fee
+        let parts = split_parts(amount - fee, beneficiaries)?;
+        self.with_connection(move |connection| {
+            let mut bfc = Vec::with_capacity(parts.len());
+            for (token, amount) in parts {
+                let acc = Self::get_account_by_token(connection, &token)?;
+                bfc.push((acc, amount));
+            }
+
+            // Hold is removed only if all transactions succeed
+            let transaction = connection
+                .transaction()
+                .context("Failed to begin sqlite transaction")?;
+            let (held_amount, payer) = held_funds(&transaction, &hold)?;
+            if amount > held_amount
+                || bfc
+                    .iter()
+                    .any(|(acc, _)| acc.card_number.as_ref().eq(&payer))
+            {
+                return Err(BankOperationError::BadTransaction);
+            }
+            transaction
+                .execute("DELETE FROM holds WHERE id = ?1", [hold])
+                .context("Failed to delete funds hold from sqlite")?;
+            for (recipient, amount) in bfc.iter() {
+                Self::create_transaction(
+                    &transaction,
+                    &payer,
+                    recipient.card_number.as_ref(),
+                    *amount,
+                )?;
+            }
+            Self::fee_transaction(&transaction, &payer, fee)?;
+            transaction
+                .commit()
+                .context("Failed to commit sqlite transaction")?;
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+
+    async fn open_credit(
+        &self,
+        card: &CardNumber,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        let card = card.clone();
+        let id = self
+            .with_connection(move |connection| {
+                let emission_account = Self::emission_account(connection)?;
+                Self::create_transaction(
+                    connection,
+                    emission_account.card_number.as_ref(),
+                    card.as_ref(),
+                    amount,
+                )
+            })
+            .await?;
+        self.notify();
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "Try to open a new loan", skip(self))]
+    async fn open_loan(
+        &self,
+        card: &CardNumber,
+        principal: i64,
+        interest_rate: Decimal,
+        term: u32,
+        period: Duration,
+    ) -> Result<Loan, BankOperationError> {
+        let card = card.clone();
+        let loan = self
+            .with_connection(move |connection| {
+                let card = &card;
+                let account = Self::find_account(connection, card)?;
+                let loan = Loan {
+                    id: Uuid::new_v4(),
+                    card_number: account.card_number,
+                    principal,
+                    interest_rate,
+                    term,
+                    period,
+                    opened_at: OffsetDateTime::now_utc(),
+                    repaid: 0,
+                };
+                loan.validate()?;
+                let emission_account = Self::emission_account(connection)?;
+
+                // Loan is recorded only with it's funds transaction
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                Self::create_transaction(
+                    &transaction,
+                    emission_account.card_number.as_ref(),
+                    card.as_ref(),
+                    principal,
+                )?;
+                transaction
+                    .execute(
+                        "INSERT INTO loans(id, created_at, account, principal, interest_rate, term, period_secs)
+                        VALUES (
+                            ?1,
+                            ?2,
+                            (SELECT id FROM accounts WHERE card_number = ?3),
+                            ?4,
+                            ?5,
+                            ?6,
+                            ?7
+                        )",
+                        params![
+                            loan.id,
+                            loan.opened_at,
+                            card.as_ref(),
+                            principal,
+                            interest_rate.to_string(),
+                            term,
+                            period.whole_seconds()
+                        ],
+                    )
+                    .context("Failed to insert loan into sqlite")?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(loan)
+            })
+            .await?;
+
+        self.notify();
+        Ok(loan)
+    }
+
+    #[tracing::instrument(name = "Try to repay a loan", skip(self))]
+    async fn repay_loan(
+        &self,
+        id: &Uuid,
+        amount: i64,
+    ) -> Result<Uuid, BankOperationError> {
+        if amount <= 0 {
+            return Err(BankOperationError::BadTransaction);
+        }
+
+        let id = *id;
+        let transaction_id = self
+            .with_connection(move |connection| {
+                let id = &id;
+                let emission_account = Self::emission_account(connection)?;
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                let loan = Self::loan(&transaction, id)?;
+                if amount > loan.outstanding()? {
+                    return Err(BankOperationError::BadOperation(
+                        "Repayment exceeds outstanding loan amount".to_string(),
+                    ));
+                }
+
+                let transaction_id = Self::create_transaction(
+                    &transaction,
+                    loan.card_number.as_ref(),
+                    emission_account.card_number.as_ref(),
+                    amount,
+                )?;
+                transaction
+                    .execute(
+                        "UPDATE loans SET repaid = repaid + ?1 WHERE id = ?2",
+                        params![amount, id],
+                    )
+                    .context("Failed to update loan in sqlite")?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(transaction_id)
+            })
+            .await?;
+
+        self.notify();
+        Ok(transaction_id)
+    }
+
+    async fn get_loan(&self, id: &Uuid) -> Result<Loan, BankOperationError> {
+        let id = *id;
+        self.with_connection(move |connection| Self::loan(connection, &id))
+            .await
+    }
+
+    async fn new_transfer_order(
+        &self,
+        sender: &CardNumber,
+        recipient: &CardNumber,
+        amount: i64,
+        schedule: OrderSchedule,
+    ) -> Result<TransferOrder, BankOperationError> {
+        let (sender, recipient) = (sender.clone(), recipient.clone());
+        let order = self
+            .with_connection(move |connection| {
+                for card in [&sender, &recipient] {
+                    if !Self::find_account(connection, card)?.is_existing {
+                        return Err(BankOperationError::AccountIsDeleted);
+                    }
+                }
+                let order = TransferOrder::new(
+                    sender.clone(),
+                    recipient.clone(),
+                    amount,
+                    schedule,
+                    OffsetDateTime::now_utc(),
+                )?;
+                let (run_at, cron) = match order.schedule {
+                    OrderSchedule::Once(at) => (Some(utc(at)), None),
+                    OrderSchedule::Cron(ref cron) => {
+                        (None, Some(cron.as_str()))
+                    }
+                };
+                connection
+                    .execute(
+                        "INSERT INTO transfer_orders(id, created_at, sender, recipient, amount, run_at, cron, next_run_at)
+                        VALUES (
+                            ?1,
+                            ?2,
+                            (SELECT id FROM accounts WHERE card_number = ?3),
+                            (SELECT id FROM accounts WHERE card_number = ?4),
+                            ?5,
+                            ?6,
+                            ?7,
+                            ?8
+                        )",
+                        params![
+                            order.id,
+                            order.created_at,
+                            sender.as_ref(),
+                            recipient.as_ref(),
+                            amount,
+                            run_at,
+                            cron,
+                            order.next_run_at.map(utc)
+                        ],
+                    )
+                    .context("Failed to insert transfer order into sqlite")?;
+                Ok(order)
+            })
+            .await?;
+
+        self.notify();
+        Ok(order)
+    }
+
+    async fn list_transfer_orders(
+        &self,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        self.with_connection(|connection| {
+            Self::transfer_orders(connection, None)
+        })
+        .await
+    }
+
+    async fn cancel_transfer_order(
+        &self,
+        id: &Uuid,
+    ) -> Result<(), BankOperationError> {
+        let id = *id;
+        self.with_connection(move |connection| {
+            let id = &id;
+            let cancelled = connection
+                .execute(
+                    "UPDATE transfer_orders
+                    SET cancelled_at = ?1, next_run_at = NULL
+                    WHERE id = ?2 AND cancelled_at IS NULL",
+                    params![OffsetDateTime::now_utc(), id],
+                )
+                .context("Failed to cancel transfer order in sqlite")?;
+            if cancelled == 0 {
+                if is_transfer_order_exists(connection, id)? {
+                    return Err(BankOperationError::BadOperation(
+                        "Order is already cancelled".to_string(),
+                    ));
+                }
+                return Err(BankOperationError::OrderNotFound);
+            }
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+
+    async fn due_transfer_orders(
+        &self,
+        now: OffsetDateTime,
+    ) -> Result<Vec<TransferOrder>, BankOperationError> {
+        self.with_connection(move |connection| {
+            Self::transfer_orders(connection, Some(now))
+        })
+        .await
+    }
+
+    async fn run_transfer_order(
+        &self,
+        id: &Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Option<OrderExecution>, BankOperationError> {
+        let id = *id;
+        let execution = self
+            .with_connection(move |connection| {
+                let id = &id;
+                let transaction = connection
+                    .transaction()
+                    .context("Failed to begin sqlite transaction")?;
+                let Some(order) = Self::transfer_orders(&transaction, Some(now))?
+                    .into_iter()
+                    .find(|order| order.id.eq(id))
+                else {
+                    return Ok(None);
+                };
+                // Failed transfer doesn't abort the sqlite transaction
+                let result = Self::create_transaction(
+                    &transaction,
+                    order.sender.as_ref(),
+                    order.recipient.as_ref(),
+                    order.amount,
+                );
+                let execution = OrderExecution::new(&result);
+                transaction
+                    .execute(
+                        "INSERT INTO transfer_order_executions(order_id, executed_at, transaction_id, error)
+                        VALUES (?1, ?2, ?3, ?4)",
+                        params![
+                            id,
+                            utc(execution.executed_at),
+                            execution.transaction_id,
+                            execution.error
+                        ],
+                    )
+                    .context("Failed to insert order execution into sqlite")?;
+                transaction
+                    .execute(
+                        "UPDATE transfer_orders
+                        SET next_run_at = ?1
+                        WHERE id = ?2",
+                        params![order.schedule.next_run(now).map(utc), id],
+                    )
+                    .context("Failed to reschedule transfer order in sqlite")?;
+                transaction
+                    .commit()
+                    .context("Failed to commit sqlite transaction")?;
+                Ok(Some(execution))
+            })
+            .await?;
+
+        self.notify();
+        Ok(execution)
+    }
+
+    async fn set_overdraft_limit(
+        &self,
+        card: &CardNumber,
+        limit: i64,
+    ) -> Result<(), BankOperationError> {
+        if limit < 0 {
+            return Err(BankOperationError::BadOperation(
+                "Overdraft limit can't be negative".to_string(),
+            ));
+        }
+
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            let card = &card;
+            let _ = Self::find_account(connection, card)?;
+            connection
+                .execute(
+                    "UPDATE accounts SET overdraft_limit = ?1 WHERE card_number = ?2",
+                    params![limit, card.as_ref()],
+                )
+                .context("Failed to set overdraft limit in sqlite")?;
+
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        self.with_connection(|connection| {
+            Self::transactions(connection, "ORDER BY t.id", [])
+        })
+        .await
+    }
+
+    async fn list_transactions_after(
+        &self,
+        last: Option<Uuid>,
+    ) -> Result<Vec<Transaction>, BankOperationError> {
+        self.with_connection(move |connection| {
+            Self::transactions(
+                connection,
+                "WHERE t.id > COALESCE(
+                    (SELECT id FROM transactions WHERE transaction_id = ?1),
+                    0
+                )
+                ORDER BY t.id",
+                [last],
+            )
+        })
+        .await
+    }
+
+    async fn get_transaction(
+        &self,
+        id: &Uuid,
+    ) -> Result<Transaction, BankOperationError> {
+        let id = *id;
+        self.with_connection(move |connection| {
+            Self::transactions(connection, "WHERE t.transaction_id = ?1", [id])?
+                .pop()
+                .ok_or(BankOperationError::TransactionNotFound)
+        })
+        .await
+    }
+
+    async fn account_statement(
+        &self,
+        req: &StatementRequest,
+    ) -> Result<StatementResponse, BankOperationError> {
+        let req = req.clone();
+        self.with_connection(move |connection| {
+            let _ = Self::find_account(connection, &req.card_number)?;
+            if let Some(cursor) = req.cursor {
+                let exists: bool = connection
+                    .query_row(
+                        "SELECT EXISTS (
+                            SELECT 1 FROM transactions WHERE transaction_id = ?1
+                        )",
+                        [cursor],
+                        |row| row.get(0),
+                    )
+                    .context("Failed to check transaction existence in sqlite")?;
+                if !exists {
+                    return Err(BankOperationError::TransactionNotFound);
+                }
+            }
+
+            let limit = req.limit();
+            let transactions = Self::transactions(
+                connection,
+                "WHERE (s.card_number = :card_number OR r.card_number = :card_number)
+                    AND (
+                        :cursor IS NULL
+                        OR (t.created_at, t.id) > (
+                            SELECT created_at, id FROM transactions WHERE transaction_id = :cursor
+                        )
+                    )
+                    AND (:date_from IS NULL OR t.created_at >= :date_from)
+                    AND (:date_to IS NULL OR t.created_at < :date_to)
+                    AND (
+                        :direction IS NULL
+                        OR (:direction = 'incoming' AND r.card_number = :card_number)
+                        OR (:direction = 'outgoing' AND s.card_number = :card_number)
+                    )
+                    AND (
+                        :counterparty IS NULL
+                        OR (s.card_number = :card_number AND r.card_number = :counterparty)
+                        OR (r.card_number = :card_number AND s.card_number = :counterparty)
+                    )
+                    AND (:min_amount IS NULL OR t.amount >= :min_amount)
+                    AND (:max_amount IS NULL OR t.amount <= :max_amount)
+                ORDER BY t.created_at, t.id
+                LIMIT :limit",
+                named_params! {
+                    ":card_number": req.card_number.as_ref(),
+                    ":cursor": req.cursor,
+                    ":date_from": req.from.map(utc),
+                    ":date_to": req.to.map(utc),
+                    ":direction": req.direction.map(|d| d.as_str()),
+                    ":counterparty": req.counterparty.as_ref().map(|c| c.as_ref()),
+                    ":min_amount": req.min_amount,
+                    ":max_amount": req.max_amount,
+                    ":limit": limit as i64 + 1,
+                },
+            )?;
+
+            Ok(StatementResponse::from_transactions(transactions, limit))
+        })
+        .await
+    }
+
+    async fn bank_emission(&self) -> Result<i64, BankOperationError> {
+        self.with_connection(|connection| {
+            let emission_account = Self::emission_account(connection)?;
+            Self::balance(connection, &emission_account.card_number)
+        })
+        .await
+    }
+
+    async fn bank_revenue(&self) -> Result<i64, BankOperationError> {
+        self.with_connection(|connection| {
+            let balance = connection
+                .query_row(
+                    "SELECT a.balance
+                    FROM system_accounts
+                    JOIN accounts a ON system_accounts.account = a.id
+                    WHERE system_accounts.role = 'revenue'",
+                    [],
+                    |row| row.get(0),
+                )
+                .context("Failed to fetch revenue account from sqlite")?;
+            Ok(balance)
+        })
+        .await
+    }
+
+    async fn audit(&self) -> Result<AuditReport, BankOperationError> {
+        // Nothing changes the data while the connection is locked
+        self.with_connection(|connection| {
+            let mut stmt = connection
+                .prepare(
+                    "SELECT id, card_number, balance, overdraft_limit, deleted_at
+                    FROM accounts
+                    ORDER BY id",
+                )
+                .context("Failed to prepare accounts query for sqlite")?;
+            let accounts = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .context("Failed to get accounts from the sqlite")?
+                .into_iter()
+                .map(|(id, card, balance, overdraft_limit, deleted_at)| {
+                    Ok(AuditedAccount {
+                        card_number: card.parse()?,
+                        balance,
+                        overdraft_limit,
+                        is_emission: id == 1,
+                        deleted_at,
+                    })
+                })
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            let mut stmt = connection
+                .prepare(
+                    "SELECT tokens.token, a.card_number
+                    FROM tokens
+                    LEFT JOIN accounts a ON tokens.account = a.id
+                    ORDER BY tokens.id",
+                )
+                .context("Failed to prepare tokens query for sqlite")?;
+            let tokens = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                    ))
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .context("Failed to get tokens from the sqlite")?
+                .into_iter()
+                .map(|(token, card_number)| {
+                    Ok(AuditedToken {
+                        token,
+                        card_number: card_number
+                            .map(|c| c.parse())
+                            .transpose()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            let transactions =
+                Self::transactions(connection, "ORDER BY t.id", [])?;
+
+            Ok(audit::audit(&accounts, &transactions, &tokens))
+        })
+        .await
+    }
+
+    async fn new_card_token(
+        &self,
+        card: &CardNumber,
+        expires_at: Option<OffsetDateTime>,
+        terminal_key: Option<Uuid>,
+    ) -> Result<String, BankOperationError> {
+        let token = generate_token();
+
+        let card = card.clone();
+        let token = self
+            .with_connection(move |connection| {
+                let card = &card;
+                // Check that card exists
+                let _ = Self::find_account(connection, card)?;
+                connection
+                    .execute(
+                        "INSERT INTO tokens(created_at, account, token, expires_at, terminal_key)
+                        VALUES (
+                            ?1,
+                            (SELECT id FROM accounts WHERE card_number = ?2),
+                            ?3,
+                            ?4,
+                            ?5
+                        )",
+                        params![
+                            OffsetDateTime::now_utc(),
+                            card.as_ref(),
+                            token,
+                            expires_at.map(utc),
+                            terminal_key
+                        ],
+                    )
+                    .context("Failed to insert new card token into sqlite")?;
+                Ok(token)
+            })
+            .await?;
+        self.notify();
+        Ok(token)
+    }
+
+    async fn revoke_card_token(
+        &self,
+        token: &str,
+    ) -> Result<(), BankOperationError> {
+        let token = token.to_string();
+        self.with_connection(move |connection| {
+            let token = token.as_str();
+            let revoked = connection
+                .execute(
+                    "UPDATE tokens
+                    SET revoked_at = ?1
+                    WHERE token = ?2 AND revoked_at IS NULL",
+                    params![OffsetDateTime::now_utc(), token],
+                )
+                .context("Failed to revoke card token in sqlite")?;
+            if revoked == 0 {
+                // Token is either missing or already revoked
+                Self::card_token(connection, token)?;
+                return Err(BankOperationError::TokenRevoked);
+            }
+            Ok(())
+        })
+        .await?;
+        self.notify();
+        Ok(())
+    }
+
+    async fn list_card_tokens(
+        &self,
+        card: &CardNumber,
+    ) -> Result<Vec<CardToken>, BankOperationError> {
+        let card = card.clone();
+        self.with_connection(move |connection| {
+            let card = &card;
+            let _ = Self::find_account(connection, card)?;
+            let mut stmt = connection
+                .prepare(
+                    "SELECT
+                        tokens.token,
+                        tokens.created_at,
+                        tokens.expires_at,
+                        tokens.revoked_at,
+                        tokens.terminal_key
+                    FROM tokens
+                    JOIN accounts a ON tokens.account = a.id
+                    WHERE a.card_number = ?1
+                    ORDER BY tokens.created_at, tokens.id",
+                )
+                .context("Failed to prepare card tokens query for sqlite")?;
+            let tokens = stmt
+                .query_map([card.as_ref()], |row| {
+                    Ok(CardToken {
+                        token: row.get(0)?,
+                        card_number: card.clone(),
+                        created_at: row.get(1)?,
+                        expires_at: row.get(2)?,
+                        revoked_at: row.get(3)?,
+                        terminal_key: row.get(4)?,
+                    })
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .context("Failed to list card tokens from sqlite")?;
+            Ok(tokens)
+        })
+        .await
+    }
+
+    async fn get_card_token(
+        &self,
+        token: &str,
+    ) -> Result<CardToken, BankOperationError> {
+        let token = token.to_string();
+        self.with_connection(move |connection| {
+            Self::card_token(connection, &token)
+        })
+        .await
+    }
+
+    async fn get_account_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Account, BankOperationError> {
+        let token = token.to_string();
+        self.with_connection(move |connection| {
+            Self::get_account_by_token(connection, &token)
+        })
+        .await
+    }
+
+    async fn begin_idempotent_request(
+        &self,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyStatus, BankOperationError> {
+        let (key, request_hash) = (key.to_string(), request_hash.to_string());
+        self.with_connection(move |connection| {
+            let inserted = connection
+                .execute(
+                    "INSERT INTO idempotency_keys(idempotency_key, request_hash)
+                    VALUES (?1, ?2)
+                    ON CONFLICT DO NOTHING",
+                    params![key, request_hash],
+                )
+                .context("Failed to insert idempotency key into sqlite")?;
+            if inserted == 1 {
+                return Ok(IdempotencyStatus::New);
+            }
+
+            let record = connection
+                .query_row(
+                    "SELECT
+                        request_hash,
+                        response_status,
+                        response_content_type,
+                        response_body
+                    FROM idempotency_keys
+                    WHERE idempotency_key = ?1",
+                    [&key],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, Option<u16>>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                            row.get::<_, Option<Vec<u8>>>(3)?,
+                        ))
+                    },
+                )
+                .optional()
+                .context("Failed to get idempotency key from sqlite")?;
+            Ok(match record {
+                Some((hash, ..)) if !hash.eq(&request_hash) => {
+                    IdempotencyStatus::KeyReused
+                }
+                Some((_, Some(status), content_type, Some(body))) => {
+                    IdempotencyStatus::Completed(IdempotentResponse {
+                        status,
+                        content_type,
+                        body,
+                    })
+                }
+                // Key was aborted by the concurrent request, or still in use
+                _ => IdempotencyStatus::InProgress,
+            })
+        })
+        .await
+    }
+
+    async fn complete_idempotent_request(
+        &self,
+        key: &str,
+        response: &IdempotentResponse,
+    ) -> Result<(), BankOperationError> {
+        let (key, response) = (key.to_string(), response.clone());
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "UPDATE idempotency_keys
+                    SET
+                        response_status = ?1,
+                        response_content_type = ?2,
+                        response_body = ?3
+                    WHERE idempotency_key = ?4",
+                    params![
+                        response.status,
+                        response.content_type,
+                        response.body,
+                        key
+                    ],
+                )
+                .context("Failed to save idempotent response into sqlite")?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn abort_idempotent_request(
+        &self,
+        key: &str,
+    ) -> Result<(), BankOperationError> {
+        let key = key.to_string();
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "DELETE FROM idempotency_keys WHERE idempotency_key = ?1",
+                    [key],
+                )
+                .context("Failed to delete idempotency key from sqlite")?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn export_dump(&self) -> Result<BankDump, BankOperationError> {
+        // Nothing changes the data while the connection is locked
+        self.with_connection(|connection| {
+            let mut stmt = connection
+                .prepare(
+                    "SELECT
+                        a.id,
+                        a.username,
+                        a.card_number,
+                        a.password_hash,
+                        a.is_existing,
+                        a.deleted_at,
+                        a.overdraft_limit,
+                        a.status,
+                        a.status_reason,
+                        a.card_expiry_month,
+                        a.card_expiry_year,
+                        a.cvv_hash,
+                        t.terminal_key,
+                        s.role
+                    FROM accounts a
+                    LEFT JOIN terminals t ON t.account = a.id
+                    LEFT JOIN system_accounts s ON s.account = a.id
+                    ORDER BY a.id",
+                )
+                .context("Failed to prepare accounts query for sqlite")?;
+            let mut rows = stmt
+                .query([])
+                .context("Failed to get accounts from the sqlite")?;
+            let mut accounts = Vec::new();
+            while let Some(row) = rows
+                .next()
+                .context("Failed to get accounts from the sqlite")?
+            {
+                accounts.push(dump_account_from_row(row)?);
+            }
+
+            let mut stmt = connection
+                .prepare(
+                    "SELECT
+                        tokens.token,
+                        a.card_number,
+                        tokens.created_at,
+                        tokens.expires_at,
+                        tokens.revoked_at,
+                        tokens.terminal_key
+                    FROM tokens
+                    JOIN accounts a ON tokens.account = a.id
+                    ORDER BY tokens.id",
+                )
+                .context("Failed to prepare tokens query for sqlite")?;
+            let tokens = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .context("Failed to get tokens from the sqlite")?
+                .into_iter()
+                .map(
+                    |(
+                        token,
+                        card_number,
+                        created_at,
+                        expires_at,
+                        revoked_at,
+                        terminal_key,
+                    )| {
+                        Ok(DumpToken {
+                            token,
+                            card_number: card_number.parse()?,
+                            created_at,
+                            expires_at,
+                            revoked_at,
+                            terminal_key,
+                        })
+                    },
+                )
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            let transactions =
+                Self::transactions(connection, "ORDER BY t.id", [])?
+                    .iter()
+                    .map(DumpTransaction::from)
+                    .collect();
+
+            Ok(BankDump {
+                version: DUMP_VERSION,
+                accounts,
+                tokens,
+                transactions,
+            })
+        })
+        .await
+    }
+
+    async fn import_dump(
+        &self,
+        dump: &BankDump,
+    ) -> Result<(), BankOperationError> {
+        // Hashing is slow, so it is done before taking the connection
+        let mut accounts = Vec::new();
+        for acc in dump.import_order() {
+            let (username, password_hash) =
+                match acc.system_credentials(&self.settings) {
+                    Some((username, password)) => (
+                        username,
+                        hash_password_blocking(
+                            self.argon2_obj.clone(),
+                            password.clone(),
+                        )
+                        .await?,
+                    ),
+                    None => (acc.username.clone(), acc.password_hash.clone()),
+                };
+            accounts.push((acc.clone(), username, password_hash));
+        }
+        let mut new_stores = Vec::new();
+        for terminal in dump.missing_terminals(&self.settings) {
+            let password_hash = hash_password_blocking(
+                self.argon2_obj.clone(),
+                terminal.password.clone(),
+            )
+            .await?;
+            new_stores.push((
+                terminal.terminal_key,
+                self.settings.store_username(terminal),
+                CardNumber::generate(&self.settings.card_settings),
+                password_hash,
+            ));
+        }
+        let (tokens, transactions) =
+            (dump.tokens.clone(), dump.transactions.clone());
+
+        self.with_connection(move |connection| {
+            let transaction = connection
+                .transaction()
+                .context("Failed to begin sqlite transaction")?;
+            // History is replaced as is. Sqlite triggers can't be
+            // disabled, so they are created again after the import.
+            let mut guards = Vec::with_capacity(TRANSACTION_GUARDS.len());
+            for name in TRANSACTION_GUARDS {
+                let sql: String = transaction
+                    .query_row(
+                        "SELECT sql FROM sqlite_master
+                        WHERE type = 'trigger' AND name = ?1",
+                        [name],
+                        |row| row.get(0),
+                    )
+                    .context("Failed to get transactions trigger from sqlite")?;
+                transaction
+                    .execute_batch(&format!("DROP TRIGGER {name}"))
+                    .context("Failed to drop transactions trigger in sqlite")?;
+                guards.push(sql);
+            }
+            // Referencing rows go first, emission account gets
+            // the first id again
+            transaction
+                .execute_batch(
+                    "DELETE FROM transfer_order_executions;
+                    DELETE FROM transfer_orders;
+                    DELETE FROM loans;
+                    DELETE FROM holds;
+                    DELETE FROM tokens;
+                    DELETE FROM terminals;
+                    DELETE FROM system_accounts;
+                    DELETE FROM idempotency_keys;
+                    DELETE FROM transactions;
+                    DELETE FROM accounts;
+                    DELETE FROM sqlite_sequence;",
+                )
+                .context("Failed to clear bank data in sqlite")?;
+
+            for (acc, username, password_hash) in accounts.iter() {
+                let card = acc.card.as_ref();
+                transaction
+                    .execute(
+                        "INSERT INTO accounts(
+                            created_at,
+                            username,
+                            card_number,
+                            password_hash,
+                            is_existing,
+                            deleted_at,
+                            overdraft_limit,
+                            status,
+                            status_reason,
+                            card_expiry_month,
+                            card_expiry_year,
+                            cvv_hash
+                        )
+                        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                        params![
+                            OffsetDateTime::now_utc(),
+                            username,
+                            acc.card_number.as_ref(),
+                            password_hash,
+                            acc.is_existing,
+                            acc.deleted_at.map(utc),
+                            acc.overdraft_limit,
+                            acc.status.as_str(),
+                            acc.status_reason,
+                            card.map(|card| card.expiry.month),
+                            card.map(|card| card.expiry.year),
+                            card.map(|card| card.cvv_hash.as_str()),
+                        ],
+                    )
+                    .context("Failed to import account to sqlite")?;
+                match acc.role {
+                    AccountRole::Store { terminal_key } => {
+                        insert_terminal(
+                            &transaction,
+                            &terminal_key,
+                            &acc.card_number,
+                        )
+                        .context("Failed to import terminal to sqlite")?;
+                    }
+                    AccountRole::Revenue => {
+                        mark_revenue_account(&transaction, &acc.card_number)
+                            .context(
+                                "Failed to import revenue account to sqlite",
+                            )?;
+                    }
+                    AccountRole::Emission | AccountRole::Customer => (),
+                }
+            }
+            for (terminal_key, username, card_number, password_hash) in
+                new_stores.iter()
+            {
+                insert_account(
+                    &transaction,
+                    username,
+                    card_number,
+                    password_hash,
+                )
+                .context("Failed to insert store account to sqlite")?;
+                insert_terminal(&transaction, terminal_key, card_number)
+                    .context("Failed to insert terminal to sqlite")?;
+            }
+            for token in tokens.iter() {
+                transaction
+                    .execute(
+                        "INSERT INTO tokens(created_at, account, token, expires_at, revoked_at, terminal_key)
+                        VALUES (
+                            ?1,
+                            (SELECT id FROM accounts WHERE card_number = ?2),
+                            ?3,
+                            ?4,
+                            ?5,
+                            ?6
+                        )",
+                        params![
+                            utc(token.created_at),
+                            token.card_number.as_ref(),
+                            token.token,
+                            token.expires_at.map(utc),
+                            token.revoked_at.map(utc),
+                            token.terminal_key,
+                        ],
+                    )
+                    .context("Failed to import card token to sqlite")?;
+            }
+            for t in transactions.iter() {
+                transaction
+                    .execute(
+                        "INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount, kind)
+                        VALUES (
+                            ?1,
+                            ?2,
+                            (SELECT id FROM accounts WHERE card_number = ?3),
+                            (SELECT id FROM accounts WHERE card_number = ?4),
+                            ?5,
+                            ?6
+                        )",
+                        params![
+                            t.id,
+                            utc(t.datetime),
+                            t.sender.as_ref(),
+                            t.recipient.as_ref(),
+                            t.amount,
+                            t.kind.as_str(),
+                        ],
+                    )
+                    .context("Failed to import transaction to sqlite")?;
+            }
+
+            for sql in guards {
+                transaction
+                    .execute_batch(&sql)
+                    .context("Failed to create transactions trigger in sqlite")?;
+            }
+            transaction
+                .commit()
+                .context("Failed to commit sqlite transaction")?;
+            Ok(())
+        })
+        .await?;
+
+        self.notify();
+        Ok(())
+    }
+}
+
+fn insert_account(
+    connection: &Connection,
+    username: &str,
+    card_number: &CardNumber,
+    password_hash: &str,
+) -> Result<usize, rusqlite::Error> {
+    connection.execute(
+        "INSERT INTO accounts(created_at, username, card_number, password_hash)
+        VALUES (?1, ?2, ?3, ?4)",
+        params![
+            OffsetDateTime::now_utc(),
+            username,
+            card_number.as_ref(),
+            password_hash
+        ],
+    )
+}
+
+fn insert_terminal(
+    connection: &Connection,
+    terminal_key: &Uuid,
+    card_number: &CardNumber,
+) -> Result<usize, rusqlite::Error> {
+    connection.execute(
+        "INSERT INTO terminals(terminal_key, account)
+        VALUES (
+            ?1,
+            (SELECT id FROM accounts WHERE card_number = ?2)
+        )",
+        params![terminal_key, card_number.as_ref()],
+    )
+}
+
+fn mark_revenue_account(
+    connection: &Connection,
+    card_number: &CardNumber,
+) -> Result<usize, rusqlite::Error> {
+    connection.execute(
+        "INSERT INTO system_accounts(role, account)
+        VALUES (
+            'revenue',
+            (SELECT id FROM accounts WHERE card_number = ?1)
+        )",
+        [card_number.as_ref()],
+    )
+}
+
+/// Owned shares of the split amount, so they can be moved to
+/// the connection
+fn split_parts(
+    amount: i64,
+    beneficiaries: &Beneficiaries,
+) -> Result<Vec<(String, i64)>, BankOperationError> {
+    Ok(split::split_amount(amount, beneficiaries)?
+        .into_iter()
+        .map(|(token, share)| (token.clone(), share))
+        .collect())
+}
+
+/// Amount and card number of the funds hold
+fn held_funds(
+    connection: &Connection,
+    hold: &Uuid,
+) -> Result<(i64, String), BankOperationError> {
+    let held = connection
+        .query_row(
+            "SELECT holds.amount, a.card_number
+            FROM holds
+            JOIN accounts a ON holds.account = a.id
+            WHERE holds.id = ?1
+                AND julianday(holds.expires_at) > julianday('now')",
+            [hold],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("Failed to get funds hold from sqlite")?
+        .ok_or(BankOperationError::HoldNotFound)?;
+    Ok(held)
+}
+
+fn is_transfer_order_exists(
+    connection: &Connection,
+    id: &Uuid,
+) -> Result<bool, BankOperationError> {
+    let exists = connection
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM transfer_orders WHERE id = ?1)",
+            [id],
+            |row| row.get(0),
+        )
+        .context("Failed to check transfer order in sqlite")?;
+    Ok(exists)
+}
+
+/// Read username, card number and existence flag,
+/// starting from the `idx` column
+fn account_from_row(
+    row: &Row,
+    idx: usize,
+) -> Result<Result<Account, anyhow::Error>, rusqlite::Error> {
+    let card_number: String = row.get(idx + 1)?;
+    Ok(Ok(Account {
+        username: row.get(idx)?,
+        card_number: match card_number.parse() {
+            Ok(card_number) => card_number,
+            Err(e) => return Ok(Err(e)),
+        },
+        password: Secret::new(String::new()),
+        is_existing: row.get(idx + 2)?,
+    }))
+}
+
+fn transaction_from_row(row: &Row) -> Result<Transaction, anyhow::Error> {
+    let kind: String = row.get(2)?;
+    let sender = account_from_row(row, 4)??;
+    let recipient = account_from_row(row, 7)??;
+    Ok(Transaction {
+        id: row.get(0)?,
+        sender,
+        recipient,
+        amount: row.get(1)?,
+        kind: kind.parse()?,
+        datetime: row.get(3)?,
+    })
+}
+
+/// Read account of the bank dump, roles are found the same
+/// way as in Postgres storage
+fn dump_account_from_row(row: &Row) -> Result<DumpAccount, anyhow::Error> {
+    let id: i64 = row.get(0)?;
+    let username: String = row.get(1)?;
+    let terminal_key: Option<Uuid> = row.get(12)?;
+    let system_role: Option<String> = row.get(13)?;
+    let role = if id == 1 {
+        AccountRole::Emission
+    } else if system_role.as_deref() == Some("revenue") {
+        AccountRole::Revenue
+    } else if let Some(terminal_key) = terminal_key {
+        AccountRole::Store { terminal_key }
+    } else {
+        AccountRole::Customer
+    };
+    let card_number: String = row.get(2)?;
+    let status: String = row.get(7)?;
+    let card = match (row.get(9)?, row.get(10)?, row.get(11)?) {
+        (Some(month), Some(year), Some(cvv_hash)) => Some(DumpCard {
+            expiry: CardExpiry { month, year },
+            cvv_hash,
+        }),
+        _ => None,
+    };
+    Ok(DumpAccount {
+        username,
+        card_number: card_number.parse()?,
+        role,
+        password_hash: row.get(3)?,
+        is_existing: row.get(4)?,
+        deleted_at: row.get(5)?,
+        overdraft_limit: row.get(6)?,
+        status: status.parse()?,
+        status_reason: row.get(8)?,
+        card,
+    })
+}
+
+/// Times are compared as text in queries, so all of them are stored
+/// with the same offset
+fn utc(datetime: OffsetDateTime) -> OffsetDateTime {
+    datetime.to_offset(UtcOffset::UTC)
+}
+
+/// Map errors raised by the `check_balance_before_transaction` trigger
+fn map_transaction_error(e: rusqlite::Error) -> BankOperationError {
+    tracing::error!("Failed to create transaction: {e}");
+    if let rusqlite::Error::SqliteFailure(_, Some(message)) = &e {
+        match message.as_str() {
+            "Not enough funds" => return BankOperationError::NotEnoughFunds,
+            "Overdraft limit exceeded" => {
+                return BankOperationError::OverdraftLimitExceeded
+            }
+            "Amount must be greater than 0" => {
+                return BankOperationError::BadTransaction
+            }
+            "Sender and recipient cannot be the same" => {
+                return BankOperationError::BadTransaction
+            }
+            "Sender or recipient account does not exist or is not active" => {
+                return BankOperationError::AccountNotFound
+            }
+            "Account is frozen" => return BankOperationError::AccountIsFrozen,
+            "Account is blocked" => {
+                return BankOperationError::AccountIsBlocked
+            }
+            _ => (),
+        }
+    }
+    BankOperationError::UnexpectedError
+}
@@ -0,0 +1,314 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month, OffsetDateTime, Time, UtcOffset};
+use uuid::Uuid;
+
+use crate::domain::card_number::CardNumber;
+
+use super::{iso_format, BankOperationError};
+
+/// Transfer between two cards, which runs once at the given time
+/// or repeats by the cron schedule. Runs are performed by the scheduler.
+#[derive(Serialize, Clone, Debug)]
+pub struct TransferOrder {
+    pub id: Uuid,
+    pub sender: CardNumber,
+    pub recipient: CardNumber,
+    pub amount: i64,
+    pub schedule: OrderSchedule,
+    #[serde(with = "iso_format")]
+    pub created_at: OffsetDateTime,
+    /// Not set for finished and cancelled orders
+    #[serde(with = "iso_format::option")]
+    pub next_run_at: Option<OffsetDateTime>,
+    #[serde(with = "iso_format::option")]
+    pub cancelled_at: Option<OffsetDateTime>,
+    pub executions: Vec<OrderExecution>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSchedule {
+    Once(#[serde(with = "iso_format")] OffsetDateTime),
+    Cron(CronSchedule),
+}
+
+/// Outcome of the single order run
+#[derive(Serialize, Clone, Debug)]
+pub struct OrderExecution {
+    #[serde(with = "iso_format")]
+    pub executed_at: OffsetDateTime,
+    pub transaction_id: Option<Uuid>,
+    /// Failure reason, like `not_enough_funds`
+    pub error: Option<String>,
+}
+
+impl OrderExecution {
+    /// Outcome of the order's transfer, made right now
+    pub fn new(result: &Result<Uuid, BankOperationError>) -> Self {
+        OrderExecution {
+            executed_at: OffsetDateTime::now_utc(),
+            transaction_id: result.as_ref().ok().copied(),
+            error: result.as_ref().err().map(|e| e.str_reason_for_client()),
+        }
+    }
+}
+
+impl TransferOrder {
+    pub fn new(
+        sender: CardNumber,
+        recipient: CardNumber,
+        amount: i64,
+        schedule: OrderSchedule,
+        now: OffsetDateTime,
+    ) -> Result<Self, BankOperationError> {
+        if amount <= 0 {
+            return Err(BankOperationError::BadOperation(
+                "Order amount should be positive".to_string(),
+            ));
+        }
+        if sender.eq(&recipient) {
+            return Err(BankOperationError::BadOperation(
+                "Sender and recipient should differ".to_string(),
+            ));
+        }
+        let next_run_at = schedule.first_run(now).ok_or_else(|| {
+            BankOperationError::BadOperation(
+                "Order schedule never runs".to_string(),
+            )
+        })?;
+        Ok(TransferOrder {
+            id: Uuid::new_v4(),
+            sender,
+            recipient,
+            amount,
+            schedule,
+            created_at: now,
+            next_run_at: Some(next_run_at),
+            cancelled_at: None,
+            executions: Vec::new(),
+        })
+    }
+}
+
+impl OrderSchedule {
+    /// One-time order with the past time runs right away
+    pub fn first_run(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self {
+            OrderSchedule::Once(at) => Some(*at),
+            OrderSchedule::Cron(cron) => cron.next_after(now),
+        }
+    }
+
+    /// `None` if the order is finished after the run. Runs missed
+    /// while the bank was stopped are not repeated.
+    pub fn next_run(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self {
+            OrderSchedule::Once(_) => None,
+            OrderSchedule::Cron(cron) => cron.next_after(now),
+        }
+    }
+}
+
+// ───── Cron ─────────────────────────────────────────────────────────────── //
+
+/// Cron expression with 5 fields: minute, hour, day of month, month
+/// and day of week (0 and 7 are Sunday), like `0 9 * * 1-5`.
+/// Fields support `*`, lists, ranges and steps, time is in UTC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// If both day fields are restricted, any of them should match
+    days_restricted: (bool, bool),
+}
+
+impl CronSchedule {
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    /// First matching minute after `after`
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let after = after.to_offset(UtcOffset::UTC);
+        let mut t = after.replace_time(
+            Time::from_hms(after.hour(), after.minute(), 0).ok()?,
+        ) + Duration::minutes(1);
+        // Impossible dates, like 31 of February, never match
+        let limit = t + Duration::days(366 * 5);
+        while t < limit {
+            if !has_bit(self.months, t.month() as u8) {
+                let month = t.month().next();
+                let year = match month {
+                    Month::January => t.year() + 1,
+                    _ => t.year(),
+                };
+                t = Date::from_calendar_date(year, month, 1)
+                    .ok()?
+                    .midnight()
+                    .assume_utc();
+            } else if !self.day_matches(t) {
+                t = t.date().next_day()?.midnight().assume_utc();
+            } else if !has_bit(self.hours, t.hour()) {
+                t = t.replace_time(Time::from_hms(t.hour(), 0, 0).ok()?)
+                    + Duration::hours(1);
+            } else if !has_bit(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: OffsetDateTime) -> bool {
+        let dom = has_bit(self.days_of_month, t.day());
+        let dow =
+            has_bit(self.days_of_week, t.weekday().number_days_from_sunday());
+        match self.days_restricted {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+}
+
+fn has_bit(mask: u64, bit: u8) -> bool {
+    mask & (1 << bit) != 0
+}
+
+/// Parse cron field into the bit mask of allowed values
+fn parse_field(field: &str, min: u8, max: u8) -> Result<u64, anyhow::Error> {
+    let mut mask = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u8>()?),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(anyhow::anyhow!("Cron step can't be zero"));
+        }
+        let (from, to) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((from, to)) => (from.parse()?, to.parse()?),
+            // `5/15` means from 5 to the end with step
+            None if item.contains('/') => (range.parse()?, max),
+            None => {
+                let value = range.parse()?;
+                (value, value)
+            }
+        };
+        if from < min || to > max || from > to {
+            return Err(anyhow::anyhow!(
+                "Cron value is out of range {min}-{max}: {item}"
+            ));
+        }
+        for value in (from..=to).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..]
+        else {
+            return Err(anyhow::anyhow!(
+                "Cron expression should have 5 fields"
+            ));
+        };
+        let mut days_of_week_mask = parse_field(days_of_week, 0, 7)?;
+        // Sunday can be written both as 0 and 7
+        if has_bit(days_of_week_mask, 7) {
+            days_of_week_mask = (days_of_week_mask | 1) & !(1 << 7);
+        }
+        Ok(CronSchedule {
+            expr: fields.join(" "),
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days_of_month: parse_field(days_of_month, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            days_of_week: days_of_week_mask,
+            days_restricted: (
+                !days_of_month.starts_with('*'),
+                !days_of_week.starts_with('*'),
+            ),
+        })
+    }
+}
+
+impl Serialize for CronSchedule {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.expr)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let expr = String::deserialize(deserializer)?;
+        expr.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn cron(expr: &str) -> CronSchedule {
+        expr.parse().unwrap()
+    }
+
+    #[test]
+    fn cron_finds_next_run() {
+        let now = datetime!(2024-01-31 10:30:15 UTC);
+        assert_eq!(
+            cron("* * * * *").next_after(now),
+            Some(datetime!(2024-01-31 10:31 UTC))
+        );
+        assert_eq!(
+            cron("0 9 1 * *").next_after(now),
+            Some(datetime!(2024-02-01 9:00 UTC))
+        );
+        assert_eq!(
+            cron("*/20 10 * * *").next_after(now),
+            Some(datetime!(2024-01-31 10:40 UTC))
+        );
+        // 3 of February 2024 is Saturday, weekdays only
+        assert_eq!(
+            cron("0 9 * * 1-5").next_after(datetime!(2024-02-02 9:00 UTC)),
+            Some(datetime!(2024-02-05 9:00 UTC))
+        );
+        // Sunday as 7, or the 15th day of month
+        assert_eq!(
+            cron("0 0 15 * 7").next_after(now),
+            Some(datetime!(2024-02-04 0:00 UTC))
+        );
+        assert_eq!(
+            cron("0 0 29 2 *").next_after(now),
+            Some(datetime!(2024-02-29 0:00 UTC))
+        );
+        assert_eq!(cron("0 0 31 2 *").next_after(now), None);
+    }
+
+    #[test]
+    fn bad_cron_is_rejected() {
+        for expr in ["", "* * * *", "60 * * * *", "* * 0 * *", "*/0 * * * *"] {
+            assert!(expr.parse::<CronSchedule>().is_err(), "{expr}");
+        }
+    }
+}
@@ -1,15 +1,21 @@
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use config::FileFormat;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use secrecy::Secret;
 use serde::Deserialize;
 use url::Url;
 
+use crate::bank::max_fee;
+
 #[derive(Deserialize, Debug, Clone)]
 pub enum DataBackendType {
     Pg,
     Mem,
+    Sqlite,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -17,11 +23,22 @@ pub struct Settings {
     #[serde(default = "data_backend_type")]
     pub data_backend_type: DataBackendType,
     pub database_settings: Option<DatabaseSettings>,
+    /// Required for the `sqlite` backend
+    pub sqlite_settings: Option<SqliteSettings>,
+    /// Snapshots of the `memory` backend, data is lost on restart
+    /// without them
+    pub memory_settings: Option<MemorySettings>,
     pub port: u16,
     pub addr: String,
+    /// Default terminal, used when request doesn't specify `terminal_key`
     pub terminal_settings: TerminalSettings,
+    /// Terminals of other merchant stores
+    #[serde(default)]
+    pub terminals: Vec<TerminalSettings>,
     pub bank_username: String,
     pub frontend_path: String,
+    #[serde(default)]
+    pub card_settings: CardSettings,
 }
 
 impl Settings {
@@ -29,17 +46,65 @@ impl Settings {
         let config_file = std::env::var("BANKSIM_CONFIG_FILE")
             .expect("BANKSIM_CONFIG_FILE var is unset!");
 
-        config::Config::builder()
+        let settings: Settings = config::Config::builder()
             .add_source(config::File::new(&config_file, FileFormat::Yaml))
             .build()?
             .try_deserialize()
-            .context("Failed to build config from local config file.")
+            .context("Failed to build config from local config file.")?;
+        settings.card_settings.validate()?;
+        let mut terminal_keys = HashSet::new();
+        for terminal in settings.all_terminals() {
+            terminal.fee.validate()?;
+            if !terminal_keys.insert(terminal.terminal_key) {
+                return Err(anyhow::anyhow!(
+                    "Duplicated terminal key: {}",
+                    terminal.terminal_key
+                ));
+            }
+        }
+        if let Some(memory_settings) = settings.memory_settings.as_ref() {
+            memory_settings.validate()?;
+        }
+        Ok(settings)
+    }
+
+    /// Iterate over all terminals, the default one goes first
+    pub fn all_terminals(&self) -> impl Iterator<Item = &TerminalSettings> {
+        std::iter::once(&self.terminal_settings).chain(self.terminals.iter())
+    }
+
+    /// Find terminal by key, or take the default one if key is not set
+    pub fn find_terminal(
+        &self,
+        terminal_key: Option<uuid::Uuid>,
+    ) -> Option<&TerminalSettings> {
+        match terminal_key {
+            Some(key) => self.all_terminals().find(|t| t.terminal_key.eq(&key)),
+            None => Some(&self.terminal_settings),
+        }
+    }
+
+    /// Username of the terminal's store account,
+    /// the default terminal keeps the `store` name.
+    pub fn store_username(&self, terminal: &TerminalSettings) -> String {
+        if terminal
+            .terminal_key
+            .eq(&self.terminal_settings.terminal_key)
+        {
+            "store".to_string()
+        } else {
+            format!("store_{}", terminal.terminal_key)
+        }
+    }
+
+    /// Username of the account, which collects acquiring fees
+    pub fn revenue_username(&self) -> String {
+        format!("{}_revenue", self.bank_username)
     }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct TerminalSettings {
-    // Currently unused
     pub terminal_key: uuid::Uuid,
 
     pub success_url: Url,
@@ -55,6 +120,90 @@ pub struct TerminalSettings {
     pub send_notification_completed: bool,
     /// Определяет, будет ли отправлена нотификация на выполнение метода Cancel
     pub send_notification_reversed: bool,
+    /// Acquiring fee, withheld from payments to the store
+    #[serde(default)]
+    pub fee: FeeSchedule,
+}
+
+/// Fee is `percent` of the payment amount plus `fixed` amount,
+/// but not less than `min`. Default schedule takes no fee.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct FeeSchedule {
+    #[serde(default)]
+    pub percent: Decimal,
+    #[serde(default)]
+    pub fixed: i64,
+    #[serde(default)]
+    pub min: i64,
+}
+
+impl FeeSchedule {
+    /// Fee for the payment, it is always less than the payment amount
+    pub fn fee(&self, amount: i64) -> i64 {
+        let percent = (Decimal::from(amount) * self.percent
+            / Decimal::ONE_HUNDRED)
+            .round()
+            .to_i64()
+            .unwrap_or(amount);
+        (percent + self.fixed)
+            .max(self.min)
+            .clamp(0, max_fee(amount))
+    }
+
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.percent < Decimal::ZERO || self.percent > Decimal::ONE_HUNDRED {
+            return Err(anyhow::anyhow!(
+                "Fee percent should be from 0 to 100: {}",
+                self.percent
+            ));
+        }
+        if self.fixed < 0 || self.min < 0 {
+            return Err(anyhow::anyhow!("Fee amounts can't be negative"));
+        }
+        Ok(())
+    }
+}
+
+/// Issued card numbers start with one of the `bins`, by default they
+/// emulate Visa, Mastercard and Mir cards.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CardSettings {
+    #[serde(default = "card_bins")]
+    pub bins: Vec<String>,
+    /// Reject card numbers with wrong Luhn check digit in requests,
+    /// disabled by default, cards issued by older versions don't pass it
+    #[serde(default)]
+    pub validate_luhn: bool,
+    /// Issued card is valid for this number of months
+    #[serde(default = "card_validity_months")]
+    pub validity_months: u32,
+}
+
+impl CardSettings {
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.bins.is_empty() {
+            return Err(anyhow::anyhow!("At least one card BIN is required"));
+        }
+        for bin in self.bins.iter() {
+            if bin.is_empty()
+                || bin.len() > 15
+                || !bin.chars().all(|c| c.is_ascii_digit())
+            {
+                return Err(anyhow::anyhow!("Bad card BIN: {bin}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CardSettings {
+    fn default() -> Self {
+        CardSettings {
+            bins: card_bins(),
+            validate_luhn: false,
+            validity_months: card_validity_months(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -66,6 +215,32 @@ pub struct DatabaseSettings {
     pub password: Secret<String>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct SqliteSettings {
+    /// Database file, it is created on the first run
+    pub path: PathBuf,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MemorySettings {
+    /// Snapshot file, it is loaded on startup and saved on shutdown
+    pub snapshot_path: PathBuf,
+    /// Snapshot is also saved with this period, if data was changed
+    #[serde(default = "snapshot_interval_secs")]
+    pub snapshot_interval_secs: u64,
+}
+
+impl MemorySettings {
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.snapshot_interval_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "Snapshot interval should be greater than 0"
+            ));
+        }
+        Ok(())
+    }
+}
+
 fn terminal_password() -> Secret<String> {
     Secret::new(
         load_value_from_file(
@@ -86,12 +261,29 @@ fn pg_password() -> Secret<String> {
     )
 }
 
+fn card_bins() -> Vec<String> {
+    // Visa, Mastercard, Mir
+    ["427600", "546900", "220220"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn card_validity_months() -> u32 {
+    48
+}
+
+fn snapshot_interval_secs() -> u64 {
+    60
+}
+
 fn data_backend_type() -> DataBackendType {
     let value = std::env::var("DATA_BACKEND_TYPE")
         .expect("DATA_BACKEND_TYPE var is unset!");
     match value.as_str() {
         "postgres" => DataBackendType::Pg,
         "memory" => DataBackendType::Mem,
+        "sqlite" => DataBackendType::Sqlite,
         _ => panic!(),
     }
 }
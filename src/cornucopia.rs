@@ -4,7 +4,7 @@
 #[allow(unused_imports)] #[allow(dead_code)] pub mod types { }#[allow(clippy :: all, clippy :: pedantic)] #[allow(unused_variables)]
 #[allow(unused_imports)] #[allow(dead_code)] pub mod queries
 { pub mod bank_queries
-{ use futures::{{StreamExt, TryStreamExt}};use futures; use cornucopia_async::GenericClient;#[derive( Debug)] pub struct InsertAccountParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub username : T1,pub card_number : T2,pub password_hash : T3,}#[derive( Debug)] pub struct CreateTransactionParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub sender_card : T1,pub recipient_card : T2,pub amount : i64,}#[derive( Debug)] pub struct InsertTokenParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub card_number : T1,pub token : T2,}pub struct I64Query < 'a, C : GenericClient, T, const N : usize >
+{ use futures::{{StreamExt, TryStreamExt}};use futures; use cornucopia_async::GenericClient;#[derive( Debug)] pub struct InsertTerminalParams < T1 : cornucopia_async::StringSql,> { pub terminal_key : uuid::Uuid,pub card_number : T1,}#[derive( Debug)] pub struct InsertAccountParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub username : T1,pub card_number : T2,pub password_hash : T3,}#[derive( Debug)] pub struct SetAccountStatusParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub status : T1,pub status_reason : Option<T2>,pub card_number : T3,}#[derive( Debug)] pub struct SetCardDetailsParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub card_expiry_month : i16,pub card_expiry_year : i16,pub cvv_hash : T1,pub card_number : T2,}#[derive( Debug)] pub struct SetAccountOverdraftLimitParams < T1 : cornucopia_async::StringSql,> { pub overdraft_limit : i64,pub card_number : T1,}#[derive( Debug)] pub struct AccountStatementParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub card_number : T1,pub cursor : Option<uuid::Uuid>,pub date_from : Option<time::OffsetDateTime>,pub date_to : Option<time::OffsetDateTime>,pub direction : Option<T2>,pub counterparty : Option<T3>,pub min_amount : Option<i64>,pub max_amount : Option<i64>,pub limit : i64,}#[derive( Debug)] pub struct CreateTransactionParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub transaction_id : uuid::Uuid,pub sender_card : T1,pub recipient_card : T2,pub amount : i64,}#[derive( Debug)] pub struct CreateFeeTransactionParams < T1 : cornucopia_async::StringSql,> { pub transaction_id : uuid::Uuid,pub sender_card : T1,pub amount : i64,}#[derive( Debug)] pub struct CreateFeeRefundTransactionParams < T1 : cornucopia_async::StringSql,> { pub transaction_id : uuid::Uuid,pub recipient_card : T1,pub amount : i64,}#[derive( Debug)] pub struct InsertTokenParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub card_number : T1,pub token : T2,pub expires_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,}#[derive( Debug)] pub struct InsertHoldParams < T1 : cornucopia_async::StringSql,> { pub id : uuid::Uuid,pub card_number : T1,pub amount : i64,pub expires_at : time::OffsetDateTime,}#[derive( Debug)] pub struct InsertIdempotencyKeyParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub idempotency_key : T1,pub request_hash : T2,}#[derive( Debug)] pub struct SaveIdempotentResponseParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::BytesSql,T3 : cornucopia_async::StringSql,> { pub response_status : i16,pub response_content_type : Option<T1>,pub response_body : T2,pub idempotency_key : T3,}#[derive( Debug)] pub struct InsertLoanParams < T1 : cornucopia_async::StringSql,> { pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub card_number : T1,pub principal : i64,pub interest_rate : rust_decimal::Decimal,pub term : i32,pub period_secs : i64,}#[derive(Clone,Copy, Debug)] pub struct AddLoanRepaymentParams { pub amount : i64,pub id : uuid::Uuid,}#[derive( Debug)] pub struct InsertTransferOrderParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub sender : T1,pub recipient : T2,pub amount : i64,pub run_at : Option<time::OffsetDateTime>,pub cron : Option<T3>,pub next_run_at : Option<time::OffsetDateTime>,}#[derive(Clone,Copy, Debug)] pub struct LockTransferOrderParams { pub id : uuid::Uuid,pub due_at : time::OffsetDateTime,}#[derive( Debug)] pub struct InsertOrderExecutionParams < T1 : cornucopia_async::StringSql,> { pub order_id : uuid::Uuid,pub executed_at : time::OffsetDateTime,pub transaction_id : Option<uuid::Uuid>,pub error : Option<T1>,}#[derive(Clone,Copy, Debug)] pub struct SetOrderNextRunParams { pub next_run_at : Option<time::OffsetDateTime>,pub id : uuid::Uuid,}#[derive( Debug)] pub struct ImportAccountParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,T4 : cornucopia_async::StringSql,T5 : cornucopia_async::StringSql,T6 : cornucopia_async::StringSql,> { pub username : T1,pub card_number : T2,pub password_hash : T3,pub is_existing : bool,pub deleted_at : Option<time::OffsetDateTime>,pub overdraft_limit : i64,pub status : T4,pub status_reason : Option<T5>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<T6>,}#[derive( Debug)] pub struct ImportTokenParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,> { pub card_number : T1,pub token : T2,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,}#[derive( Debug)] pub struct ImportTransactionParams < T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> { pub transaction_id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub sender_card : T1,pub recipient_card : T2,pub amount : i64,pub kind : T3,}pub struct I64Query < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
     [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
@@ -44,13 +44,13 @@ where C : GenericClient
         Ok(it)
     }
 }#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetEmissionAccount
-{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : String,pub card_number : String,pub password_hash : String,pub is_existing : bool,}pub struct GetEmissionAccountBorrowed < 'a >
-{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : &'a str,pub card_number : &'a str,pub password_hash : &'a str,pub is_existing : bool,} impl < 'a > From < GetEmissionAccountBorrowed <
+{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : String,pub card_number : String,pub password_hash : String,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : String,pub status_reason : Option<String>,pub status_changed_at : Option<time::OffsetDateTime>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<String>,pub deleted_at : Option<time::OffsetDateTime>,}pub struct GetEmissionAccountBorrowed < 'a >
+{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : &'a str,pub card_number : &'a str,pub password_hash : &'a str,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : &'a str,pub status_reason : Option<&'a str>,pub status_changed_at : Option<time::OffsetDateTime>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<&'a str>,pub deleted_at : Option<time::OffsetDateTime>,} impl < 'a > From < GetEmissionAccountBorrowed <
 'a >> for GetEmissionAccount
 {
     fn
-    from(GetEmissionAccountBorrowed { id,created_at,username,card_number,password_hash,is_existing,} : GetEmissionAccountBorrowed < 'a >)
-    -> Self { Self { id,created_at,username: username.into(),card_number: card_number.into(),password_hash: password_hash.into(),is_existing,} }
+    from(GetEmissionAccountBorrowed { id,created_at,username,card_number,password_hash,is_existing,balance,overdraft_limit,status,status_reason,status_changed_at,card_expiry_month,card_expiry_year,cvv_hash,deleted_at,} : GetEmissionAccountBorrowed < 'a >)
+    -> Self { Self { id,created_at,username: username.into(),card_number: card_number.into(),password_hash: password_hash.into(),is_existing,balance,overdraft_limit,status: status.into(),status_reason: status_reason.map(|v| v.into()),status_changed_at,card_expiry_month,card_expiry_year,cvv_hash: cvv_hash.map(|v| v.into()),deleted_at,} }
 }pub struct GetEmissionAccountQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
@@ -90,27 +90,74 @@ where C : GenericClient
         res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
         Ok(it)
     }
-}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetStoreAccount
-{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : String,pub card_number : String,pub password_hash : String,pub is_existing : bool,}pub struct GetStoreAccountBorrowed < 'a >
-{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : &'a str,pub card_number : &'a str,pub password_hash : &'a str,pub is_existing : bool,} impl < 'a > From < GetStoreAccountBorrowed <
-'a >> for GetStoreAccount
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetLegacyStoreAccount
+{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : String,pub card_number : String,pub password_hash : String,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : String,pub status_reason : Option<String>,pub status_changed_at : Option<time::OffsetDateTime>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<String>,pub deleted_at : Option<time::OffsetDateTime>,}pub struct GetLegacyStoreAccountBorrowed < 'a >
+{ pub id : i32,pub created_at : time::OffsetDateTime,pub username : &'a str,pub card_number : &'a str,pub password_hash : &'a str,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : &'a str,pub status_reason : Option<&'a str>,pub status_changed_at : Option<time::OffsetDateTime>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<&'a str>,pub deleted_at : Option<time::OffsetDateTime>,} impl < 'a > From < GetLegacyStoreAccountBorrowed <
+'a >> for GetLegacyStoreAccount
 {
     fn
-    from(GetStoreAccountBorrowed { id,created_at,username,card_number,password_hash,is_existing,} : GetStoreAccountBorrowed < 'a >)
-    -> Self { Self { id,created_at,username: username.into(),card_number: card_number.into(),password_hash: password_hash.into(),is_existing,} }
-}pub struct GetStoreAccountQuery < 'a, C : GenericClient, T, const N : usize >
+    from(GetLegacyStoreAccountBorrowed { id,created_at,username,card_number,password_hash,is_existing,balance,overdraft_limit,status,status_reason,status_changed_at,card_expiry_month,card_expiry_year,cvv_hash,deleted_at,} : GetLegacyStoreAccountBorrowed < 'a >)
+    -> Self { Self { id,created_at,username: username.into(),card_number: card_number.into(),password_hash: password_hash.into(),is_existing,balance,overdraft_limit,status: status.into(),status_reason: status_reason.map(|v| v.into()),status_changed_at,card_expiry_month,card_expiry_year,cvv_hash: cvv_hash.map(|v| v.into()),deleted_at,} }
+}pub struct GetLegacyStoreAccountQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
     [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
-    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetStoreAccountBorrowed,
-    mapper : fn(GetStoreAccountBorrowed) -> T,
-} impl < 'a, C, T : 'a, const N : usize > GetStoreAccountQuery < 'a, C, T, N >
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetLegacyStoreAccountBorrowed,
+    mapper : fn(GetLegacyStoreAccountBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetLegacyStoreAccountQuery < 'a, C, T, N >
 where C : GenericClient
 {
-    pub fn map < R > (self, mapper : fn(GetStoreAccountBorrowed) -> R) -> GetStoreAccountQuery
+    pub fn map < R > (self, mapper : fn(GetLegacyStoreAccountBorrowed) -> R) -> GetLegacyStoreAccountQuery
     < 'a, C, R, N >
     {
-        GetStoreAccountQuery
+        GetLegacyStoreAccountQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetTerminalStoreAccount
+{ pub username : String,pub card_number : String,pub is_existing : bool,}pub struct GetTerminalStoreAccountBorrowed < 'a >
+{ pub username : &'a str,pub card_number : &'a str,pub is_existing : bool,} impl < 'a > From < GetTerminalStoreAccountBorrowed <
+'a >> for GetTerminalStoreAccount
+{
+    fn
+    from(GetTerminalStoreAccountBorrowed { username,card_number,is_existing,} : GetTerminalStoreAccountBorrowed < 'a >)
+    -> Self { Self { username: username.into(),card_number: card_number.into(),is_existing,} }
+}pub struct GetTerminalStoreAccountQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetTerminalStoreAccountBorrowed,
+    mapper : fn(GetTerminalStoreAccountBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetTerminalStoreAccountQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetTerminalStoreAccountBorrowed) -> R) -> GetTerminalStoreAccountQuery
+    < 'a, C, R, N >
+    {
+        GetTerminalStoreAccountQuery
         {
             client : self.client, params : self.params, stmt : self.stmt,
             extractor : self.extractor, mapper,
@@ -176,14 +223,92 @@ where C : GenericClient
         res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
         Ok(it)
     }
+}pub struct I32Query < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> i32,
+    mapper : fn(i32) -> T,
+} impl < 'a, C, T : 'a, const N : usize > I32Query < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(i32) -> R) -> I32Query
+    < 'a, C, R, N >
+    {
+        I32Query
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}pub struct StringQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> & str,
+    mapper : fn(& str) -> T,
+} impl < 'a, C, T : 'a, const N : usize > StringQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(& str) -> R) -> StringQuery
+    < 'a, C, R, N >
+    {
+        StringQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
 }#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetAccount
-{ pub username : String,pub card_number : String,pub is_existing : bool,pub password_hash : String,}pub struct GetAccountBorrowed < 'a >
-{ pub username : &'a str,pub card_number : &'a str,pub is_existing : bool,pub password_hash : &'a str,} impl < 'a > From < GetAccountBorrowed <
+{ pub username : String,pub card_number : String,pub is_existing : bool,pub password_hash : String,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<String>,}pub struct GetAccountBorrowed < 'a >
+{ pub username : &'a str,pub card_number : &'a str,pub is_existing : bool,pub password_hash : &'a str,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<&'a str>,} impl < 'a > From < GetAccountBorrowed <
 'a >> for GetAccount
 {
     fn
-    from(GetAccountBorrowed { username,card_number,is_existing,password_hash,} : GetAccountBorrowed < 'a >)
-    -> Self { Self { username: username.into(),card_number: card_number.into(),is_existing,password_hash: password_hash.into(),} }
+    from(GetAccountBorrowed { username,card_number,is_existing,password_hash,card_expiry_month,card_expiry_year,cvv_hash,} : GetAccountBorrowed < 'a >)
+    -> Self { Self { username: username.into(),card_number: card_number.into(),is_existing,password_hash: password_hash.into(),card_expiry_month,card_expiry_year,cvv_hash: cvv_hash.map(|v| v.into()),} }
 }pub struct GetAccountQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
@@ -270,6 +395,100 @@ where C : GenericClient
         res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
         Ok(it)
     }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetToken
+{ pub token : String,pub card_number : String,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,}pub struct GetTokenBorrowed < 'a >
+{ pub token : &'a str,pub card_number : &'a str,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,} impl < 'a > From < GetTokenBorrowed <
+'a >> for GetToken
+{
+    fn
+    from(GetTokenBorrowed { token,card_number,created_at,expires_at,revoked_at,terminal_key,} : GetTokenBorrowed < 'a >)
+    -> Self { Self { token: token.into(),card_number: card_number.into(),created_at,expires_at,revoked_at,terminal_key,} }
+}pub struct GetTokenQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetTokenBorrowed,
+    mapper : fn(GetTokenBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetTokenQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetTokenBorrowed) -> R) -> GetTokenQuery
+    < 'a, C, R, N >
+    {
+        GetTokenQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct ListAccountTokens
+{ pub token : String,pub card_number : String,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,}pub struct ListAccountTokensBorrowed < 'a >
+{ pub token : &'a str,pub card_number : &'a str,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,} impl < 'a > From < ListAccountTokensBorrowed <
+'a >> for ListAccountTokens
+{
+    fn
+    from(ListAccountTokensBorrowed { token,card_number,created_at,expires_at,revoked_at,terminal_key,} : ListAccountTokensBorrowed < 'a >)
+    -> Self { Self { token: token.into(),card_number: card_number.into(),created_at,expires_at,revoked_at,terminal_key,} }
+}pub struct ListAccountTokensQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> ListAccountTokensBorrowed,
+    mapper : fn(ListAccountTokensBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > ListAccountTokensQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(ListAccountTokensBorrowed) -> R) -> ListAccountTokensQuery
+    < 'a, C, R, N >
+    {
+        ListAccountTokensQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
 }pub struct RustdecimalDecimalQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
@@ -310,13 +529,13 @@ where C : GenericClient
         Ok(it)
     }
 }#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct ListAccountTransactions
-{ pub amount : i64,pub created_at : time::OffsetDateTime,pub sender_username : String,pub sender_card_number : String,pub sender_is_existing : bool,pub recipient_username : String,pub recipient_card_number : String,pub recipient_is_existing : bool,}pub struct ListAccountTransactionsBorrowed < 'a >
-{ pub amount : i64,pub created_at : time::OffsetDateTime,pub sender_username : &'a str,pub sender_card_number : &'a str,pub sender_is_existing : bool,pub recipient_username : &'a str,pub recipient_card_number : &'a str,pub recipient_is_existing : bool,} impl < 'a > From < ListAccountTransactionsBorrowed <
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : String,pub created_at : time::OffsetDateTime,pub sender_username : String,pub sender_card_number : String,pub sender_is_existing : bool,pub recipient_username : String,pub recipient_card_number : String,pub recipient_is_existing : bool,}pub struct ListAccountTransactionsBorrowed < 'a >
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : &'a str,pub created_at : time::OffsetDateTime,pub sender_username : &'a str,pub sender_card_number : &'a str,pub sender_is_existing : bool,pub recipient_username : &'a str,pub recipient_card_number : &'a str,pub recipient_is_existing : bool,} impl < 'a > From < ListAccountTransactionsBorrowed <
 'a >> for ListAccountTransactions
 {
     fn
-    from(ListAccountTransactionsBorrowed { amount,created_at,sender_username,sender_card_number,sender_is_existing,recipient_username,recipient_card_number,recipient_is_existing,} : ListAccountTransactionsBorrowed < 'a >)
-    -> Self { Self { amount,created_at,sender_username: sender_username.into(),sender_card_number: sender_card_number.into(),sender_is_existing,recipient_username: recipient_username.into(),recipient_card_number: recipient_card_number.into(),recipient_is_existing,} }
+    from(ListAccountTransactionsBorrowed { transaction_id,amount,kind,created_at,sender_username,sender_card_number,sender_is_existing,recipient_username,recipient_card_number,recipient_is_existing,} : ListAccountTransactionsBorrowed < 'a >)
+    -> Self { Self { transaction_id,amount,kind: kind.into(),created_at,sender_username: sender_username.into(),sender_card_number: sender_card_number.into(),sender_is_existing,recipient_username: recipient_username.into(),recipient_card_number: recipient_card_number.into(),recipient_is_existing,} }
 }pub struct ListAccountTransactionsQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
@@ -356,27 +575,27 @@ where C : GenericClient
         res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
         Ok(it)
     }
-}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetAccounts
-{ pub username : String,pub card_number : String,pub is_existing : bool,pub balance : rust_decimal::Decimal,pub tokens : Vec<Option<String>>,}pub struct GetAccountsBorrowed < 'a >
-{ pub username : &'a str,pub card_number : &'a str,pub is_existing : bool,pub balance : rust_decimal::Decimal,pub tokens : cornucopia_async::ArrayIterator<'a, Option<&'a str>>,} impl < 'a > From < GetAccountsBorrowed <
-'a >> for GetAccounts
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetTransaction
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : String,pub created_at : time::OffsetDateTime,pub sender_username : String,pub sender_card_number : String,pub sender_is_existing : bool,pub recipient_username : String,pub recipient_card_number : String,pub recipient_is_existing : bool,}pub struct GetTransactionBorrowed < 'a >
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : &'a str,pub created_at : time::OffsetDateTime,pub sender_username : &'a str,pub sender_card_number : &'a str,pub sender_is_existing : bool,pub recipient_username : &'a str,pub recipient_card_number : &'a str,pub recipient_is_existing : bool,} impl < 'a > From < GetTransactionBorrowed <
+'a >> for GetTransaction
 {
     fn
-    from(GetAccountsBorrowed { username,card_number,is_existing,balance,tokens,} : GetAccountsBorrowed < 'a >)
-    -> Self { Self { username: username.into(),card_number: card_number.into(),is_existing,balance,tokens: tokens.map(|v| v.map(|v| v.into())).collect(),} }
-}pub struct GetAccountsQuery < 'a, C : GenericClient, T, const N : usize >
+    from(GetTransactionBorrowed { transaction_id,amount,kind,created_at,sender_username,sender_card_number,sender_is_existing,recipient_username,recipient_card_number,recipient_is_existing,} : GetTransactionBorrowed < 'a >)
+    -> Self { Self { transaction_id,amount,kind: kind.into(),created_at,sender_username: sender_username.into(),sender_card_number: sender_card_number.into(),sender_is_existing,recipient_username: recipient_username.into(),recipient_card_number: recipient_card_number.into(),recipient_is_existing,} }
+}pub struct GetTransactionQuery < 'a, C : GenericClient, T, const N : usize >
 {
     client : & 'a  C, params :
     [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
-    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetAccountsBorrowed,
-    mapper : fn(GetAccountsBorrowed) -> T,
-} impl < 'a, C, T : 'a, const N : usize > GetAccountsQuery < 'a, C, T, N >
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetTransactionBorrowed,
+    mapper : fn(GetTransactionBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetTransactionQuery < 'a, C, T, N >
 where C : GenericClient
 {
-    pub fn map < R > (self, mapper : fn(GetAccountsBorrowed) -> R) -> GetAccountsQuery
+    pub fn map < R > (self, mapper : fn(GetTransactionBorrowed) -> R) -> GetTransactionQuery
     < 'a, C, R, N >
     {
-        GetAccountsQuery
+        GetTransactionQuery
         {
             client : self.client, params : self.params, stmt : self.stmt,
             extractor : self.extractor, mapper,
@@ -403,263 +622,2003 @@ where C : GenericClient
         res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
         Ok(it)
     }
-}pub fn accounts_count() -> AccountsCountStmt
-{ AccountsCountStmt(cornucopia_async :: private :: Stmt :: new("SELECT COUNT(*)
-FROM accounts")) } pub
-struct AccountsCountStmt(cornucopia_async :: private :: Stmt) ; impl
-AccountsCountStmt { pub fn bind < 'a, C : GenericClient, >
-(& 'a mut self, client : & 'a  C,
-) -> I64Query < 'a, C,
-i64, 0 >
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct ListTransactions
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : String,pub created_at : time::OffsetDateTime,pub sender_username : String,pub sender_card_number : String,pub sender_is_existing : bool,pub recipient_username : String,pub recipient_card_number : String,pub recipient_is_existing : bool,}pub struct ListTransactionsBorrowed < 'a >
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : &'a str,pub created_at : time::OffsetDateTime,pub sender_username : &'a str,pub sender_card_number : &'a str,pub sender_is_existing : bool,pub recipient_username : &'a str,pub recipient_card_number : &'a str,pub recipient_is_existing : bool,} impl < 'a > From < ListTransactionsBorrowed <
+'a >> for ListTransactions
 {
-    I64Query
-    {
-        client, params : [], stmt : & mut self.0, extractor :
-        | row | { row.get(0) }, mapper : | it | { it },
-    }
-} }pub fn get_emission_account() -> GetEmissionAccountStmt
-{ GetEmissionAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT *
-FROM accounts
-WHERE accounts.id = 1")) } pub
-struct GetEmissionAccountStmt(cornucopia_async :: private :: Stmt) ; impl
-GetEmissionAccountStmt { pub fn bind < 'a, C : GenericClient, >
-(& 'a mut self, client : & 'a  C,
-) -> GetEmissionAccountQuery < 'a, C,
-GetEmissionAccount, 0 >
+    fn
+    from(ListTransactionsBorrowed { transaction_id,amount,kind,created_at,sender_username,sender_card_number,sender_is_existing,recipient_username,recipient_card_number,recipient_is_existing,} : ListTransactionsBorrowed < 'a >)
+    -> Self { Self { transaction_id,amount,kind: kind.into(),created_at,sender_username: sender_username.into(),sender_card_number: sender_card_number.into(),sender_is_existing,recipient_username: recipient_username.into(),recipient_card_number: recipient_card_number.into(),recipient_is_existing,} }
+}pub struct ListTransactionsQuery < 'a, C : GenericClient, T, const N : usize >
 {
-    GetEmissionAccountQuery
-    {
-        client, params : [], stmt : & mut self.0, extractor :
-        | row | { GetEmissionAccountBorrowed { id : row.get(0),created_at : row.get(1),username : row.get(2),card_number : row.get(3),password_hash : row.get(4),is_existing : row.get(5),} }, mapper : | it | { <GetEmissionAccount>::from(it) },
-    }
-} }pub fn get_store_account() -> GetStoreAccountStmt
-{ GetStoreAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT *
-FROM accounts
-WHERE accounts.id = 2")) } pub
-struct GetStoreAccountStmt(cornucopia_async :: private :: Stmt) ; impl
-GetStoreAccountStmt { pub fn bind < 'a, C : GenericClient, >
-(& 'a mut self, client : & 'a  C,
-) -> GetStoreAccountQuery < 'a, C,
-GetStoreAccount, 0 >
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> ListTransactionsBorrowed,
+    mapper : fn(ListTransactionsBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > ListTransactionsQuery < 'a, C, T, N >
+where C : GenericClient
 {
-    GetStoreAccountQuery
+    pub fn map < R > (self, mapper : fn(ListTransactionsBorrowed) -> R) -> ListTransactionsQuery
+    < 'a, C, R, N >
     {
-        client, params : [], stmt : & mut self.0, extractor :
-        | row | { GetStoreAccountBorrowed { id : row.get(0),created_at : row.get(1),username : row.get(2),card_number : row.get(3),password_hash : row.get(4),is_existing : row.get(5),} }, mapper : | it | { <GetStoreAccount>::from(it) },
+        ListTransactionsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
     }
-} }pub fn insert_account() -> InsertAccountStmt
-{ InsertAccountStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO accounts(username, card_number, password_hash)
-VALUES ($1, $2, $3)")) } pub
-struct InsertAccountStmt(cornucopia_async :: private :: Stmt) ; impl
-InsertAccountStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
-(& 'a mut self, client : & 'a  C,
-username : & 'a T1,card_number : & 'a T2,password_hash : & 'a T3,) -> Result < u64, tokio_postgres :: Error >
-{
-    let stmt = self.0.prepare(client) .await ? ;
-    client.execute(stmt, & [username,card_number,password_hash,]) .await
-} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
-cornucopia_async :: Params < 'a, InsertAccountParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
-u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertAccountStmt
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct AccountStatement
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : String,pub created_at : time::OffsetDateTime,pub sender_username : String,pub sender_card_number : String,pub sender_is_existing : bool,pub recipient_username : String,pub recipient_card_number : String,pub recipient_is_existing : bool,}pub struct AccountStatementBorrowed < 'a >
+{ pub transaction_id : uuid::Uuid,pub amount : i64,pub kind : &'a str,pub created_at : time::OffsetDateTime,pub sender_username : &'a str,pub sender_card_number : &'a str,pub sender_is_existing : bool,pub recipient_username : &'a str,pub recipient_card_number : &'a str,pub recipient_is_existing : bool,} impl < 'a > From < AccountStatementBorrowed <
+'a >> for AccountStatement
 {
     fn
-    params(& 'a mut self, client : & 'a  C, params : & 'a
-    InsertAccountParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
-    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.username,& params.card_number,& params.password_hash,) ) }
-}pub fn is_account_exists() -> IsAccountExistsStmt
+    from(AccountStatementBorrowed { transaction_id,amount,kind,created_at,sender_username,sender_card_number,sender_is_existing,recipient_username,recipient_card_number,recipient_is_existing,} : AccountStatementBorrowed < 'a >)
+    -> Self { Self { transaction_id,amount,kind: kind.into(),created_at,sender_username: sender_username.into(),sender_card_number: sender_card_number.into(),sender_is_existing,recipient_username: recipient_username.into(),recipient_card_number: recipient_card_number.into(),recipient_is_existing,} }
+}pub struct AccountStatementQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> AccountStatementBorrowed,
+    mapper : fn(AccountStatementBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > AccountStatementQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(AccountStatementBorrowed) -> R) -> AccountStatementQuery
+    < 'a, C, R, N >
+    {
+        AccountStatementQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetAccounts
+{ pub username : String,pub card_number : String,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : String,pub status_reason : Option<String>,pub tokens : Vec<Option<String>>,}pub struct GetAccountsBorrowed < 'a >
+{ pub username : &'a str,pub card_number : &'a str,pub is_existing : bool,pub balance : i64,pub overdraft_limit : i64,pub status : &'a str,pub status_reason : Option<&'a str>,pub tokens : cornucopia_async::ArrayIterator<'a, Option<&'a str>>,} impl < 'a > From < GetAccountsBorrowed <
+'a >> for GetAccounts
+{
+    fn
+    from(GetAccountsBorrowed { username,card_number,is_existing,balance,overdraft_limit,status,status_reason,tokens,} : GetAccountsBorrowed < 'a >)
+    -> Self { Self { username: username.into(),card_number: card_number.into(),is_existing,balance,overdraft_limit,status: status.into(),status_reason: status_reason.map(|v| v.into()),tokens: tokens.map(|v| v.map(|v| v.into())).collect(),} }
+}pub struct GetAccountsQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetAccountsBorrowed,
+    mapper : fn(GetAccountsBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetAccountsQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetAccountsBorrowed) -> R) -> GetAccountsQuery
+    < 'a, C, R, N >
+    {
+        GetAccountsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetHold
+{ pub amount : i64,pub card_number : String,}pub struct GetHoldBorrowed < 'a >
+{ pub amount : i64,pub card_number : &'a str,} impl < 'a > From < GetHoldBorrowed <
+'a >> for GetHold
+{
+    fn
+    from(GetHoldBorrowed { amount,card_number,} : GetHoldBorrowed < 'a >)
+    -> Self { Self { amount,card_number: card_number.into(),} }
+}pub struct GetHoldQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetHoldBorrowed,
+    mapper : fn(GetHoldBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetHoldQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetHoldBorrowed) -> R) -> GetHoldQuery
+    < 'a, C, R, N >
+    {
+        GetHoldQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, Copy)] pub struct ListAccountHolds
+{ pub id : uuid::Uuid,pub amount : i64,pub created_at : time::OffsetDateTime,pub expires_at : time::OffsetDateTime,}pub struct ListAccountHoldsQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> ListAccountHolds,
+    mapper : fn(ListAccountHolds) -> T,
+} impl < 'a, C, T : 'a, const N : usize > ListAccountHoldsQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(ListAccountHolds) -> R) -> ListAccountHoldsQuery
+    < 'a, C, R, N >
+    {
+        ListAccountHoldsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetIdempotencyKey
+{ pub request_hash : String,pub response_status : Option<i16>,pub response_content_type : Option<String>,pub response_body : Option<Vec<u8>>,}pub struct GetIdempotencyKeyBorrowed < 'a >
+{ pub request_hash : &'a str,pub response_status : Option<i16>,pub response_content_type : Option<&'a str>,pub response_body : Option<&'a [u8]>,} impl < 'a > From < GetIdempotencyKeyBorrowed <
+'a >> for GetIdempotencyKey
+{
+    fn
+    from(GetIdempotencyKeyBorrowed { request_hash,response_status,response_content_type,response_body,} : GetIdempotencyKeyBorrowed < 'a >)
+    -> Self { Self { request_hash: request_hash.into(),response_status,response_content_type: response_content_type.map(|v| v.into()),response_body: response_body.map(|v| v.into()),} }
+}pub struct GetIdempotencyKeyQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetIdempotencyKeyBorrowed,
+    mapper : fn(GetIdempotencyKeyBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetIdempotencyKeyQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetIdempotencyKeyBorrowed) -> R) -> GetIdempotencyKeyQuery
+    < 'a, C, R, N >
+    {
+        GetIdempotencyKeyQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetLoan
+{ pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub card_number : String,pub principal : i64,pub interest_rate : rust_decimal::Decimal,pub term : i32,pub period_secs : i64,pub repaid : i64,}pub struct GetLoanBorrowed < 'a >
+{ pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub card_number : &'a str,pub principal : i64,pub interest_rate : rust_decimal::Decimal,pub term : i32,pub period_secs : i64,pub repaid : i64,} impl < 'a > From < GetLoanBorrowed <
+'a >> for GetLoan
+{
+    fn
+    from(GetLoanBorrowed { id,created_at,card_number,principal,interest_rate,term,period_secs,repaid,} : GetLoanBorrowed < 'a >)
+    -> Self { Self { id,created_at,card_number: card_number.into(),principal,interest_rate,term,period_secs,repaid,} }
+}pub struct GetLoanQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetLoanBorrowed,
+    mapper : fn(GetLoanBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetLoanQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetLoanBorrowed) -> R) -> GetLoanQuery
+    < 'a, C, R, N >
+    {
+        GetLoanQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}pub struct UuidUuidQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> uuid::Uuid,
+    mapper : fn(uuid::Uuid) -> T,
+} impl < 'a, C, T : 'a, const N : usize > UuidUuidQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(uuid::Uuid) -> R) -> UuidUuidQuery
+    < 'a, C, R, N >
+    {
+        UuidUuidQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetTransferOrders
+{ pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub sender : String,pub recipient : String,pub amount : i64,pub run_at : Option<time::OffsetDateTime>,pub cron : Option<String>,pub next_run_at : Option<time::OffsetDateTime>,pub cancelled_at : Option<time::OffsetDateTime>,}pub struct GetTransferOrdersBorrowed < 'a >
+{ pub id : uuid::Uuid,pub created_at : time::OffsetDateTime,pub sender : &'a str,pub recipient : &'a str,pub amount : i64,pub run_at : Option<time::OffsetDateTime>,pub cron : Option<&'a str>,pub next_run_at : Option<time::OffsetDateTime>,pub cancelled_at : Option<time::OffsetDateTime>,} impl < 'a > From < GetTransferOrdersBorrowed <
+'a >> for GetTransferOrders
+{
+    fn
+    from(GetTransferOrdersBorrowed { id,created_at,sender,recipient,amount,run_at,cron,next_run_at,cancelled_at,} : GetTransferOrdersBorrowed < 'a >)
+    -> Self { Self { id,created_at,sender: sender.into(),recipient: recipient.into(),amount,run_at,cron: cron.map(|v| v.into()),next_run_at,cancelled_at,} }
+}pub struct GetTransferOrdersQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetTransferOrdersBorrowed,
+    mapper : fn(GetTransferOrdersBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetTransferOrdersQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetTransferOrdersBorrowed) -> R) -> GetTransferOrdersQuery
+    < 'a, C, R, N >
+    {
+        GetTransferOrdersQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct GetOrderExecutions
+{ pub order_id : uuid::Uuid,pub executed_at : time::OffsetDateTime,pub transaction_id : Option<uuid::Uuid>,pub error : Option<String>,}pub struct GetOrderExecutionsBorrowed < 'a >
+{ pub order_id : uuid::Uuid,pub executed_at : time::OffsetDateTime,pub transaction_id : Option<uuid::Uuid>,pub error : Option<&'a str>,} impl < 'a > From < GetOrderExecutionsBorrowed <
+'a >> for GetOrderExecutions
+{
+    fn
+    from(GetOrderExecutionsBorrowed { order_id,executed_at,transaction_id,error,} : GetOrderExecutionsBorrowed < 'a >)
+    -> Self { Self { order_id,executed_at,transaction_id,error: error.map(|v| v.into()),} }
+}pub struct GetOrderExecutionsQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> GetOrderExecutionsBorrowed,
+    mapper : fn(GetOrderExecutionsBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > GetOrderExecutionsQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(GetOrderExecutionsBorrowed) -> R) -> GetOrderExecutionsQuery
+    < 'a, C, R, N >
+    {
+        GetOrderExecutionsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct AuditAccounts
+{ pub id : i32,pub card_number : String,pub balance : i64,pub overdraft_limit : i64,pub deleted_at : Option<time::OffsetDateTime>,}pub struct AuditAccountsBorrowed < 'a >
+{ pub id : i32,pub card_number : &'a str,pub balance : i64,pub overdraft_limit : i64,pub deleted_at : Option<time::OffsetDateTime>,} impl < 'a > From < AuditAccountsBorrowed <
+'a >> for AuditAccounts
+{
+    fn
+    from(AuditAccountsBorrowed { id,card_number,balance,overdraft_limit,deleted_at,} : AuditAccountsBorrowed < 'a >)
+    -> Self { Self { id,card_number: card_number.into(),balance,overdraft_limit,deleted_at,} }
+}pub struct AuditAccountsQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> AuditAccountsBorrowed,
+    mapper : fn(AuditAccountsBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > AuditAccountsQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(AuditAccountsBorrowed) -> R) -> AuditAccountsQuery
+    < 'a, C, R, N >
+    {
+        AuditAccountsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct AuditTokens
+{ pub token : String,pub card_number : Option<String>,}pub struct AuditTokensBorrowed < 'a >
+{ pub token : &'a str,pub card_number : Option<&'a str>,} impl < 'a > From < AuditTokensBorrowed <
+'a >> for AuditTokens
+{
+    fn
+    from(AuditTokensBorrowed { token,card_number,} : AuditTokensBorrowed < 'a >)
+    -> Self { Self { token: token.into(),card_number: card_number.map(|v| v.into()),} }
+}pub struct AuditTokensQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> AuditTokensBorrowed,
+    mapper : fn(AuditTokensBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > AuditTokensQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(AuditTokensBorrowed) -> R) -> AuditTokensQuery
+    < 'a, C, R, N >
+    {
+        AuditTokensQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct ExportAccounts
+{ pub id : i32,pub username : String,pub card_number : String,pub password_hash : String,pub is_existing : bool,pub deleted_at : Option<time::OffsetDateTime>,pub overdraft_limit : i64,pub status : String,pub status_reason : Option<String>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<String>,pub terminal_key : Option<uuid::Uuid>,}pub struct ExportAccountsBorrowed < 'a >
+{ pub id : i32,pub username : &'a str,pub card_number : &'a str,pub password_hash : &'a str,pub is_existing : bool,pub deleted_at : Option<time::OffsetDateTime>,pub overdraft_limit : i64,pub status : &'a str,pub status_reason : Option<&'a str>,pub card_expiry_month : Option<i16>,pub card_expiry_year : Option<i16>,pub cvv_hash : Option<&'a str>,pub terminal_key : Option<uuid::Uuid>,} impl < 'a > From < ExportAccountsBorrowed <
+'a >> for ExportAccounts
+{
+    fn
+    from(ExportAccountsBorrowed { id,username,card_number,password_hash,is_existing,deleted_at,overdraft_limit,status,status_reason,card_expiry_month,card_expiry_year,cvv_hash,terminal_key,} : ExportAccountsBorrowed < 'a >)
+    -> Self { Self { id,username: username.into(),card_number: card_number.into(),password_hash: password_hash.into(),is_existing,deleted_at,overdraft_limit,status: status.into(),status_reason: status_reason.map(|v| v.into()),card_expiry_month,card_expiry_year,cvv_hash: cvv_hash.map(|v| v.into()),terminal_key,} }
+}pub struct ExportAccountsQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> ExportAccountsBorrowed,
+    mapper : fn(ExportAccountsBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > ExportAccountsQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(ExportAccountsBorrowed) -> R) -> ExportAccountsQuery
+    < 'a, C, R, N >
+    {
+        ExportAccountsQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}#[derive(serde::Serialize, Debug, Clone, PartialEq, )] pub struct ExportTokens
+{ pub token : String,pub card_number : String,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,}pub struct ExportTokensBorrowed < 'a >
+{ pub token : &'a str,pub card_number : &'a str,pub created_at : time::OffsetDateTime,pub expires_at : Option<time::OffsetDateTime>,pub revoked_at : Option<time::OffsetDateTime>,pub terminal_key : Option<uuid::Uuid>,} impl < 'a > From < ExportTokensBorrowed <
+'a >> for ExportTokens
+{
+    fn
+    from(ExportTokensBorrowed { token,card_number,created_at,expires_at,revoked_at,terminal_key,} : ExportTokensBorrowed < 'a >)
+    -> Self { Self { token: token.into(),card_number: card_number.into(),created_at,expires_at,revoked_at,terminal_key,} }
+}pub struct ExportTokensQuery < 'a, C : GenericClient, T, const N : usize >
+{
+    client : & 'a  C, params :
+    [& 'a (dyn postgres_types :: ToSql + Sync) ; N], stmt : & 'a mut cornucopia_async
+    :: private :: Stmt, extractor : fn(& tokio_postgres :: Row) -> ExportTokensBorrowed,
+    mapper : fn(ExportTokensBorrowed) -> T,
+} impl < 'a, C, T : 'a, const N : usize > ExportTokensQuery < 'a, C, T, N >
+where C : GenericClient
+{
+    pub fn map < R > (self, mapper : fn(ExportTokensBorrowed) -> R) -> ExportTokensQuery
+    < 'a, C, R, N >
+    {
+        ExportTokensQuery
+        {
+            client : self.client, params : self.params, stmt : self.stmt,
+            extractor : self.extractor, mapper,
+        }
+    } pub async fn one(self) -> Result < T, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let row =
+        self.client.query_one(stmt, & self.params) .await ? ;
+        Ok((self.mapper) ((self.extractor) (& row)))
+    } pub async fn all(self) -> Result < Vec < T >, tokio_postgres :: Error >
+    { self.iter() .await ?.try_collect().await } pub async fn opt(self) -> Result
+    < Option < T >, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ;
+        Ok(self.client.query_opt(stmt, & self.params) .await
+        ?.map(| row | (self.mapper) ((self.extractor) (& row))))
+    } pub async fn iter(self,) -> Result < impl futures::Stream < Item = Result
+    < T, tokio_postgres :: Error >> + 'a, tokio_postgres :: Error >
+    {
+        let stmt = self.stmt.prepare(self.client) .await ? ; let it =
+        self.client.query_raw(stmt, cornucopia_async :: private ::
+        slice_iter(& self.params)) .await ?
+        .map(move | res |
+        res.map(| row | (self.mapper) ((self.extractor) (& row)))) .into_stream() ;
+        Ok(it)
+    }
+}pub fn accounts_count() -> AccountsCountStmt
+{ AccountsCountStmt(cornucopia_async :: private :: Stmt :: new("SELECT COUNT(*)
+FROM accounts")) } pub
+struct AccountsCountStmt(cornucopia_async :: private :: Stmt) ; impl
+AccountsCountStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> I64Query < 'a, C,
+i64, 0 >
+{
+    I64Query
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn get_emission_account() -> GetEmissionAccountStmt
+{ GetEmissionAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT *
+FROM accounts
+WHERE accounts.id = 1")) } pub
+struct GetEmissionAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetEmissionAccountStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> GetEmissionAccountQuery < 'a, C,
+GetEmissionAccount, 0 >
+{
+    GetEmissionAccountQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { GetEmissionAccountBorrowed { id : row.get(0),created_at : row.get(1),username : row.get(2),card_number : row.get(3),password_hash : row.get(4),is_existing : row.get(5),balance : row.get(6),overdraft_limit : row.get(7),status : row.get(8),status_reason : row.get(9),status_changed_at : row.get(10),card_expiry_month : row.get(11),card_expiry_year : row.get(12),cvv_hash : row.get(13),deleted_at : row.get(14),} }, mapper : | it | { <GetEmissionAccount>::from(it) },
+    }
+} }pub fn get_legacy_store_account() -> GetLegacyStoreAccountStmt
+{ GetLegacyStoreAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT *
+FROM accounts
+WHERE accounts.id = 2
+    AND NOT EXISTS (SELECT 1 FROM terminals WHERE terminals.account = 2)")) } pub
+struct GetLegacyStoreAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetLegacyStoreAccountStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> GetLegacyStoreAccountQuery < 'a, C,
+GetLegacyStoreAccount, 0 >
+{
+    GetLegacyStoreAccountQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { GetLegacyStoreAccountBorrowed { id : row.get(0),created_at : row.get(1),username : row.get(2),card_number : row.get(3),password_hash : row.get(4),is_existing : row.get(5),balance : row.get(6),overdraft_limit : row.get(7),status : row.get(8),status_reason : row.get(9),status_changed_at : row.get(10),card_expiry_month : row.get(11),card_expiry_year : row.get(12),cvv_hash : row.get(13),deleted_at : row.get(14),} }, mapper : | it | { <GetLegacyStoreAccount>::from(it) },
+    }
+} }pub fn get_terminal_store_account() -> GetTerminalStoreAccountStmt
+{ GetTerminalStoreAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    a.username,
+    a.card_number,
+    a.is_existing
+FROM terminals
+JOIN accounts a ON terminals.account = a.id
+WHERE terminals.terminal_key = $1")) } pub
+struct GetTerminalStoreAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetTerminalStoreAccountStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+terminal_key : & 'a uuid::Uuid,) -> GetTerminalStoreAccountQuery < 'a, C,
+GetTerminalStoreAccount, 1 >
+{
+    GetTerminalStoreAccountQuery
+    {
+        client, params : [terminal_key,], stmt : & mut self.0, extractor :
+        | row | { GetTerminalStoreAccountBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),} }, mapper : | it | { <GetTerminalStoreAccount>::from(it) },
+    }
+} }pub fn insert_terminal() -> InsertTerminalStmt
+{ InsertTerminalStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO terminals(terminal_key, account)
+VALUES (
+    $1,
+    (
+        SELECT id FROM accounts WHERE card_number = $2
+    )
+)")) } pub
+struct InsertTerminalStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertTerminalStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+terminal_key : & 'a uuid::Uuid,card_number : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [terminal_key,card_number,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertTerminalParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertTerminalStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertTerminalParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.terminal_key,& params.card_number,) ) }
+}pub fn is_store_account() -> IsStoreAccountStmt
+{ IsStoreAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT EXISTS (
+    SELECT 1
+    FROM terminals
+    JOIN accounts a ON terminals.account = a.id
+    WHERE a.card_number = $1
+)")) } pub
+struct IsStoreAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+IsStoreAccountStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> BoolQuery < 'a, C,
+bool, 1 >
+{
+    BoolQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn lock_accounts() -> LockAccountsStmt
+{ LockAccountsStmt(cornucopia_async :: private :: Stmt :: new("SELECT id
+FROM accounts
+WHERE card_number = ANY($1)
+ORDER BY id
+FOR UPDATE")) } pub
+struct LockAccountsStmt(cornucopia_async :: private :: Stmt) ; impl
+LockAccountsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::ArraySql<Item = T1>,>
+(& 'a mut self, client : & 'a  C,
+card_numbers : & 'a T2,) -> I32Query < 'a, C,
+i32, 1 >
+{
+    I32Query
+    {
+        client, params : [card_numbers,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn insert_account() -> InsertAccountStmt
+{ InsertAccountStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO accounts(username, card_number, password_hash)
+VALUES ($1, $2, $3)")) } pub
+struct InsertAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertAccountStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+username : & 'a T1,card_number : & 'a T2,password_hash : & 'a T3,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [username,card_number,password_hash,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertAccountParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertAccountStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertAccountParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.username,& params.card_number,& params.password_hash,) ) }
+}pub fn is_account_exists() -> IsAccountExistsStmt
 { IsAccountExistsStmt(cornucopia_async :: private :: Stmt :: new("SELECT is_existing FROM accounts
 WHERE card_number = $1")) } pub
-struct IsAccountExistsStmt(cornucopia_async :: private :: Stmt) ; impl
-IsAccountExistsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+struct IsAccountExistsStmt(cornucopia_async :: private :: Stmt) ; impl
+IsAccountExistsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> BoolQuery < 'a, C,
+bool, 1 >
+{
+    BoolQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn mark_account_as_deleted() -> MarkAccountAsDeletedStmt
+{ MarkAccountAsDeletedStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
+SET is_existing = FALSE,
+    deleted_at = CURRENT_TIMESTAMP
+WHERE card_number = $1")) } pub
+struct MarkAccountAsDeletedStmt(cornucopia_async :: private :: Stmt) ; impl
+MarkAccountAsDeletedStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [card_number,]) .await
+} }pub fn mark_account_as_restored() -> MarkAccountAsRestoredStmt
+{ MarkAccountAsRestoredStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
+SET is_existing = TRUE,
+    deleted_at = NULL
+WHERE card_number = $1")) } pub
+struct MarkAccountAsRestoredStmt(cornucopia_async :: private :: Stmt) ; impl
+MarkAccountAsRestoredStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [card_number,]) .await
+} }pub fn get_account_status() -> GetAccountStatusStmt
+{ GetAccountStatusStmt(cornucopia_async :: private :: Stmt :: new("SELECT status
+FROM accounts
+WHERE card_number = $1")) } pub
+struct GetAccountStatusStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountStatusStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> StringQuery < 'a, C,
+String, 1 >
+{
+    StringQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it.into() },
+    }
+} }pub fn set_account_status() -> SetAccountStatusStmt
+{ SetAccountStatusStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
+SET status = $1,
+    status_reason = $2,
+    status_changed_at = CURRENT_TIMESTAMP
+WHERE card_number = $3")) } pub
+struct SetAccountStatusStmt(cornucopia_async :: private :: Stmt) ; impl
+SetAccountStatusStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+status : & 'a T1,status_reason : & 'a Option<T2>,card_number : & 'a T3,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [status,status_reason,card_number,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, SetAccountStatusParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for SetAccountStatusStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    SetAccountStatusParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.status,& params.status_reason,& params.card_number,) ) }
+}pub fn get_account() -> GetAccountStmt
+{ GetAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    accounts.username,
+    accounts.card_number,
+    accounts.is_existing,
+    accounts.password_hash,
+    accounts.card_expiry_month,
+    accounts.card_expiry_year,
+    accounts.cvv_hash
+FROM accounts
+WHERE card_number = $1")) } pub
+struct GetAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> GetAccountQuery < 'a, C,
+GetAccount, 1 >
+{
+    GetAccountQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { GetAccountBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),password_hash : row.get(3),card_expiry_month : row.get(4),card_expiry_year : row.get(5),cvv_hash : row.get(6),} }, mapper : | it | { <GetAccount>::from(it) },
+    }
+} }pub fn set_card_details() -> SetCardDetailsStmt
+{ SetCardDetailsStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
+SET card_expiry_month = $1,
+    card_expiry_year = $2,
+    cvv_hash = $3
+WHERE card_number = $4")) } pub
+struct SetCardDetailsStmt(cornucopia_async :: private :: Stmt) ; impl
+SetCardDetailsStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_expiry_month : & 'a i16,card_expiry_year : & 'a i16,cvv_hash : & 'a T1,card_number : & 'a T2,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [card_expiry_month,card_expiry_year,cvv_hash,card_number,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, SetCardDetailsParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for SetCardDetailsStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    SetCardDetailsParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.card_expiry_month,& params.card_expiry_year,& params.cvv_hash,& params.card_number,) ) }
+}pub fn get_account_by_token() -> GetAccountByTokenStmt
+{ GetAccountByTokenStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    a.username,
+    a.card_number,
+    a.is_existing
+FROM tokens
+LEFT JOIN accounts a ON tokens.account = a.id
+WHERE  tokens.token = $1")) } pub
+struct GetAccountByTokenStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountByTokenStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+token : & 'a T1,) -> GetAccountByTokenQuery < 'a, C,
+GetAccountByToken, 1 >
+{
+    GetAccountByTokenQuery
+    {
+        client, params : [token,], stmt : & mut self.0, extractor :
+        | row | { GetAccountByTokenBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),} }, mapper : | it | { <GetAccountByToken>::from(it) },
+    }
+} }pub fn get_token() -> GetTokenStmt
+{ GetTokenStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+WHERE tokens.token = $1")) } pub
+struct GetTokenStmt(cornucopia_async :: private :: Stmt) ; impl
+GetTokenStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+token : & 'a T1,) -> GetTokenQuery < 'a, C,
+GetToken, 1 >
+{
+    GetTokenQuery
+    {
+        client, params : [token,], stmt : & mut self.0, extractor :
+        | row | { GetTokenBorrowed { token : row.get(0),card_number : row.get(1),created_at : row.get(2),expires_at : row.get(3),revoked_at : row.get(4),terminal_key : row.get(5),} }, mapper : | it | { <GetToken>::from(it) },
+    }
+} }pub fn list_account_tokens() -> ListAccountTokensStmt
+{ ListAccountTokensStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+WHERE a.card_number = $1
+ORDER BY tokens.created_at, tokens.id")) } pub
+struct ListAccountTokensStmt(cornucopia_async :: private :: Stmt) ; impl
+ListAccountTokensStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> ListAccountTokensQuery < 'a, C,
+ListAccountTokens, 1 >
+{
+    ListAccountTokensQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { ListAccountTokensBorrowed { token : row.get(0),card_number : row.get(1),created_at : row.get(2),expires_at : row.get(3),revoked_at : row.get(4),terminal_key : row.get(5),} }, mapper : | it | { <ListAccountTokens>::from(it) },
+    }
+} }pub fn revoke_token() -> RevokeTokenStmt
+{ RevokeTokenStmt(cornucopia_async :: private :: Stmt :: new("UPDATE tokens
+SET revoked_at = CURRENT_TIMESTAMP
+WHERE token = $1 AND revoked_at IS NULL")) } pub
+struct RevokeTokenStmt(cornucopia_async :: private :: Stmt) ; impl
+RevokeTokenStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+token : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [token,]) .await
+} }pub fn get_account_balance() -> GetAccountBalanceStmt
+{ GetAccountBalanceStmt(cornucopia_async :: private :: Stmt :: new("SELECT balance
+FROM accounts
+WHERE card_number = $1")) } pub
+struct GetAccountBalanceStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountBalanceStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> I64Query < 'a, C,
+i64, 1 >
+{
+    I64Query
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn get_account_overdraft_limit() -> GetAccountOverdraftLimitStmt
+{ GetAccountOverdraftLimitStmt(cornucopia_async :: private :: Stmt :: new("SELECT overdraft_limit
+FROM accounts
+WHERE card_number = $1")) } pub
+struct GetAccountOverdraftLimitStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountOverdraftLimitStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> I64Query < 'a, C,
+i64, 1 >
+{
+    I64Query
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn set_account_overdraft_limit() -> SetAccountOverdraftLimitStmt
+{ SetAccountOverdraftLimitStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
+SET overdraft_limit = $1
+WHERE card_number = $2")) } pub
+struct SetAccountOverdraftLimitStmt(cornucopia_async :: private :: Stmt) ; impl
+SetAccountOverdraftLimitStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+overdraft_limit : & 'a i64,card_number : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [overdraft_limit,card_number,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, SetAccountOverdraftLimitParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for SetAccountOverdraftLimitStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    SetAccountOverdraftLimitParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.overdraft_limit,& params.card_number,) ) }
+}pub fn get_account_held_amount() -> GetAccountHeldAmountStmt
+{ GetAccountHeldAmountStmt(cornucopia_async :: private :: Stmt :: new("SELECT COALESCE(SUM(holds.amount), 0) AS held
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE a.card_number = $1 AND holds.expires_at > CURRENT_TIMESTAMP")) } pub
+struct GetAccountHeldAmountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountHeldAmountStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> RustdecimalDecimalQuery < 'a, C,
+rust_decimal::Decimal, 1 >
+{
+    RustdecimalDecimalQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn list_account_transactions() -> ListAccountTransactionsStmt
+{ ListAccountTransactionsStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+LEFT JOIN accounts sender_account ON t.sender = sender_account.id
+LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE sender_account.card_number = $1 OR recipient_account.card_number = $1")) } pub
+struct ListAccountTransactionsStmt(cornucopia_async :: private :: Stmt) ; impl
+ListAccountTransactionsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> ListAccountTransactionsQuery < 'a, C,
+ListAccountTransactions, 1 >
+{
+    ListAccountTransactionsQuery
+    {
+        client, params : [card_number,], stmt : & mut self.0, extractor :
+        | row | { ListAccountTransactionsBorrowed { transaction_id : row.get(0),amount : row.get(1),kind : row.get(2),created_at : row.get(3),sender_username : row.get(4),sender_card_number : row.get(5),sender_is_existing : row.get(6),recipient_username : row.get(7),recipient_card_number : row.get(8),recipient_is_existing : row.get(9),} }, mapper : | it | { <ListAccountTransactions>::from(it) },
+    }
+} }pub fn get_transaction() -> GetTransactionStmt
+{ GetTransactionStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+LEFT JOIN accounts sender_account ON t.sender = sender_account.id
+LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE t.transaction_id = $1")) } pub
+struct GetTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
+GetTransactionStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+transaction_id : & 'a uuid::Uuid,) -> GetTransactionQuery < 'a, C,
+GetTransaction, 1 >
+{
+    GetTransactionQuery
+    {
+        client, params : [transaction_id,], stmt : & mut self.0, extractor :
+        | row | { GetTransactionBorrowed { transaction_id : row.get(0),amount : row.get(1),kind : row.get(2),created_at : row.get(3),sender_username : row.get(4),sender_card_number : row.get(5),sender_is_existing : row.get(6),recipient_username : row.get(7),recipient_card_number : row.get(8),recipient_is_existing : row.get(9),} }, mapper : | it | { <GetTransaction>::from(it) },
+    }
+} }pub fn list_transactions() -> ListTransactionsStmt
+{ ListTransactionsStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+LEFT JOIN accounts sender_account ON t.sender = sender_account.id
+LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE t.id > COALESCE(
+    (SELECT id FROM transactions WHERE transaction_id = $1),
+    0
+)
+ORDER BY t.id")) } pub
+struct ListTransactionsStmt(cornucopia_async :: private :: Stmt) ; impl
+ListTransactionsStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+last_id : & 'a Option<uuid::Uuid>,) -> ListTransactionsQuery < 'a, C,
+ListTransactions, 1 >
+{
+    ListTransactionsQuery
+    {
+        client, params : [last_id,], stmt : & mut self.0, extractor :
+        | row | { ListTransactionsBorrowed { transaction_id : row.get(0),amount : row.get(1),kind : row.get(2),created_at : row.get(3),sender_username : row.get(4),sender_card_number : row.get(5),sender_is_existing : row.get(6),recipient_username : row.get(7),recipient_card_number : row.get(8),recipient_is_existing : row.get(9),} }, mapper : | it | { <ListTransactions>::from(it) },
+    }
+} }pub fn account_statement() -> AccountStatementStmt
+{ AccountStatementStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
+    t.transaction_id,
+    t.amount,
+    t.kind,
+    t.created_at,
+    sender_account.username AS sender_username,
+    sender_account.card_number AS sender_card_number,
+    sender_account.is_existing AS sender_is_existing,
+    recipient_account.username AS recipient_username,
+    recipient_account.card_number AS recipient_card_number,
+    recipient_account.is_existing AS recipient_is_existing
+FROM transactions t
+JOIN accounts sender_account ON t.sender = sender_account.id
+JOIN accounts recipient_account ON t.recipient = recipient_account.id
+WHERE (sender_account.card_number = $1 OR recipient_account.card_number = $1)
+    AND (
+        $2::UUID IS NULL
+        OR (t.created_at, t.id) > (
+            SELECT created_at, id FROM transactions WHERE transaction_id = $2
+        )
+    )
+    AND ($3::TIMESTAMPTZ IS NULL OR t.created_at >= $3)
+    AND ($4::TIMESTAMPTZ IS NULL OR t.created_at < $4)
+    AND (
+        $5::TEXT IS NULL
+        OR ($5 = 'incoming' AND recipient_account.card_number = $1)
+        OR ($5 = 'outgoing' AND sender_account.card_number = $1)
+    )
+    AND (
+        $6::TEXT IS NULL
+        OR (sender_account.card_number = $1 AND recipient_account.card_number = $6)
+        OR (recipient_account.card_number = $1 AND sender_account.card_number = $6)
+    )
+    AND ($7::BIGINT IS NULL OR t.amount >= $7)
+    AND ($8::BIGINT IS NULL OR t.amount <= $8)
+ORDER BY t.created_at, t.id
+LIMIT $9")) } pub
+struct AccountStatementStmt(cornucopia_async :: private :: Stmt) ; impl
+AccountStatementStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,cursor : & 'a Option<uuid::Uuid>,date_from : & 'a Option<time::OffsetDateTime>,date_to : & 'a Option<time::OffsetDateTime>,direction : & 'a Option<T2>,counterparty : & 'a Option<T3>,min_amount : & 'a Option<i64>,max_amount : & 'a Option<i64>,limit : & 'a i64,) -> AccountStatementQuery < 'a, C,
+AccountStatement, 9 >
+{
+    AccountStatementQuery
+    {
+        client, params : [card_number,cursor,date_from,date_to,direction,counterparty,min_amount,max_amount,limit,], stmt : & mut self.0, extractor :
+        | row | { AccountStatementBorrowed { transaction_id : row.get(0),amount : row.get(1),kind : row.get(2),created_at : row.get(3),sender_username : row.get(4),sender_card_number : row.get(5),sender_is_existing : row.get(6),recipient_username : row.get(7),recipient_card_number : row.get(8),recipient_is_existing : row.get(9),} }, mapper : | it | { <AccountStatement>::from(it) },
+    }
+} }impl < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,> cornucopia_async ::
+Params < 'a, AccountStatementParams < T1,T2,T3,>, AccountStatementQuery < 'a, C,
+AccountStatement, 9 >, C > for AccountStatementStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    AccountStatementParams < T1,T2,T3,>) -> AccountStatementQuery < 'a, C,
+    AccountStatement, 9 >
+    { self.bind(client, & params.card_number,& params.cursor,& params.date_from,& params.date_to,& params.direction,& params.counterparty,& params.min_amount,& params.max_amount,& params.limit,) }
+}pub fn is_transaction_exists() -> IsTransactionExistsStmt
+{ IsTransactionExistsStmt(cornucopia_async :: private :: Stmt :: new("SELECT EXISTS (
+    SELECT 1 FROM transactions WHERE transaction_id = $1
+)")) } pub
+struct IsTransactionExistsStmt(cornucopia_async :: private :: Stmt) ; impl
+IsTransactionExistsStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+transaction_id : & 'a uuid::Uuid,) -> BoolQuery < 'a, C,
+bool, 1 >
+{
+    BoolQuery
+    {
+        client, params : [transaction_id,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn get_accounts() -> GetAccountsStmt
+{ GetAccountsStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    a.username,
+    a.card_number,
+    a.is_existing,
+    a.balance,
+    a.overdraft_limit,
+    a.status,
+    a.status_reason,
+    ARRAY_AGG(t.token) AS tokens
+FROM accounts a
+LEFT JOIN tokens t ON a.id = t.account
+    AND t.revoked_at IS NULL
+    AND (t.expires_at IS NULL OR t.expires_at > CURRENT_TIMESTAMP)
+WHERE a.id NOT IN (SELECT account FROM terminals)
+    AND a.id NOT IN (SELECT account FROM system_accounts)
+GROUP BY a.id")) } pub
+struct GetAccountsStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountsStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> GetAccountsQuery < 'a, C,
+GetAccounts, 0 >
+{
+    GetAccountsQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { GetAccountsBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),balance : row.get(3),overdraft_limit : row.get(4),status : row.get(5),status_reason : row.get(6),tokens : row.get(7),} }, mapper : | it | { <GetAccounts>::from(it) },
+    }
+} }pub fn create_transaction() -> CreateTransactionStmt
+{ CreateTransactionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transactions(transaction_id, sender, recipient, amount)
+VALUES (
+    $1,
+    (
+        SELECT id FROM accounts WHERE card_number = $2 
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = $3
+    ),
+     $4
+)")) } pub
+struct CreateTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
+CreateTransactionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+transaction_id : & 'a uuid::Uuid,sender_card : & 'a T1,recipient_card : & 'a T2,amount : & 'a i64,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [transaction_id,sender_card,recipient_card,amount,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, CreateTransactionParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for CreateTransactionStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    CreateTransactionParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.transaction_id,& params.sender_card,& params.recipient_card,& params.amount,) ) }
+}pub fn create_fee_transaction() -> CreateFeeTransactionStmt
+{ CreateFeeTransactionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transactions(transaction_id, sender, recipient, amount, kind)
+VALUES (
+    $1,
+    (
+        SELECT id FROM accounts WHERE card_number = $2
+    ),
+    (
+        SELECT account FROM system_accounts WHERE role = 'revenue'
+    ),
+    $3,
+    'acquiring_fee'
+)")) } pub
+struct CreateFeeTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
+CreateFeeTransactionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+transaction_id : & 'a uuid::Uuid,sender_card : & 'a T1,amount : & 'a i64,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [transaction_id,sender_card,amount,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, CreateFeeTransactionParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for CreateFeeTransactionStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    CreateFeeTransactionParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.transaction_id,& params.sender_card,& params.amount,) ) }
+}pub fn create_fee_refund_transaction() -> CreateFeeRefundTransactionStmt
+{ CreateFeeRefundTransactionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transactions(transaction_id, sender, recipient, amount, kind)
+VALUES (
+    $1,
+    (
+        SELECT account FROM system_accounts WHERE role = 'revenue'
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = $2
+    ),
+    $3,
+    'fee_refund'
+)")) } pub
+struct CreateFeeRefundTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
+CreateFeeRefundTransactionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+transaction_id : & 'a uuid::Uuid,recipient_card : & 'a T1,amount : & 'a i64,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [transaction_id,recipient_card,amount,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, CreateFeeRefundTransactionParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for CreateFeeRefundTransactionStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    CreateFeeRefundTransactionParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.transaction_id,& params.recipient_card,& params.amount,) ) }
+}pub fn get_account_by_username() -> GetAccountByUsernameStmt
+{ GetAccountByUsernameStmt(cornucopia_async :: private :: Stmt :: new("SELECT card_number
+FROM accounts
+WHERE username = $1")) } pub
+struct GetAccountByUsernameStmt(cornucopia_async :: private :: Stmt) ; impl
+GetAccountByUsernameStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+username : & 'a T1,) -> StringQuery < 'a, C,
+String, 1 >
+{
+    StringQuery
+    {
+        client, params : [username,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it.into() },
+    }
+} }pub fn get_revenue_account() -> GetRevenueAccountStmt
+{ GetRevenueAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT a.card_number
+FROM system_accounts
+JOIN accounts a ON system_accounts.account = a.id
+WHERE system_accounts.role = 'revenue'")) } pub
+struct GetRevenueAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+GetRevenueAccountStmt { pub fn bind < 'a, C : GenericClient, >
 (& 'a mut self, client : & 'a  C,
-card_number : & 'a T1,) -> BoolQuery < 'a, C,
-bool, 1 >
+) -> StringQuery < 'a, C,
+String, 0 >
 {
-    BoolQuery
+    StringQuery
     {
-        client, params : [card_number,], stmt : & mut self.0, extractor :
-        | row | { row.get(0) }, mapper : | it | { it },
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it.into() },
     }
-} }pub fn mark_account_as_deleted() -> MarkAccountAsDeletedStmt
-{ MarkAccountAsDeletedStmt(cornucopia_async :: private :: Stmt :: new("UPDATE accounts
-SET is_existing = FALSE
-WHERE card_number = $1")) } pub
-struct MarkAccountAsDeletedStmt(cornucopia_async :: private :: Stmt) ; impl
-MarkAccountAsDeletedStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+} }pub fn mark_revenue_account() -> MarkRevenueAccountStmt
+{ MarkRevenueAccountStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO system_accounts(role, account)
+VALUES (
+    'revenue',
+    (
+        SELECT id FROM accounts WHERE card_number = $1
+    )
+)")) } pub
+struct MarkRevenueAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+MarkRevenueAccountStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
 (& 'a mut self, client : & 'a  C,
 card_number : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
 {
     let stmt = self.0.prepare(client) .await ? ;
     client.execute(stmt, & [card_number,]) .await
-} }pub fn get_account() -> GetAccountStmt
-{ GetAccountStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
-    accounts.username,
-    accounts.card_number,
-    accounts.is_existing,
-    accounts.password_hash
-FROM accounts
-WHERE card_number = $1")) } pub
-struct GetAccountStmt(cornucopia_async :: private :: Stmt) ; impl
-GetAccountStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+} }pub fn insert_token() -> InsertTokenStmt
+{ InsertTokenStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO tokens(account, token, expires_at, terminal_key)
+VALUES (
+    (
+        SELECT id FROM accounts WHERE card_number = $1
+    ),
+    $2,
+    $3,
+    $4
+)")) } pub
+struct InsertTokenStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertTokenStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
 (& 'a mut self, client : & 'a  C,
-card_number : & 'a T1,) -> GetAccountQuery < 'a, C,
-GetAccount, 1 >
+card_number : & 'a T1,token : & 'a T2,expires_at : & 'a Option<time::OffsetDateTime>,terminal_key : & 'a Option<uuid::Uuid>,) -> Result < u64, tokio_postgres :: Error >
 {
-    GetAccountQuery
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [card_number,token,expires_at,terminal_key,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertTokenParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertTokenStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertTokenParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.card_number,& params.token,& params.expires_at,& params.terminal_key,) ) }
+}pub fn insert_hold() -> InsertHoldStmt
+{ InsertHoldStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO holds(id, account, amount, expires_at)
+VALUES (
+    $1,
+    (
+        SELECT id FROM accounts WHERE card_number = $2
+    ),
+    $3,
+    $4
+)")) } pub
+struct InsertHoldStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertHoldStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,card_number : & 'a T1,amount : & 'a i64,expires_at : & 'a time::OffsetDateTime,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [id,card_number,amount,expires_at,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertHoldParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertHoldStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertHoldParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.id,& params.card_number,& params.amount,& params.expires_at,) ) }
+}pub fn get_hold() -> GetHoldStmt
+{ GetHoldStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    holds.amount,
+    a.card_number
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE holds.id = $1 AND holds.expires_at > CURRENT_TIMESTAMP")) } pub
+struct GetHoldStmt(cornucopia_async :: private :: Stmt) ; impl
+GetHoldStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,) -> GetHoldQuery < 'a, C,
+GetHold, 1 >
+{
+    GetHoldQuery
+    {
+        client, params : [id,], stmt : & mut self.0, extractor :
+        | row | { GetHoldBorrowed { amount : row.get(0),card_number : row.get(1),} }, mapper : | it | { <GetHold>::from(it) },
+    }
+} }pub fn delete_hold() -> DeleteHoldStmt
+{ DeleteHoldStmt(cornucopia_async :: private :: Stmt :: new("DELETE FROM holds
+WHERE id = $1")) } pub
+struct DeleteHoldStmt(cornucopia_async :: private :: Stmt) ; impl
+DeleteHoldStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [id,]) .await
+} }pub fn delete_all_holds() -> DeleteAllHoldsStmt
+{ DeleteAllHoldsStmt(cornucopia_async :: private :: Stmt :: new("DELETE FROM holds")) } pub
+struct DeleteAllHoldsStmt(cornucopia_async :: private :: Stmt) ; impl
+DeleteAllHoldsStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & []) .await
+} }pub fn list_account_holds() -> ListAccountHoldsStmt
+{ ListAccountHoldsStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    holds.id,
+    holds.amount,
+    holds.created_at,
+    holds.expires_at
+FROM holds
+JOIN accounts a ON holds.account = a.id
+WHERE a.card_number = $1 AND holds.expires_at > CURRENT_TIMESTAMP
+ORDER BY holds.created_at")) } pub
+struct ListAccountHoldsStmt(cornucopia_async :: private :: Stmt) ; impl
+ListAccountHoldsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+card_number : & 'a T1,) -> ListAccountHoldsQuery < 'a, C,
+ListAccountHolds, 1 >
+{
+    ListAccountHoldsQuery
     {
         client, params : [card_number,], stmt : & mut self.0, extractor :
-        | row | { GetAccountBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),password_hash : row.get(3),} }, mapper : | it | { <GetAccount>::from(it) },
+        | row | { ListAccountHolds { id : row.get(0),amount : row.get(1),created_at : row.get(2),expires_at : row.get(3),} }, mapper : | it | { <ListAccountHolds>::from(it) },
     }
-} }pub fn get_account_by_token() -> GetAccountByTokenStmt
-{ GetAccountByTokenStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
-    a.username,
+} }pub fn insert_idempotency_key() -> InsertIdempotencyKeyStmt
+{ InsertIdempotencyKeyStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO idempotency_keys(idempotency_key, request_hash)
+VALUES ($1, $2)
+ON CONFLICT DO NOTHING")) } pub
+struct InsertIdempotencyKeyStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertIdempotencyKeyStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+idempotency_key : & 'a T1,request_hash : & 'a T2,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [idempotency_key,request_hash,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertIdempotencyKeyParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertIdempotencyKeyStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertIdempotencyKeyParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.idempotency_key,& params.request_hash,) ) }
+}pub fn get_idempotency_key() -> GetIdempotencyKeyStmt
+{ GetIdempotencyKeyStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    request_hash,
+    response_status,
+    response_content_type,
+    response_body
+FROM idempotency_keys
+WHERE idempotency_key = $1")) } pub
+struct GetIdempotencyKeyStmt(cornucopia_async :: private :: Stmt) ; impl
+GetIdempotencyKeyStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+idempotency_key : & 'a T1,) -> GetIdempotencyKeyQuery < 'a, C,
+GetIdempotencyKey, 1 >
+{
+    GetIdempotencyKeyQuery
+    {
+        client, params : [idempotency_key,], stmt : & mut self.0, extractor :
+        | row | { GetIdempotencyKeyBorrowed { request_hash : row.get(0),response_status : row.get(1),response_content_type : row.get(2),response_body : row.get(3),} }, mapper : | it | { <GetIdempotencyKey>::from(it) },
+    }
+} }pub fn save_idempotent_response() -> SaveIdempotentResponseStmt
+{ SaveIdempotentResponseStmt(cornucopia_async :: private :: Stmt :: new("UPDATE idempotency_keys
+SET
+    response_status = $1,
+    response_content_type = $2,
+    response_body = $3
+WHERE idempotency_key = $4")) } pub
+struct SaveIdempotentResponseStmt(cornucopia_async :: private :: Stmt) ; impl
+SaveIdempotentResponseStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::BytesSql,T3 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+response_status : & 'a i16,response_content_type : & 'a Option<T1>,response_body : & 'a T2,idempotency_key : & 'a T3,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [response_status,response_content_type,response_body,idempotency_key,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::BytesSql,T3 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, SaveIdempotentResponseParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for SaveIdempotentResponseStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    SaveIdempotentResponseParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.response_status,& params.response_content_type,& params.response_body,& params.idempotency_key,) ) }
+}pub fn delete_idempotency_key() -> DeleteIdempotencyKeyStmt
+{ DeleteIdempotencyKeyStmt(cornucopia_async :: private :: Stmt :: new("DELETE FROM idempotency_keys
+WHERE idempotency_key = $1")) } pub
+struct DeleteIdempotencyKeyStmt(cornucopia_async :: private :: Stmt) ; impl
+DeleteIdempotencyKeyStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+idempotency_key : & 'a T1,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [idempotency_key,]) .await
+} }pub fn insert_loan() -> InsertLoanStmt
+{ InsertLoanStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO loans(id, created_at, account, principal, interest_rate, term, period_secs)
+VALUES (
+    $1,
+    $2,
+    (
+        SELECT id FROM accounts WHERE card_number = $3
+    ),
+    $4,
+    $5,
+    $6,
+    $7
+)")) } pub
+struct InsertLoanStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertLoanStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,created_at : & 'a time::OffsetDateTime,card_number : & 'a T1,principal : & 'a i64,interest_rate : & 'a rust_decimal::Decimal,term : & 'a i32,period_secs : & 'a i64,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [id,created_at,card_number,principal,interest_rate,term,period_secs,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertLoanParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertLoanStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertLoanParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.id,& params.created_at,& params.card_number,& params.principal,& params.interest_rate,& params.term,& params.period_secs,) ) }
+}pub fn get_loan() -> GetLoanStmt
+{ GetLoanStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    loans.id,
+    loans.created_at,
     a.card_number,
-    a.is_existing
-FROM tokens
-LEFT JOIN accounts a ON tokens.account = a.id
-WHERE  tokens.token = $1")) } pub
-struct GetAccountByTokenStmt(cornucopia_async :: private :: Stmt) ; impl
-GetAccountByTokenStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+    loans.principal,
+    loans.interest_rate,
+    loans.term,
+    loans.period_secs,
+    loans.repaid
+FROM loans
+JOIN accounts a ON loans.account = a.id
+WHERE loans.id = $1")) } pub
+struct GetLoanStmt(cornucopia_async :: private :: Stmt) ; impl
+GetLoanStmt { pub fn bind < 'a, C : GenericClient, >
 (& 'a mut self, client : & 'a  C,
-token : & 'a T1,) -> GetAccountByTokenQuery < 'a, C,
-GetAccountByToken, 1 >
+id : & 'a uuid::Uuid,) -> GetLoanQuery < 'a, C,
+GetLoan, 1 >
 {
-    GetAccountByTokenQuery
+    GetLoanQuery
     {
-        client, params : [token,], stmt : & mut self.0, extractor :
-        | row | { GetAccountByTokenBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),} }, mapper : | it | { <GetAccountByToken>::from(it) },
+        client, params : [id,], stmt : & mut self.0, extractor :
+        | row | { GetLoanBorrowed { id : row.get(0),created_at : row.get(1),card_number : row.get(2),principal : row.get(3),interest_rate : row.get(4),term : row.get(5),period_secs : row.get(6),repaid : row.get(7),} }, mapper : | it | { <GetLoan>::from(it) },
     }
-} }pub fn get_account_balance() -> GetAccountBalanceStmt
-{ GetAccountBalanceStmt(cornucopia_async :: private :: Stmt :: new("WITH received_amount AS (
-    SELECT recipient, COALESCE(SUM(amount), 0) AS received_total
-    FROM transactions
-    GROUP BY recipient
-),
-spent_amount AS (
-    SELECT sender, COALESCE(SUM(amount), 0) AS spent_total
-    FROM transactions
-    GROUP BY sender
-)
-SELECT COALESCE(ra.received_total, 0) - COALESCE(sa.spent_total, 0) AS balance
-FROM accounts a
-LEFT JOIN received_amount ra ON a.id = ra.recipient
-LEFT JOIN spent_amount sa ON a.id = sa.sender
-WHERE a.card_number = $1")) } pub
-struct GetAccountBalanceStmt(cornucopia_async :: private :: Stmt) ; impl
-GetAccountBalanceStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+} }pub fn lock_loan() -> LockLoanStmt
+{ LockLoanStmt(cornucopia_async :: private :: Stmt :: new("SELECT id
+FROM loans
+WHERE id = $1
+FOR UPDATE")) } pub
+struct LockLoanStmt(cornucopia_async :: private :: Stmt) ; impl
+LockLoanStmt { pub fn bind < 'a, C : GenericClient, >
 (& 'a mut self, client : & 'a  C,
-card_number : & 'a T1,) -> RustdecimalDecimalQuery < 'a, C,
-rust_decimal::Decimal, 1 >
+id : & 'a uuid::Uuid,) -> UuidUuidQuery < 'a, C,
+uuid::Uuid, 1 >
 {
-    RustdecimalDecimalQuery
+    UuidUuidQuery
     {
-        client, params : [card_number,], stmt : & mut self.0, extractor :
+        client, params : [id,], stmt : & mut self.0, extractor :
         | row | { row.get(0) }, mapper : | it | { it },
     }
-} }pub fn list_account_transactions() -> ListAccountTransactionsStmt
-{ ListAccountTransactionsStmt(cornucopia_async :: private :: Stmt :: new("SELECT 
-    t.amount,
-    t.created_at,
-    sender_account.username AS sender_username,
-    sender_account.card_number AS sender_card_number,
-    sender_account.is_existing AS sender_is_existing,
-    recipient_account.username AS recipient_username,
-    recipient_account.card_number AS recipient_card_number,
-    recipient_account.is_existing AS recipient_is_existing
-FROM transactions t
-LEFT JOIN accounts sender_account ON t.sender = sender_account.id
-LEFT JOIN accounts recipient_account ON t.recipient = recipient_account.id
-WHERE sender_account.card_number = $1 OR recipient_account.card_number = $1")) } pub
-struct ListAccountTransactionsStmt(cornucopia_async :: private :: Stmt) ; impl
-ListAccountTransactionsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+} }pub fn add_loan_repayment() -> AddLoanRepaymentStmt
+{ AddLoanRepaymentStmt(cornucopia_async :: private :: Stmt :: new("UPDATE loans
+SET repaid = repaid + $1
+WHERE id = $2")) } pub
+struct AddLoanRepaymentStmt(cornucopia_async :: private :: Stmt) ; impl
+AddLoanRepaymentStmt { pub async fn bind < 'a, C : GenericClient, >
 (& 'a mut self, client : & 'a  C,
-card_number : & 'a T1,) -> ListAccountTransactionsQuery < 'a, C,
-ListAccountTransactions, 1 >
+amount : & 'a i64,id : & 'a uuid::Uuid,) -> Result < u64, tokio_postgres :: Error >
 {
-    ListAccountTransactionsQuery
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [amount,id,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, >
+cornucopia_async :: Params < 'a, AddLoanRepaymentParams, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for AddLoanRepaymentStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    AddLoanRepaymentParams) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.amount,& params.id,) ) }
+}pub fn insert_transfer_order() -> InsertTransferOrderStmt
+{ InsertTransferOrderStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transfer_orders(id, created_at, sender, recipient, amount, run_at, cron, next_run_at)
+VALUES (
+    $1,
+    $2,
+    (
+        SELECT id FROM accounts WHERE card_number = $3
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = $4
+    ),
+    $5,
+    $6,
+    $7,
+    $8
+)")) } pub
+struct InsertTransferOrderStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertTransferOrderStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,created_at : & 'a time::OffsetDateTime,sender : & 'a T1,recipient : & 'a T2,amount : & 'a i64,run_at : & 'a Option<time::OffsetDateTime>,cron : & 'a Option<T3>,next_run_at : & 'a Option<time::OffsetDateTime>,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [id,created_at,sender,recipient,amount,run_at,cron,next_run_at,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertTransferOrderParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertTransferOrderStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertTransferOrderParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.id,& params.created_at,& params.sender,& params.recipient,& params.amount,& params.run_at,& params.cron,& params.next_run_at,) ) }
+}pub fn get_transfer_orders() -> GetTransferOrdersStmt
+{ GetTransferOrdersStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    transfer_orders.id,
+    transfer_orders.created_at,
+    s.card_number AS sender,
+    r.card_number AS recipient,
+    transfer_orders.amount,
+    transfer_orders.run_at,
+    transfer_orders.cron,
+    transfer_orders.next_run_at,
+    transfer_orders.cancelled_at
+FROM transfer_orders
+JOIN accounts s ON transfer_orders.sender = s.id
+JOIN accounts r ON transfer_orders.recipient = r.id
+WHERE $1::TIMESTAMP WITH TIME ZONE IS NULL
+    OR (transfer_orders.cancelled_at IS NULL AND transfer_orders.next_run_at <= $1)
+ORDER BY transfer_orders.created_at")) } pub
+struct GetTransferOrdersStmt(cornucopia_async :: private :: Stmt) ; impl
+GetTransferOrdersStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+due_at : & 'a Option<time::OffsetDateTime>,) -> GetTransferOrdersQuery < 'a, C,
+GetTransferOrders, 1 >
+{
+    GetTransferOrdersQuery
     {
-        client, params : [card_number,], stmt : & mut self.0, extractor :
-        | row | { ListAccountTransactionsBorrowed { amount : row.get(0),created_at : row.get(1),sender_username : row.get(2),sender_card_number : row.get(3),sender_is_existing : row.get(4),recipient_username : row.get(5),recipient_card_number : row.get(6),recipient_is_existing : row.get(7),} }, mapper : | it | { <ListAccountTransactions>::from(it) },
+        client, params : [due_at,], stmt : & mut self.0, extractor :
+        | row | { GetTransferOrdersBorrowed { id : row.get(0),created_at : row.get(1),sender : row.get(2),recipient : row.get(3),amount : row.get(4),run_at : row.get(5),cron : row.get(6),next_run_at : row.get(7),cancelled_at : row.get(8),} }, mapper : | it | { <GetTransferOrders>::from(it) },
     }
-} }pub fn get_accounts() -> GetAccountsStmt
-{ GetAccountsStmt(cornucopia_async :: private :: Stmt :: new("WITH received_amount AS (
-    SELECT recipient, COALESCE(SUM(amount), 0) AS received_total
-    FROM transactions
-    GROUP BY recipient
-),
-spent_amount AS (
-    SELECT sender, COALESCE(SUM(amount), 0) AS spent_total
-    FROM transactions
-    GROUP BY sender
-)
-SELECT
+} }pub fn get_order_executions() -> GetOrderExecutionsStmt
+{ GetOrderExecutionsStmt(cornucopia_async :: private :: Stmt :: new("SELECT order_id, executed_at, transaction_id, error
+FROM transfer_order_executions
+WHERE order_id = ANY($1)
+ORDER BY id")) } pub
+struct GetOrderExecutionsStmt(cornucopia_async :: private :: Stmt) ; impl
+GetOrderExecutionsStmt { pub fn bind < 'a, C : GenericClient, T1 : cornucopia_async::ArraySql<Item = uuid::Uuid>,>
+(& 'a mut self, client : & 'a  C,
+order_ids : & 'a T1,) -> GetOrderExecutionsQuery < 'a, C,
+GetOrderExecutions, 1 >
+{
+    GetOrderExecutionsQuery
+    {
+        client, params : [order_ids,], stmt : & mut self.0, extractor :
+        | row | { GetOrderExecutionsBorrowed { order_id : row.get(0),executed_at : row.get(1),transaction_id : row.get(2),error : row.get(3),} }, mapper : | it | { <GetOrderExecutions>::from(it) },
+    }
+} }pub fn cancel_transfer_order() -> CancelTransferOrderStmt
+{ CancelTransferOrderStmt(cornucopia_async :: private :: Stmt :: new("UPDATE transfer_orders
+SET cancelled_at = CURRENT_TIMESTAMP, next_run_at = NULL
+WHERE id = $1 AND cancelled_at IS NULL")) } pub
+struct CancelTransferOrderStmt(cornucopia_async :: private :: Stmt) ; impl
+CancelTransferOrderStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [id,]) .await
+} }pub fn is_transfer_order_exists() -> IsTransferOrderExistsStmt
+{ IsTransferOrderExistsStmt(cornucopia_async :: private :: Stmt :: new("SELECT EXISTS(SELECT 1 FROM transfer_orders WHERE id = $1)")) } pub
+struct IsTransferOrderExistsStmt(cornucopia_async :: private :: Stmt) ; impl
+IsTransferOrderExistsStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,) -> BoolQuery < 'a, C,
+bool, 1 >
+{
+    BoolQuery
+    {
+        client, params : [id,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }pub fn lock_transfer_order() -> LockTransferOrderStmt
+{ LockTransferOrderStmt(cornucopia_async :: private :: Stmt :: new("SELECT id
+FROM transfer_orders
+WHERE id = $1 AND cancelled_at IS NULL AND next_run_at <= $2
+FOR UPDATE")) } pub
+struct LockTransferOrderStmt(cornucopia_async :: private :: Stmt) ; impl
+LockTransferOrderStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+id : & 'a uuid::Uuid,due_at : & 'a time::OffsetDateTime,) -> UuidUuidQuery < 'a, C,
+uuid::Uuid, 2 >
+{
+    UuidUuidQuery
+    {
+        client, params : [id,due_at,], stmt : & mut self.0, extractor :
+        | row | { row.get(0) }, mapper : | it | { it },
+    }
+} }impl < 'a, C : GenericClient, > cornucopia_async ::
+Params < 'a, LockTransferOrderParams, UuidUuidQuery < 'a, C,
+uuid::Uuid, 2 >, C > for LockTransferOrderStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    LockTransferOrderParams) -> UuidUuidQuery < 'a, C,
+    uuid::Uuid, 2 >
+    { self.bind(client, & params.id,& params.due_at,) }
+}pub fn insert_order_execution() -> InsertOrderExecutionStmt
+{ InsertOrderExecutionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transfer_order_executions(order_id, executed_at, transaction_id, error)
+VALUES ($1, $2, $3, $4)")) } pub
+struct InsertOrderExecutionStmt(cornucopia_async :: private :: Stmt) ; impl
+InsertOrderExecutionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+order_id : & 'a uuid::Uuid,executed_at : & 'a time::OffsetDateTime,transaction_id : & 'a Option<uuid::Uuid>,error : & 'a Option<T1>,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [order_id,executed_at,transaction_id,error,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, InsertOrderExecutionParams < T1,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertOrderExecutionStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    InsertOrderExecutionParams < T1,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.order_id,& params.executed_at,& params.transaction_id,& params.error,) ) }
+}pub fn set_order_next_run() -> SetOrderNextRunStmt
+{ SetOrderNextRunStmt(cornucopia_async :: private :: Stmt :: new("UPDATE transfer_orders
+SET next_run_at = $1
+WHERE id = $2 AND cancelled_at IS NULL")) } pub
+struct SetOrderNextRunStmt(cornucopia_async :: private :: Stmt) ; impl
+SetOrderNextRunStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+next_run_at : & 'a Option<time::OffsetDateTime>,id : & 'a uuid::Uuid,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [next_run_at,id,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, >
+cornucopia_async :: Params < 'a, SetOrderNextRunParams, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for SetOrderNextRunStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    SetOrderNextRunParams) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.next_run_at,& params.id,) ) }
+}pub fn audit_accounts() -> AuditAccountsStmt
+{ AuditAccountsStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    id,
+    card_number,
+    balance,
+    overdraft_limit,
+    deleted_at
+FROM accounts
+ORDER BY id")) } pub
+struct AuditAccountsStmt(cornucopia_async :: private :: Stmt) ; impl
+AuditAccountsStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> AuditAccountsQuery < 'a, C,
+AuditAccounts, 0 >
+{
+    AuditAccountsQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { AuditAccountsBorrowed { id : row.get(0),card_number : row.get(1),balance : row.get(2),overdraft_limit : row.get(3),deleted_at : row.get(4),} }, mapper : | it | { <AuditAccounts>::from(it) },
+    }
+} }pub fn audit_tokens() -> AuditTokensStmt
+{ AuditTokensStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    tokens.token,
+    a.card_number
+FROM tokens
+LEFT JOIN accounts a ON tokens.account = a.id
+ORDER BY tokens.id")) } pub
+struct AuditTokensStmt(cornucopia_async :: private :: Stmt) ; impl
+AuditTokensStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> AuditTokensQuery < 'a, C,
+AuditTokens, 0 >
+{
+    AuditTokensQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { AuditTokensBorrowed { token : row.get(0),card_number : row.get(1),} }, mapper : | it | { <AuditTokens>::from(it) },
+    }
+} }pub fn export_accounts() -> ExportAccountsStmt
+{ ExportAccountsStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    a.id,
     a.username,
     a.card_number,
+    a.password_hash,
     a.is_existing,
-    COALESCE(ra.received_total, 0) - COALESCE(sa.spent_total, 0) AS balance,
-    ARRAY_AGG(t.token) AS tokens
+    a.deleted_at,
+    a.overdraft_limit,
+    a.status,
+    a.status_reason,
+    a.card_expiry_month,
+    a.card_expiry_year,
+    a.cvv_hash,
+    t.terminal_key
 FROM accounts a
-LEFT JOIN received_amount ra ON a.id = ra.recipient
-LEFT JOIN spent_amount sa ON a.id = sa.sender
-LEFT JOIN tokens t ON a.id = t.account
-GROUP BY a.username, a.card_number, a.is_existing, ra.received_total, sa.spent_total")) } pub
-struct GetAccountsStmt(cornucopia_async :: private :: Stmt) ; impl
-GetAccountsStmt { pub fn bind < 'a, C : GenericClient, >
+LEFT JOIN terminals t ON t.account = a.id
+ORDER BY a.id")) } pub
+struct ExportAccountsStmt(cornucopia_async :: private :: Stmt) ; impl
+ExportAccountsStmt { pub fn bind < 'a, C : GenericClient, >
 (& 'a mut self, client : & 'a  C,
-) -> GetAccountsQuery < 'a, C,
-GetAccounts, 0 >
+) -> ExportAccountsQuery < 'a, C,
+ExportAccounts, 0 >
 {
-    GetAccountsQuery
+    ExportAccountsQuery
     {
         client, params : [], stmt : & mut self.0, extractor :
-        | row | { GetAccountsBorrowed { username : row.get(0),card_number : row.get(1),is_existing : row.get(2),balance : row.get(3),tokens : row.get(4),} }, mapper : | it | { <GetAccounts>::from(it) },
+        | row | { ExportAccountsBorrowed { id : row.get(0),username : row.get(1),card_number : row.get(2),password_hash : row.get(3),is_existing : row.get(4),deleted_at : row.get(5),overdraft_limit : row.get(6),status : row.get(7),status_reason : row.get(8),card_expiry_month : row.get(9),card_expiry_year : row.get(10),cvv_hash : row.get(11),terminal_key : row.get(12),} }, mapper : | it | { <ExportAccounts>::from(it) },
     }
-} }pub fn create_transaction() -> CreateTransactionStmt
-{ CreateTransactionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transactions(sender, recipient, amount)
+} }pub fn export_tokens() -> ExportTokensStmt
+{ ExportTokensStmt(cornucopia_async :: private :: Stmt :: new("SELECT
+    tokens.token,
+    a.card_number,
+    tokens.created_at,
+    tokens.expires_at,
+    tokens.revoked_at,
+    tokens.terminal_key
+FROM tokens
+JOIN accounts a ON tokens.account = a.id
+ORDER BY tokens.id")) } pub
+struct ExportTokensStmt(cornucopia_async :: private :: Stmt) ; impl
+ExportTokensStmt { pub fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> ExportTokensQuery < 'a, C,
+ExportTokens, 0 >
+{
+    ExportTokensQuery
+    {
+        client, params : [], stmt : & mut self.0, extractor :
+        | row | { ExportTokensBorrowed { token : row.get(0),card_number : row.get(1),created_at : row.get(2),expires_at : row.get(3),revoked_at : row.get(4),terminal_key : row.get(5),} }, mapper : | it | { <ExportTokens>::from(it) },
+    }
+} }pub fn clear_bank() -> ClearBankStmt
+{ ClearBankStmt(cornucopia_async :: private :: Stmt :: new("TRUNCATE accounts, transactions, tokens, holds, terminals, idempotency_keys,
+    loans, transfer_orders, transfer_order_executions, system_accounts
+RESTART IDENTITY")) } pub
+struct ClearBankStmt(cornucopia_async :: private :: Stmt) ; impl
+ClearBankStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & []) .await
+} }pub fn disable_balance_check() -> DisableBalanceCheckStmt
+{ DisableBalanceCheckStmt(cornucopia_async :: private :: Stmt :: new("ALTER TABLE transactions
+DISABLE TRIGGER trg_check_balance_before_transaction")) } pub
+struct DisableBalanceCheckStmt(cornucopia_async :: private :: Stmt) ; impl
+DisableBalanceCheckStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & []) .await
+} }pub fn enable_balance_check() -> EnableBalanceCheckStmt
+{ EnableBalanceCheckStmt(cornucopia_async :: private :: Stmt :: new("ALTER TABLE transactions
+ENABLE TRIGGER trg_check_balance_before_transaction")) } pub
+struct EnableBalanceCheckStmt(cornucopia_async :: private :: Stmt) ; impl
+EnableBalanceCheckStmt { pub async fn bind < 'a, C : GenericClient, >
+(& 'a mut self, client : & 'a  C,
+) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & []) .await
+} }pub fn import_account() -> ImportAccountStmt
+{ ImportAccountStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO accounts(
+    username,
+    card_number,
+    password_hash,
+    is_existing,
+    deleted_at,
+    overdraft_limit,
+    status,
+    status_reason,
+    card_expiry_month,
+    card_expiry_year,
+    cvv_hash
+)
+VALUES (
+    $1,
+    $2,
+    $3,
+    $4,
+    $5,
+    $6,
+    $7,
+    $8,
+    $9,
+    $10,
+    $11
+)")) } pub
+struct ImportAccountStmt(cornucopia_async :: private :: Stmt) ; impl
+ImportAccountStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,T4 : cornucopia_async::StringSql,T5 : cornucopia_async::StringSql,T6 : cornucopia_async::StringSql,>
+(& 'a mut self, client : & 'a  C,
+username : & 'a T1,card_number : & 'a T2,password_hash : & 'a T3,is_existing : & 'a bool,deleted_at : & 'a Option<time::OffsetDateTime>,overdraft_limit : & 'a i64,status : & 'a T4,status_reason : & 'a Option<T5>,card_expiry_month : & 'a Option<i16>,card_expiry_year : & 'a Option<i16>,cvv_hash : & 'a Option<T6>,) -> Result < u64, tokio_postgres :: Error >
+{
+    let stmt = self.0.prepare(client) .await ? ;
+    client.execute(stmt, & [username,card_number,password_hash,is_existing,deleted_at,overdraft_limit,status,status_reason,card_expiry_month,card_expiry_year,cvv_hash,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,T4 : cornucopia_async::StringSql,T5 : cornucopia_async::StringSql,T6 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, ImportAccountParams < T1,T2,T3,T4,T5,T6,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for ImportAccountStmt
+{
+    fn
+    params(& 'a mut self, client : & 'a  C, params : & 'a
+    ImportAccountParams < T1,T2,T3,T4,T5,T6,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.username,& params.card_number,& params.password_hash,& params.is_existing,& params.deleted_at,& params.overdraft_limit,& params.status,& params.status_reason,& params.card_expiry_month,& params.card_expiry_year,& params.cvv_hash,) ) }
+}pub fn import_token() -> ImportTokenStmt
+{ ImportTokenStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO tokens(account, token, created_at, expires_at, revoked_at, terminal_key)
 VALUES (
     (
-        SELECT id FROM accounts WHERE card_number = $1 
-    ),
-    (
-        SELECT id FROM accounts WHERE card_number = $2
+        SELECT id FROM accounts WHERE card_number = $1
     ),
-     $3
+    $2,
+    $3,
+    $4,
+    $5,
+    $6
 )")) } pub
-struct CreateTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
-CreateTransactionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+struct ImportTokenStmt(cornucopia_async :: private :: Stmt) ; impl
+ImportTokenStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
 (& 'a mut self, client : & 'a  C,
-sender_card : & 'a T1,recipient_card : & 'a T2,amount : & 'a i64,) -> Result < u64, tokio_postgres :: Error >
+card_number : & 'a T1,token : & 'a T2,created_at : & 'a time::OffsetDateTime,expires_at : & 'a Option<time::OffsetDateTime>,revoked_at : & 'a Option<time::OffsetDateTime>,terminal_key : & 'a Option<uuid::Uuid>,) -> Result < u64, tokio_postgres :: Error >
 {
     let stmt = self.0.prepare(client) .await ? ;
-    client.execute(stmt, & [sender_card,recipient_card,amount,]) .await
+    client.execute(stmt, & [card_number,token,created_at,expires_at,revoked_at,terminal_key,]) .await
 } }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
-cornucopia_async :: Params < 'a, CreateTransactionParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
-u64, tokio_postgres :: Error > > + Send + 'a>>, C > for CreateTransactionStmt
+cornucopia_async :: Params < 'a, ImportTokenParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for ImportTokenStmt
 {
     fn
     params(& 'a mut self, client : & 'a  C, params : & 'a
-    CreateTransactionParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
-    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.sender_card,& params.recipient_card,& params.amount,) ) }
-}pub fn insert_token() -> InsertTokenStmt
-{ InsertTokenStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO tokens(account, token)
+    ImportTokenParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.card_number,& params.token,& params.created_at,& params.expires_at,& params.revoked_at,& params.terminal_key,) ) }
+}pub fn import_transaction() -> ImportTransactionStmt
+{ ImportTransactionStmt(cornucopia_async :: private :: Stmt :: new("INSERT INTO transactions(transaction_id, created_at, sender, recipient, amount, kind)
 VALUES (
+    $1,
+    $2,
     (
-        SELECT id FROM accounts WHERE card_number = $1
+        SELECT id FROM accounts WHERE card_number = $3
+    ),
+    (
+        SELECT id FROM accounts WHERE card_number = $4
     ),
-    $2
+    $5,
+    $6
 )")) } pub
-struct InsertTokenStmt(cornucopia_async :: private :: Stmt) ; impl
-InsertTokenStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
+struct ImportTransactionStmt(cornucopia_async :: private :: Stmt) ; impl
+ImportTransactionStmt { pub async fn bind < 'a, C : GenericClient, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
 (& 'a mut self, client : & 'a  C,
-card_number : & 'a T1,token : & 'a T2,) -> Result < u64, tokio_postgres :: Error >
+transaction_id : & 'a uuid::Uuid,created_at : & 'a time::OffsetDateTime,sender_card : & 'a T1,recipient_card : & 'a T2,amount : & 'a i64,kind : & 'a T3,) -> Result < u64, tokio_postgres :: Error >
 {
     let stmt = self.0.prepare(client) .await ? ;
-    client.execute(stmt, & [card_number,token,]) .await
-} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,>
-cornucopia_async :: Params < 'a, InsertTokenParams < T1,T2,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
-u64, tokio_postgres :: Error > > + Send + 'a>>, C > for InsertTokenStmt
+    client.execute(stmt, & [transaction_id,created_at,sender_card,recipient_card,amount,kind,]) .await
+} }impl < 'a, C : GenericClient + Send + Sync, T1 : cornucopia_async::StringSql,T2 : cornucopia_async::StringSql,T3 : cornucopia_async::StringSql,>
+cornucopia_async :: Params < 'a, ImportTransactionParams < T1,T2,T3,>, std::pin::Pin<Box<dyn futures::Future<Output = Result <
+u64, tokio_postgres :: Error > > + Send + 'a>>, C > for ImportTransactionStmt
 {
     fn
     params(& 'a mut self, client : & 'a  C, params : & 'a
-    InsertTokenParams < T1,T2,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
-    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.card_number,& params.token,) ) }
+    ImportTransactionParams < T1,T2,T3,>) -> std::pin::Pin<Box<dyn futures::Future<Output = Result < u64, tokio_postgres ::
+    Error > > + Send + 'a>> { Box::pin(self.bind(client, & params.transaction_id,& params.created_at,& params.sender_card,& params.recipient_card,& params.amount,& params.kind,) ) }
 }}}
\ No newline at end of file
@@ -2,38 +2,117 @@
 
 use std::str::FromStr;
 
-use rand::distributions::Uniform;
+use rand::seq::SliceRandom;
 use rand::thread_rng;
 use rand::Rng;
 use serde::{de::Visitor, Deserialize, Serialize};
 
+use crate::bank::BankOperationError;
+use crate::config::CardSettings;
+
+const CARD_LENGTH: usize = 16;
+
+/// Payment system, detected by the card number prefix
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CardNetwork {
+    Visa,
+    Mastercard,
+    Mir,
+    Unknown,
+}
+
 /// This type guarantees us that `UserName` is properly formed.
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct CardNumber(String);
 
 impl CardNumber {
     /// Returns an instance of `CardNumber` if the input satisfies
     /// our validation constraints on card numbers.
     pub fn parse(card: &str) -> Result<CardNumber, anyhow::Error> {
-        if !card.chars().all(char::is_numeric) {
+        if !card.chars().all(|c| c.is_ascii_digit()) {
             return Err(anyhow::anyhow!("Card should contain only numerics"));
         }
-        if card.len() != 16 {
+        if card.len() != CARD_LENGTH {
             return Err(anyhow::anyhow!("Card should contain 16 numbers"));
         }
         Ok(CardNumber(String::from(card)))
     }
 
-    pub fn generate() -> Self {
+    /// Generate Luhn-valid card number, starting with one of the
+    /// configured BINs.
+    pub fn generate(settings: &CardSettings) -> Self {
         let mut rng = thread_rng();
-        CardNumber(
-            // ASCII table contains number symbols from 48 to 57 cells
-            std::iter::repeat_with(|| rng.sample(Uniform::new(48, 57)))
-                .map(char::from)
-                .take(16)
-                .collect(),
-        )
+        let bin = settings
+            .bins
+            .choose(&mut rng)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let mut card: String = bin.chars().take(CARD_LENGTH - 1).collect();
+        while card.len() < CARD_LENGTH - 1 {
+            card.push(char::from(b'0' + rng.gen_range(0..10)));
+        }
+        card.push(luhn_check_digit(&card));
+        CardNumber(card)
+    }
+
+    pub fn is_luhn_valid(&self) -> bool {
+        let payload = &self.0[..CARD_LENGTH - 1];
+        self.0.ends_with(luhn_check_digit(payload))
+    }
+
+    /// Card numbers from requests should have a valid Luhn check digit,
+    /// if it is enabled in the settings
+    pub fn check_luhn(
+        &self,
+        settings: &CardSettings,
+    ) -> Result<(), BankOperationError> {
+        if settings.validate_luhn && !self.is_luhn_valid() {
+            return Err(BankOperationError::InvalidCardNumber);
+        }
+        Ok(())
+    }
+
+    pub fn network(&self) -> CardNetwork {
+        let prefix = |len: usize| self.0[..len].parse::<u32>().unwrap_or(0);
+        match (prefix(1), prefix(2), prefix(4)) {
+            (4, _, _) => CardNetwork::Visa,
+            (_, 51..=55, _) | (_, _, 2221..=2720) => CardNetwork::Mastercard,
+            (_, _, 2200..=2204) => CardNetwork::Mir,
+            _ => CardNetwork::Unknown,
+        }
     }
+
+    /// Card number with only the first and the last 4 digits shown,
+    /// like `2200 **** **** 1234`, safe to share with merchants.
+    pub fn masked(&self) -> String {
+        format!("{} **** **** {}", &self.0[..4], &self.0[CARD_LENGTH - 4..])
+    }
+}
+
+/// Check digit, which makes the Luhn sum of `payload` with it
+/// divisible by 10.
+fn luhn_check_digit(payload: &str) -> char {
+    let sum: u32 = payload
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(idx, digit)| {
+            // Double every second digit, starting left of the check digit
+            if idx % 2 == 0 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    char::from(b'0' + ((10 - sum % 10) % 10) as u8)
 }
 
 impl AsRef<str> for CardNumber {
@@ -82,7 +161,8 @@ impl<'de> Deserialize<'de> for CardNumber {
 
 #[cfg(test)]
 mod tests {
-    use super::CardNumber;
+    use super::{CardNetwork, CardNumber};
+    use crate::config::CardSettings;
 
     #[test]
     fn test_correct_card_number() {
@@ -96,6 +176,13 @@ mod tests {
         assert!(card.is_err())
     }
 
+    #[test]
+    fn test_card_contains_non_ascii_digits() {
+        // Arabic-Indic digits are numeric, but take two bytes each
+        let card = CardNumber::parse("١٢٣٤٥٦٧٨");
+        assert!(card.is_err())
+    }
+
     #[test]
     fn test_card_has_bad_length() {
         let card = CardNumber::parse("1234123412341");
@@ -104,9 +191,34 @@ mod tests {
 
     #[test]
     fn generation_success() {
+        let settings = CardSettings::default();
         for _ in 0..100 {
-            let card = CardNumber::generate();
-            assert!(CardNumber::parse(&card.0).is_ok())
+            let card = CardNumber::generate(&settings);
+            assert!(CardNumber::parse(&card.0).is_ok());
+            assert!(card.is_luhn_valid());
+            assert_ne!(card.network(), CardNetwork::Unknown);
         }
     }
+
+    #[test]
+    fn luhn_check_digit_and_network() {
+        let visa = CardNumber::parse("4111111111111111").unwrap();
+        assert!(visa.is_luhn_valid());
+        assert_eq!(visa.network(), CardNetwork::Visa);
+        let mastercard = CardNumber::parse("5555555555554444").unwrap();
+        assert!(mastercard.is_luhn_valid());
+        assert_eq!(mastercard.network(), CardNetwork::Mastercard);
+        let mir = CardNumber::parse("2200000000000004").unwrap();
+        assert!(mir.is_luhn_valid());
+        assert_eq!(mir.network(), CardNetwork::Mir);
+        assert!(!CardNumber::parse("4111111111111112")
+            .unwrap()
+            .is_luhn_valid());
+    }
+
+    #[test]
+    fn masked_card_hides_middle_digits() {
+        let card = CardNumber::parse("2200123456781234").unwrap();
+        assert_eq!(card.masked(), "2200 **** **** 1234");
+    }
 }
@@ -1 +1,2 @@
+pub mod session_api;
 pub mod system_api;
@@ -0,0 +1,49 @@
+use banksim_api::init_payment::beneficiaries::Beneficiaries;
+use banksim_api::session::webhook::WebhookRequest;
+use serde::Deserialize;
+use url::Url;
+use uuid::Uuid;
+
+/// Request to the store-bank api, `terminal_key` selects merchant's
+/// terminal, the default one is used if it is not set.
+#[derive(Deserialize)]
+pub struct TerminalRequest<T> {
+    pub terminal_key: Option<Uuid>,
+    #[serde(flatten)]
+    pub req: T,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeTokenRequest {
+    pub card_token: String,
+}
+
+/// Merchant-initiated payment from the tokenized card, without payer's
+/// interaction.
+#[derive(Deserialize)]
+pub struct ChargeTokenRequest {
+    pub card_token: String,
+    pub amount: i64,
+    /// Receives `PaymentFinished` notification, as for the usual payment
+    pub notification_url: Url,
+    /// If not set, money goes to the store account
+    pub beneficiaries: Option<Beneficiaries>,
+}
+
+#[derive(Deserialize)]
+pub struct CaptureRequest {
+    #[serde(flatten)]
+    pub webhook: WebhookRequest,
+    /// If not set, the whole authorized amount will be captured
+    pub amount: Option<i64>,
+}
+
+/// Refund of the captured payment, possible only while it's session
+/// lives, an hour after creation
+#[derive(Deserialize)]
+pub struct RefundRequest {
+    #[serde(flatten)]
+    pub webhook: WebhookRequest,
+    /// If not set, the whole remaining amount will be refunded
+    pub amount: Option<i64>,
+}
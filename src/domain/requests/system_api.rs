@@ -1,8 +1,16 @@
+use rust_decimal::Decimal;
 use secrecy::Secret;
 use serde::Deserialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
+use crate::bank::transfer_order::OrderSchedule;
+use crate::bank::{iso_format, AccountStatus};
 use crate::domain::card_number::CardNumber;
 
+const DEFAULT_STATEMENT_LIMIT: usize = 50;
+const MAX_STATEMENT_LIMIT: usize = 500;
+
 #[derive(Deserialize)]
 pub struct AddAccountRequest {
     pub username: String,
@@ -14,15 +22,144 @@ pub struct DeleteAccountRequest {
     pub card_number: CardNumber,
 }
 
+#[derive(Deserialize)]
+pub struct ReissueCardRequest {
+    pub card_number: CardNumber,
+    /// Set both to issue the card with a specific, even past, expiry date
+    pub expiry_month: Option<u8>,
+    pub expiry_year: Option<u16>,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreAccountRequest {
+    pub card_number: CardNumber,
+}
+
+#[derive(Deserialize)]
+pub struct SetAccountStatusRequest {
+    pub card_number: CardNumber,
+    pub status: AccountStatus,
+    /// Why the status was changed, stored with the status
+    pub reason: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct OpenCreditRequest {
     pub card_number: CardNumber,
     pub amount: i64,
 }
 
+#[derive(Deserialize)]
+pub struct OpenLoanRequest {
+    pub card_number: CardNumber,
+    pub principal: i64,
+    /// Interest rate per period, `0.01` is 1%
+    pub interest_rate: Decimal,
+    /// Number of periods
+    pub term: u32,
+    /// Period length, 30 days by default
+    pub period_secs: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct RepayLoanRequest {
+    pub loan_id: Uuid,
+    pub amount: i64,
+}
+
+#[derive(Deserialize)]
+pub struct NewTransferOrderRequest {
+    pub sender: CardNumber,
+    pub recipient: CardNumber,
+    pub amount: i64,
+    /// `{"once": "<datetime>"}` or `{"cron": "0 9 1 * *"}`
+    pub schedule: OrderSchedule,
+}
+
+#[derive(Deserialize)]
+pub struct CancelTransferOrderRequest {
+    pub order_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct SetOverdraftLimitRequest {
+    pub card_number: CardNumber,
+    pub limit: i64,
+}
+
 #[derive(Deserialize)]
 pub struct NewTransactionRequest {
     pub from: CardNumber,
     pub to: CardNumber,
     pub amount: i64,
 }
+
+#[derive(Deserialize)]
+pub struct NewCardTokenRequest {
+    pub card_number: CardNumber,
+    /// Token lives until it is revoked if it is not set
+    #[serde(default, with = "iso_format::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    /// Only this terminal can charge the token, unbound token can't be
+    /// charged through the session api
+    pub terminal_key: Option<uuid::Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeCardTokenRequest {
+    pub card_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct CardTokensQuery {
+    pub card_number: CardNumber,
+}
+
+/// Selects merchant's terminal, the default one is used if it is not set
+#[derive(Deserialize)]
+pub struct TerminalQuery {
+    pub terminal_key: Option<uuid::Uuid>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Incoming => "incoming",
+            Direction::Outgoing => "outgoing",
+        }
+    }
+}
+
+/// Page of the card statement, transactions are sorted by time
+#[derive(Deserialize, Clone)]
+pub struct StatementRequest {
+    pub card_number: CardNumber,
+    /// Id of the last transaction on the previous page
+    pub cursor: Option<Uuid>,
+    pub limit: Option<usize>,
+    /// Inclusive
+    #[serde(default, with = "iso_format::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Exclusive
+    #[serde(default, with = "iso_format::option")]
+    pub to: Option<OffsetDateTime>,
+    pub direction: Option<Direction>,
+    pub counterparty: Option<CardNumber>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+}
+
+impl StatementRequest {
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_STATEMENT_LIMIT)
+            .clamp(1, MAX_STATEMENT_LIMIT)
+    }
+}
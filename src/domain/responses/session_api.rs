@@ -0,0 +1,102 @@
+use banksim_api::make_payment;
+use banksim_api::token_info;
+use banksim_api::OperationStatus;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::bank::{iso_format, CardExpiry};
+use crate::domain::card_number::CardNetwork;
+
+/// Notifications which are not covered by `banksim_api::notifications`,
+/// serialized in the same shape.
+#[derive(Serialize)]
+pub enum Notification {
+    PaymentNotification(PaymentNotification),
+    RefundNotification(RefundNotification),
+}
+
+#[derive(Serialize)]
+pub enum PaymentNotification {
+    /// `banksim_api` notification extended with captured amount,
+    /// which can differ from the authorized one, and acquiring fee,
+    /// withheld from it
+    PaymentFinished {
+        session_id: Uuid,
+        status: OperationStatus,
+        captured_amount: Option<i64>,
+        fee: Option<i64>,
+    },
+}
+
+/// `banksim_api` response extended with id of the performed transaction
+#[derive(Serialize)]
+pub struct MakePaymentResponse {
+    #[serde(flatten)]
+    pub response: make_payment::MakePaymentResponse,
+    pub transaction_id: Option<Uuid>,
+}
+
+impl MakePaymentResponse {
+    pub fn success(transaction_id: Uuid) -> Self {
+        MakePaymentResponse {
+            response: make_payment::MakePaymentResponse::success(),
+            transaction_id: Some(transaction_id),
+        }
+    }
+
+    pub fn err(e: String) -> Self {
+        MakePaymentResponse {
+            response: make_payment::MakePaymentResponse::err(e),
+            transaction_id: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub enum RefundNotification {
+    RefundFinished {
+        session_id: Uuid,
+        amount: i64,
+        status: OperationStatus,
+    },
+}
+
+/// `banksim_api` response extended with card details, `card` is set
+/// only for the existing account. Full card number is never exposed.
+#[derive(Serialize)]
+pub struct TokenInfoResponse {
+    #[serde(flatten)]
+    pub response: token_info::TokenInfoResponse,
+    pub card: Option<TokenCardInfo>,
+}
+
+impl TokenInfoResponse {
+    pub fn err(e: &str) -> Self {
+        TokenInfoResponse {
+            response: token_info::TokenInfoResponse {
+                status: Err(e.to_string()),
+            },
+            card: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TokenCardInfo {
+    /// Like `2200 **** **** 1234`
+    pub masked_card_number: String,
+    pub network: CardNetwork,
+    pub expiry: Option<CardExpiry>,
+    #[serde(with = "iso_format")]
+    pub token_created_at: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+pub struct ChargeTokenResponse {
+    /// Id of the `PaymentFinished` notification session
+    pub session_id: Uuid,
+    pub status: OperationStatus,
+    /// One transaction per beneficiary for the split payment
+    pub transaction_ids: Vec<Uuid>,
+}
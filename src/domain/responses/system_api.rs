@@ -1,19 +1,47 @@
+use secrecy::ExposeSecret;
 use serde::Serialize;
-use std::collections::HashMap;
+use uuid::Uuid;
 
-use crate::{bank::Transaction, domain::card_number::CardNumber};
+use crate::bank::transfer_order::TransferOrder;
+use crate::bank::{AccountStatus, CardToken, Hold, IssuedCard, Transaction};
+use crate::domain::card_number::CardNumber;
 
 #[derive(Serialize)]
 pub struct AddAccountResponse {
     pub card_number: CardNumber,
+    pub expiry_month: u8,
+    pub expiry_year: u16,
+    pub cvv: String,
+}
+
+impl From<IssuedCard> for AddAccountResponse {
+    fn from(card: IssuedCard) -> Self {
+        AddAccountResponse {
+            card_number: card.card_number,
+            expiry_month: card.expiry.month,
+            expiry_year: card.expiry.year,
+            cvv: card.cvv.expose_secret().clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TransactionResponse {
+    pub transaction_id: Uuid,
 }
 
 #[derive(Debug, Serialize)]
 pub struct Account {
     pub card_number: CardNumber,
     pub balance: i64,
+    pub overdraft_limit: i64,
+    /// Negative part of the balance
+    pub debt: i64,
     pub transactions: Vec<Transaction>,
+    pub holds: Vec<Hold>,
     pub exists: bool,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
     pub tokens: Vec<String>,
     pub username: String,
 }
@@ -23,7 +51,44 @@ pub struct ListAccountsResponse {
     pub accounts: Vec<Account>,
 }
 
+#[derive(Serialize)]
+pub struct NewCardTokenResponse {
+    pub card_token: String,
+}
+
 #[derive(Serialize)]
 pub struct ListCardTokensResponse {
-    pub list: HashMap<String, CardNumber>,
+    pub tokens: Vec<CardToken>,
+}
+
+#[derive(Serialize)]
+pub struct ListTransferOrdersResponse {
+    pub orders: Vec<TransferOrder>,
+}
+
+#[derive(Serialize)]
+pub struct StatementResponse {
+    pub transactions: Vec<Transaction>,
+    /// Pass as `cursor` to get the next page, `None` on the last page
+    pub next_cursor: Option<Uuid>,
+}
+
+impl StatementResponse {
+    /// Build a page from up to `limit + 1` transactions,
+    /// the extra one means there is a next page
+    pub fn from_transactions(
+        mut transactions: Vec<Transaction>,
+        limit: usize,
+    ) -> Self {
+        let next_cursor = if transactions.len() > limit {
+            transactions.truncate(limit);
+            transactions.last().map(|t| t.id())
+        } else {
+            None
+        };
+        StatementResponse {
+            transactions,
+            next_cursor,
+        }
+    }
 }
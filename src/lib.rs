@@ -7,6 +7,7 @@ mod startup;
 
 pub mod cornucopia;
 pub mod domain;
+pub mod scheduler;
 pub mod session;
 pub mod tasks;
 pub mod ws_tracing_subscriber;
@@ -1,16 +1,26 @@
 use anyhow::Context;
-use axum::http::{HeaderMap, StatusCode};
+use axum::body::Body;
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::{extract::Request, response::Response};
 use base64::Engine;
 use futures::future::BoxFuture;
-use secrecy::Secret;
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::task::Poll;
 use tower::{Layer, Service};
 
+use crate::bank::{IdempotencyStatus, IdempotentResponse};
+use crate::config::{Settings, TerminalSettings};
 use crate::startup::AppState;
 
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+/// Limit of the buffered request and response bodies,
+/// same as the axum default request body limit
+const MAX_IDEMPOTENT_BODY_LEN: usize = 2 * 1024 * 1024;
+
 pub struct Credentials {
     pub username: String,
     pub password: Secret<String>,
@@ -83,6 +93,261 @@ where
     }
 }
 
+/// Authenticates the sender of the request by it's headers and body,
+/// `None` if the request is not authorized
+pub type IdempotencyPrincipal =
+    fn(&AppState, &HeaderMap, &[u8]) -> Option<String>;
+
+/// Performs request with `Idempotency-Key` header only once,
+/// repeated requests get the stored response. Keys of different
+/// principals don't clash.
+#[derive(Clone)]
+pub struct IdempotencyLayer {
+    pub state: AppState,
+    pub principal: IdempotencyPrincipal,
+}
+
+impl<S> Layer<S> for IdempotencyLayer {
+    type Service = Idempotency<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idempotency {
+            inner,
+            state: self.state.clone(),
+            principal: self.principal,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Idempotency<S> {
+    inner: S,
+    state: AppState,
+    principal: IdempotencyPrincipal,
+}
+
+/// System user, already authorized by `BasicAuthLayer`
+pub fn system_principal(
+    _state: &AppState,
+    headers: &HeaderMap,
+    _body: &[u8],
+) -> Option<String> {
+    let credentials = basic_authentication(headers).ok()?;
+    Some(format!("system:{}", credentials.username))
+}
+
+/// Terminal with it's key and password in 'Basic' auth
+pub fn terminal_principal(
+    state: &AppState,
+    headers: &HeaderMap,
+    _body: &[u8],
+) -> Option<String> {
+    let terminal = terminal_authentication(&state.settings, headers).ok()?;
+    Some(format!("terminal:{}", terminal.terminal_key))
+}
+
+impl<S> Service<Request> for Idempotency<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // Take the service which is ready, leave a clone instead
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+        let principal = self.principal;
+        Box::pin(async move {
+            let key = match idempotency_key(request.headers()) {
+                Ok(Some(key)) => key,
+                Ok(None) => return inner.call(request).await,
+                Err(e) => {
+                    tracing::warn!("Bad idempotency key: {e}");
+                    return Ok((StatusCode::BAD_REQUEST, e.to_string())
+                        .into_response());
+                }
+            };
+
+            let (parts, body) = request.into_parts();
+            let body = match axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_LEN)
+                .await
+            {
+                Ok(body) => body,
+                Err(e) => {
+                    tracing::error!("Failed to read request body: {e}");
+                    return Ok(StatusCode::BAD_REQUEST.into_response());
+                }
+            };
+
+            // Unauthorized request is rejected by the handler, it can't
+            // take keys of other principals
+            let Some(principal) = principal(&state, &parts.headers, &body)
+            else {
+                let request = Request::from_parts(parts, Body::from(body));
+                return inner.call(request).await;
+            };
+            let key = format!("{principal}:{key}");
+
+            let mut hasher = Sha256::new();
+            hasher.update(parts.method.as_str());
+            hasher.update(parts.uri.path());
+            hasher.update(&body);
+            let request_hash = hex::encode(hasher.finalize());
+
+            match state
+                .bank
+                .begin_idempotent_request(&key, &request_hash)
+                .await
+            {
+                Ok(IdempotencyStatus::New) => (),
+                Ok(IdempotencyStatus::Completed(response)) => {
+                    tracing::info!("Repeated request with key {key}");
+                    return Ok(stored_response(response));
+                }
+                Ok(IdempotencyStatus::InProgress) => {
+                    return Ok((
+                        StatusCode::CONFLICT,
+                        "Request with this idempotency key is in progress",
+                    )
+                        .into_response());
+                }
+                Ok(IdempotencyStatus::KeyReused) => {
+                    return Ok((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "Idempotency key was used with a different request",
+                    )
+                        .into_response());
+                }
+                Err(e) => {
+                    tracing::error!("Failed to check idempotency key: {e}");
+                    return Ok(
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    );
+                }
+            }
+
+            // Handler runs in it's own task, so the key is completed or
+            // aborted even if the client disconnects or the handler panics
+            let request = Request::from_parts(parts, Body::from(body));
+            let task = tokio::spawn(perform_idempotent_request(
+                inner,
+                request,
+                state.clone(),
+                key.clone(),
+            ));
+            match task.await {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::error!("Idempotent request task failed: {e}");
+                    abort_idempotent_request(&state, &key).await;
+                    Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                }
+            }
+        })
+    }
+}
+
+/// Perform the request with reserved idempotency key and store it's response
+async fn perform_idempotent_request<S>(
+    mut inner: S,
+    request: Request,
+    state: AppState,
+    key: String,
+) -> Result<Response, S::Error>
+where
+    S: Service<Request, Response = Response>,
+{
+    let response = match inner.call(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            abort_idempotent_request(&state, &key).await;
+            return Err(e);
+        }
+    };
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_LEN).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to read response body: {e}");
+            abort_idempotent_request(&state, &key).await;
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+
+    // Let the client retry requests failed on our side
+    let result = if parts.status.is_server_error() {
+        state.bank.abort_idempotent_request(&key).await
+    } else {
+        let response = IdempotentResponse {
+            status: parts.status.as_u16(),
+            content_type: parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+            body: body.to_vec(),
+        };
+        state
+            .bank
+            .complete_idempotent_request(&key, &response)
+            .await
+    };
+    if let Err(e) = result {
+        tracing::error!("Failed to store idempotent response: {e}");
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Release the key, so the client can retry the request
+async fn abort_idempotent_request(state: &AppState, key: &str) {
+    if let Err(e) = state.bank.abort_idempotent_request(key).await {
+        tracing::error!("Failed to abort idempotency key: {e}");
+    }
+}
+
+fn idempotency_key(
+    headers: &HeaderMap,
+) -> Result<Option<String>, anyhow::Error> {
+    let Some(header_value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = header_value
+        .to_str()
+        .context("The 'Idempotency-Key' header was not a valid string")?
+        .trim();
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        anyhow::bail!(
+            "The 'Idempotency-Key' header should contain 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        );
+    }
+    Ok(Some(key.to_string()))
+}
+
+fn stored_response(stored: IdempotentResponse) -> Response {
+    let mut response = Response::builder().status(stored.status);
+    if let Some(content_type) = stored.content_type {
+        response = response.header(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .body(Body::from(stored.body))
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
 fn basic_authentication(
     headers: &HeaderMap,
 ) -> Result<Credentials, anyhow::Error> {
@@ -120,3 +385,27 @@ fn basic_authentication(
         password: Secret::new(password),
     })
 }
+
+/// Terminal authenticates with it's key and password in 'Basic' auth
+pub fn terminal_authentication<'a>(
+    settings: &'a Settings,
+    headers: &HeaderMap,
+) -> Result<&'a TerminalSettings, anyhow::Error> {
+    let credentials = basic_authentication(headers)?;
+    let terminal_key = credentials
+        .username
+        .parse()
+        .context("Terminal key is not a valid uuid")?;
+    let terminal = settings
+        .find_terminal(Some(terminal_key))
+        .context("Unknown terminal")?;
+    if terminal
+        .password
+        .expose_secret()
+        .eq(credentials.password.expose_secret())
+    {
+        Ok(terminal)
+    } else {
+        Err(anyhow::anyhow!("Wrong terminal password"))
+    }
+}
@@ -19,6 +19,18 @@ use crate::startup::AppState;
 pub struct Credentials {
     pub card_number: CardNumber,
     pub password: Secret<String>,
+    /// Terminal of the store
+    pub terminal_key: Uuid,
+}
+
+/// Card details, entered by the payer on the payment page
+#[derive(Clone, Debug, Deserialize)]
+pub struct CardCredentials {
+    pub card_number: CardNumber,
+    pub password: Secret<String>,
+    pub expiry_month: u8,
+    pub expiry_year: u16,
+    pub cvv: Secret<String>,
 }
 
 // ───── Handlers ─────────────────────────────────────────────────────────── //
@@ -95,11 +107,17 @@ pub async fn payment_html_page(
 pub async fn trigger_payment(
     State(state): State<AppState>,
     Path(payment_id): Path<Uuid>,
-    Json(creds): Json<Credentials>,
+    Json(creds): Json<CardCredentials>,
 ) -> Result<String, StatusCode> {
     use crate::session::payment::Event;
     use crate::session::payment::State as PaymentState;
 
+    if let Err(e) = creds.card_number.check_luhn(&state.settings.card_settings)
+    {
+        tracing::warn!("Bad card number: {e}");
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     let session = acquire_session(&state.sessions, payment_id)?;
 
     // Upgrade session state
@@ -125,6 +143,7 @@ pub async fn trigger_payment(
     };
     drop(session_state_guard);
 
+    let bank = state.bank.clone();
     tokio::spawn(async move {
         let mut session_state_guard = session
             .payment_session()
@@ -134,10 +153,7 @@ pub async fn trigger_payment(
             .lock()
             .await;
         session_state_guard
-            .handle(&Event::Submit {
-                bank: state.bank.clone(),
-                creds,
-            })
+            .handle(&Event::Submit { bank, creds })
             .await;
     });
 
@@ -169,7 +185,11 @@ pub async fn trigger_payment(
                 .state
                 .lock()
                 .await;
-            session_state_guard.handle(&Event::Timeout).await;
+            session_state_guard
+                .handle(&Event::Timeout {
+                    bank: state.bank.clone(),
+                })
+                .await;
             Ok(fail_url)
         }
     };
@@ -237,7 +257,10 @@ pub async fn trigger_card_token_registration(
         .await;
 
     // Parse card
-    let Ok(card_for_reg) = CardNumber::parse(&body) else {
+    let Some(card_for_reg) = CardNumber::parse(&body)
+        .ok()
+        .filter(|card| card.check_luhn(&state.settings.card_settings).is_ok())
+    else {
         tracing::error!("Can't parse card number: {}", body);
         return Ok(session_state_guard.req.fail_url.to_string());
     };
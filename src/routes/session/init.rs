@@ -1,5 +1,6 @@
+use axum::http::HeaderMap;
 use axum::{extract::State, routing, Json, Router};
-use banksim_api::make_payment::{MakePaymentRequest, MakePaymentResponse};
+use banksim_api::make_payment::MakePaymentRequest;
 use banksim_api::OperationError;
 use banksim_api::{Operation, Tokenizable};
 
@@ -11,6 +12,9 @@ use banksim_api::register_card_token::{
     RegisterCardTokenRequest, RegisterCardTokenResponse,
 };
 
+use crate::domain::requests::session_api::TerminalRequest;
+use crate::domain::responses::session_api::MakePaymentResponse;
+use crate::middleware::IdempotencyLayer;
 use crate::routes::html_pages_and_triggers::Credentials;
 use crate::session::IntoSession;
 use crate::startup::AppState;
@@ -18,7 +22,7 @@ use crate::tasks::wait_hour_and_remove;
 
 // ───── Handlers ─────────────────────────────────────────────────────────── //
 
-pub fn init_router() -> Router<AppState> {
+pub fn init_router(state: AppState) -> Router<AppState> {
     Router::new()
         .route(
             "/payment",
@@ -35,39 +39,52 @@ pub fn init_router() -> Router<AppState> {
                 >,
             ),
         )
-        .route("/MakePayment", routing::post(make_payment))
+        .route(
+            "/MakePayment",
+            routing::post(make_payment).layer(IdempotencyLayer {
+                state,
+                principal: make_payment_principal,
+            }),
+        )
 }
 
 #[tracing::instrument(name = "Init session", skip_all)]
 async fn init_session<Request, Response>(
     State(mut state): State<AppState>,
-    Json(req): Json<Request>,
+    Json(TerminalRequest { terminal_key, req }): Json<TerminalRequest<Request>>,
 ) -> Json<impl Serialize + 'static>
 where
     Request: Tokenizable + IntoSession,
     Response: Operation + Serialize + 'static,
 {
-    // NOTE: we have only one store account in our virtual bank
-    let store_card = match state.bank.get_store_account().await {
-        Ok(acc) => acc.card(),
-        Err(e) => {
-            tracing::error!("Failed to get store account: {e}");
-            return Json(Response::operation_error(
-                OperationError::Unexpected(e.to_string()),
-            ));
-        }
+    // Resolve merchant's terminal
+    let Some(terminal) = state.settings.find_terminal(terminal_key).cloned()
+    else {
+        tracing::warn!("Unknown terminal: {terminal_key:?}");
+        return Json(Response::operation_error(
+            OperationError::NotAuthorizedRequest,
+        ));
     };
 
+    let store_card =
+        match state.bank.get_store_account(&terminal.terminal_key).await {
+            Ok(acc) => acc.card(),
+            Err(e) => {
+                tracing::error!("Failed to get store account: {e}");
+                return Json(Response::operation_error(
+                    OperationError::Unexpected(e.to_string()),
+                ));
+            }
+        };
+
     let store_creds = Credentials {
         card_number: store_card,
-        password: state.settings.terminal_settings.password.clone(),
+        password: terminal.password.clone(),
+        terminal_key: terminal.terminal_key,
     };
 
     // Authorize request
-    if req
-        .validate_token(&state.settings.terminal_settings.password)
-        .is_err()
-    {
+    if req.validate_token(&terminal.password).is_err() {
         tracing::warn!("Unauthorized request");
         return Json(Response::operation_error(
             OperationError::NotAuthorizedRequest,
@@ -93,7 +110,13 @@ where
     };
 
     // Launch async task which will track our session
-    wait_hour_and_remove(state.sessions, rx, session_id, created_at);
+    let (sessions, bank) = (state.sessions.clone(), state.bank.clone());
+    let expire = async move {
+        if let Err(e) = sessions.expire_session_by_id(session_id, &bank).await {
+            tracing::error!("Failed to expire session: {e}");
+        }
+    };
+    wait_hour_and_remove(state.sessions, rx, session_id, created_at, expire);
 
     let url = format!(
         "http://{}:{}/{}/{}",
@@ -116,28 +139,47 @@ where
     Json(Response::operation_success(session_ui_url, session_id))
 }
 
+/// Terminal of the payment, the request token is checked with it's password
+fn make_payment_principal(
+    state: &AppState,
+    _headers: &HeaderMap,
+    body: &[u8],
+) -> Option<String> {
+    let TerminalRequest { terminal_key, req } =
+        serde_json::from_slice::<TerminalRequest<MakePaymentRequest>>(body)
+            .ok()?;
+    let terminal = state.settings.find_terminal(terminal_key)?;
+    req.validate_token(&terminal.password).ok()?;
+    Some(format!("terminal:{}", terminal.terminal_key))
+}
+
 #[tracing::instrument(name = "Make payment", skip_all)]
 async fn make_payment(
     State(state): State<AppState>,
-    Json(req): Json<MakePaymentRequest>,
+    Json(TerminalRequest { terminal_key, req }): Json<
+        TerminalRequest<MakePaymentRequest>,
+    >,
 ) -> Json<MakePaymentResponse> {
+    // Resolve merchant's terminal
+    let Some(terminal) = state.settings.find_terminal(terminal_key) else {
+        tracing::warn!("Unknown terminal: {terminal_key:?}");
+        return Json(MakePaymentResponse::err("Unauthorized".to_string()));
+    };
+
     // Authorize request
-    if req
-        .validate_token(&state.settings.terminal_settings.password)
-        .is_err()
-    {
+    if req.validate_token(&terminal.password).is_err() {
         tracing::warn!("Unauthorized request");
         return Json(MakePaymentResponse::err("Unauthorized".to_string()));
     }
 
-    // We have only one store account in our virtual bank
-    let store_card = match state.bank.get_store_account().await {
-        Ok(acc) => acc.card(),
-        Err(e) => {
-            tracing::error!("Failed to get store account: {e}");
-            return Json(MakePaymentResponse::err(e.to_string()));
-        }
-    };
+    let store_card =
+        match state.bank.get_store_account(&terminal.terminal_key).await {
+            Ok(acc) => acc.card(),
+            Err(e) => {
+                tracing::error!("Failed to get store account: {e}");
+                return Json(MakePaymentResponse::err(e.to_string()));
+            }
+        };
 
     let recipient_card =
         match state.bank.get_account_by_token(&req.recipient_token).await {
@@ -153,7 +195,7 @@ async fn make_payment(
         .new_transaction(&store_card, &recipient_card, req.amount)
         .await
     {
-        Ok(()) => Json(MakePaymentResponse::success()),
+        Ok(id) => Json(MakePaymentResponse::success(id)),
         Err(e) => {
             tracing::error!("Failed to make transaction: {e}");
             Json(MakePaymentResponse::err(e.to_string()))
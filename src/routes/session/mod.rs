@@ -8,8 +8,12 @@ use banksim_api::session::webhook::WebhookResponse;
 use banksim_api::OperationError;
 use banksim_api::OperationStatus;
 use banksim_api::Tokenizable;
+use serde::de::DeserializeOwned;
 
-use crate::bank::Bank;
+use crate::bank::BankOperationError;
+use crate::domain::requests::session_api::CaptureRequest;
+use crate::domain::requests::session_api::RefundRequest;
+use crate::session::payment::PaymentSession;
 use crate::session::Session;
 use crate::startup::AppState;
 
@@ -20,62 +24,81 @@ use super::html_pages_and_triggers::Credentials;
 pub mod init;
 
 trait WebhookHandler {
+    /// Request body, which carries the webhook fields
+    type Request: DeserializeOwned;
+
+    fn webhook(req: &Self::Request) -> &WebhookRequest;
+
+    /// Reported when the session is unknown or already removed
+    fn session_not_found() -> OperationError {
+        OperationError::SessionNotFound
+    }
+
     async fn handle(
-        bank: Bank,
+        state: AppState,
         session: &Session,
-        req: &WebhookRequest,
+        req: &Self::Request,
     ) -> Result<(), Json<WebhookResponse>>;
 }
 
 struct ConfirmWebhook;
 struct CaptureWebhook;
 struct CancelWebhook;
+struct RefundWebhook;
 
 // ───── Handlers ─────────────────────────────────────────────────────────── //
 
-pub fn session_router() -> Router<AppState> {
+pub fn session_router(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/confirm", routing::post(webhook::<ConfirmWebhook>))
         .route("/capture", routing::post(webhook::<CaptureWebhook>))
         .route("/cancel", routing::post(webhook::<CancelWebhook>))
-        .nest("/init", init_router())
+        .route("/refund", routing::post(webhook::<RefundWebhook>))
+        .nest("/init", init_router(state))
 }
 
 #[tracing::instrument(name = "Webhook request", skip_all, fields(uri=?uri))]
 async fn webhook<T>(
     State(state): State<AppState>,
     uri: Uri,
-    Json(req): Json<WebhookRequest>,
+    Json(req): Json<T::Request>,
 ) -> Result<Json<WebhookResponse>, Json<WebhookResponse>>
 where
     T: WebhookHandler,
 {
+    let session_id = T::webhook(&req).session_id;
     let session = acquire_session(
         &state,
-        req.session_id,
+        session_id,
         WebhookResponse {
-            session_id: req.session_id,
-            status: banksim_api::OperationStatus::Fail(
-                OperationError::SessionNotFound,
-            ),
+            session_id,
+            status: banksim_api::OperationStatus::Fail(T::session_not_found()),
         },
     )?;
 
-    T::handle(state.bank, &session, &req).await?;
+    T::handle(state, &session, &req).await?;
 
-    // Successfully updated session state
+    // Successfully updated session state, result of the operation
+    // will be sent with webhook
     Ok(Json(WebhookResponse {
-        session_id: req.session_id,
+        session_id,
         status: OperationStatus::Success,
     }))
 }
 
 impl WebhookHandler for ConfirmWebhook {
+    type Request = WebhookRequest;
+
+    fn webhook(req: &WebhookRequest) -> &WebhookRequest {
+        req
+    }
+
     async fn handle(
-        bank: Bank,
+        state: AppState,
         session: &Session,
         req: &WebhookRequest,
     ) -> Result<(), Json<WebhookResponse>> {
+        let bank = state.bank;
         match session {
             Session::PaymentSession(p_s) => {
                 use crate::session::payment::Event;
@@ -83,7 +106,7 @@ impl WebhookHandler for ConfirmWebhook {
 
                 let mut guard = p_s.state.lock().await;
                 validate_session(&guard.store_credentials, req).await?;
-                if let Err(e) = match guard.state() {
+                let result = match guard.state() {
                     PaymentState::ReadyToConfirm {} => {
                         guard.handle(&Event::ConfirmRequest).await;
                         Ok(())
@@ -95,12 +118,8 @@ impl WebhookHandler for ConfirmWebhook {
                         Err(OperationStatus::Fail(err.clone()))
                     }
                     _ => Err(OperationStatus::Fail(OperationError::BadRequest)),
-                } {
-                    return Err(Json(WebhookResponse {
-                        session_id: req.session_id,
-                        status: e,
-                    }));
-                }
+                };
+                respond(req.session_id, result)
             }
             Session::CardTokenRegSession(t_s) => {
                 use crate::session::card_token::Event;
@@ -108,7 +127,7 @@ impl WebhookHandler for ConfirmWebhook {
 
                 let mut guard = t_s.state.lock().await;
                 validate_session(&guard.store_credentials, req).await?;
-                if let Err(e) = match guard.state() {
+                let result = match guard.state() {
                     TokenState::ReadyToConfirm {} => {
                         guard.handle(&Event::ConfirmRequest { bank }).await;
                         Ok(())
@@ -120,67 +139,26 @@ impl WebhookHandler for ConfirmWebhook {
                         Err(OperationStatus::Fail(err.clone()))
                     }
                     _ => Err(OperationStatus::Fail(OperationError::BadRequest)),
-                } {
-                    return Err(Json(WebhookResponse {
-                        session_id: req.session_id,
-                        status: e,
-                    }));
-                }
+                };
+                respond(req.session_id, result)
             }
         }
-        Ok(())
     }
 }
 
-impl WebhookHandler for CaptureWebhook {
-    async fn handle(
-        bank: Bank,
-        session: &Session,
-        req: &WebhookRequest,
-    ) -> Result<(), Json<WebhookResponse>> {
-        match session {
-            Session::PaymentSession(p_s) => {
-                use crate::session::payment::Event;
-                use crate::session::payment::State as PaymentState;
+impl WebhookHandler for CancelWebhook {
+    type Request = WebhookRequest;
 
-                let mut guard = p_s.state.lock().await;
-                validate_session(&guard.store_credentials, req).await?;
-                if let Err(e) = match guard.state() {
-                    PaymentState::ReadyToCapture {} => {
-                        guard.handle(&Event::CaptureRequest { bank }).await;
-                        Ok(())
-                    }
-                    PaymentState::Closed { .. } => {
-                        Err(OperationStatus::Fail(OperationError::Cancelled))
-                    }
-                    PaymentState::Failed { err, .. } => {
-                        Err(OperationStatus::Fail(err.clone()))
-                    }
-                    _ => Err(OperationStatus::Fail(OperationError::BadRequest)),
-                } {
-                    return Err(Json(WebhookResponse {
-                        session_id: req.session_id,
-                        status: e,
-                    }));
-                }
-            }
-            Session::CardTokenRegSession(_) => {
-                return Err(Json(WebhookResponse {
-                    session_id: req.session_id,
-                    status: OperationStatus::Fail(OperationError::BadRequest),
-                }));
-            }
-        }
-        Ok(())
+    fn webhook(req: &WebhookRequest) -> &WebhookRequest {
+        req
     }
-}
 
-impl WebhookHandler for CancelWebhook {
     async fn handle(
-        _: Bank,
+        state: AppState,
         session: &Session,
         req: &WebhookRequest,
     ) -> Result<(), Json<WebhookResponse>> {
+        let bank = state.bank;
         match session {
             Session::PaymentSession(p_s) => {
                 use crate::session::payment::Event;
@@ -188,26 +166,23 @@ impl WebhookHandler for CancelWebhook {
 
                 let mut guard = p_s.state.lock().await;
                 validate_session(&guard.store_credentials, req).await?;
-                if let Err(e) = match guard.state() {
+                let result = match guard.state() {
                     PaymentState::Closed { .. } => {
                         Err(OperationStatus::Fail(OperationError::Cancelled))
                     }
                     PaymentState::Failed { err, .. } => {
                         Err(OperationStatus::Fail(err.clone()))
                     }
-                    PaymentState::Successed { .. } => {
+                    PaymentState::Successed { .. }
+                    | PaymentState::Refunded {} => {
                         Err(OperationStatus::Fail(OperationError::BadRequest))
                     }
                     _ => {
-                        guard.handle(&Event::CancelRequest).await;
+                        guard.handle(&Event::CancelRequest { bank }).await;
                         Ok(())
                     }
-                } {
-                    return Err(Json(WebhookResponse {
-                        session_id: req.session_id,
-                        status: e,
-                    }));
-                }
+                };
+                respond(req.session_id, result)
             }
             Session::CardTokenRegSession(t_s) => {
                 use crate::session::card_token::Event;
@@ -215,7 +190,7 @@ impl WebhookHandler for CancelWebhook {
 
                 let mut guard = t_s.state.lock().await;
                 validate_session(&guard.store_credentials, req).await?;
-                if let Err(e) = match guard.state() {
+                let result = match guard.state() {
                     TokenState::Closed { .. } => {
                         Err(OperationStatus::Fail(OperationError::Cancelled))
                     }
@@ -229,20 +204,152 @@ impl WebhookHandler for CancelWebhook {
                         guard.handle(&Event::CancelRequest).await;
                         Ok(())
                     }
-                } {
-                    return Err(Json(WebhookResponse {
-                        session_id: req.session_id,
-                        status: e,
-                    }));
+                };
+                respond(req.session_id, result)
+            }
+        }
+    }
+}
+
+impl WebhookHandler for CaptureWebhook {
+    type Request = CaptureRequest;
+
+    fn webhook(req: &CaptureRequest) -> &WebhookRequest {
+        &req.webhook
+    }
+
+    async fn handle(
+        state: AppState,
+        session: &Session,
+        req: &CaptureRequest,
+    ) -> Result<(), Json<WebhookResponse>> {
+        use crate::session::payment::Event;
+        use crate::session::payment::State as PaymentState;
+
+        let p_s = payment_session(session, &req.webhook)?;
+        let mut guard = p_s.state.lock().await;
+        validate_session(&guard.store_credentials, &req.webhook).await?;
+        let result = match guard.state() {
+            PaymentState::ReadyToCapture {} => {
+                // Fee can't be withheld without terminal settings
+                let terminal = state
+                    .settings
+                    .find_terminal(Some(guard.store_credentials.terminal_key))
+                    .ok_or(OperationError::Failed {
+                        reason: BankOperationError::TerminalNotFound
+                            .str_reason_for_client(),
+                    });
+                match terminal.and_then(|terminal| {
+                    let amount = guard.capture_amount(req.amount)?;
+                    Ok((amount, terminal.fee.fee(amount)))
+                }) {
+                    Ok((amount, fee)) => {
+                        guard
+                            .handle(&Event::CaptureRequest {
+                                bank: state.bank,
+                                amount,
+                                fee,
+                            })
+                            .await;
+                        Ok(())
+                    }
+                    Err(e) => Err(OperationStatus::Fail(e)),
                 }
             }
+            PaymentState::Closed { .. } => {
+                Err(OperationStatus::Fail(OperationError::Cancelled))
+            }
+            PaymentState::Failed { err, .. } => {
+                Err(OperationStatus::Fail(err.clone()))
+            }
+            _ => Err(OperationStatus::Fail(OperationError::BadRequest)),
+        };
+        respond(req.webhook.session_id, result)
+    }
+}
+
+impl WebhookHandler for RefundWebhook {
+    type Request = RefundRequest;
+
+    fn webhook(req: &RefundRequest) -> &WebhookRequest {
+        &req.webhook
+    }
+
+    /// Payment sessions live in memory for an hour after creation, the
+    /// payment can't be refunded after that
+    fn session_not_found() -> OperationError {
+        OperationError::Failed {
+            reason: "refund_window_expired".to_string(),
         }
-        Ok(())
+    }
+
+    async fn handle(
+        state: AppState,
+        session: &Session,
+        req: &RefundRequest,
+    ) -> Result<(), Json<WebhookResponse>> {
+        use crate::session::payment::Event;
+        use crate::session::payment::State as PaymentState;
+
+        let p_s = payment_session(session, &req.webhook)?;
+        let mut guard = p_s.state.lock().await;
+        validate_session(&guard.store_credentials, &req.webhook).await?;
+        let refundable = guard.refundable_amount();
+        let amount = req.amount.unwrap_or(refundable);
+        let result = match guard.state() {
+            PaymentState::Successed { .. } if amount <= 0 => {
+                Err(OperationStatus::Fail(OperationError::BadRequest))
+            }
+            PaymentState::Successed { .. } if amount > refundable => {
+                Err(OperationStatus::Fail(OperationError::Failed {
+                    reason: "refund_exceeds_remaining_amount".to_string(),
+                }))
+            }
+            PaymentState::Successed { .. } => {
+                guard
+                    .handle(&Event::RefundRequest {
+                        bank: state.bank,
+                        amount,
+                    })
+                    .await;
+                Ok(())
+            }
+            PaymentState::Closed { .. } => {
+                Err(OperationStatus::Fail(OperationError::Cancelled))
+            }
+            PaymentState::Failed { err, .. } => {
+                Err(OperationStatus::Fail(err.clone()))
+            }
+            _ => Err(OperationStatus::Fail(OperationError::BadRequest)),
+        };
+        respond(req.webhook.session_id, result)
     }
 }
 
 // ───── Helpers ──────────────────────────────────────────────────────────── //
 
+/// Capture and refund are possible only for the payment
+fn payment_session<'a>(
+    session: &'a Session,
+    req: &WebhookRequest,
+) -> Result<&'a PaymentSession, Json<WebhookResponse>> {
+    match session {
+        Session::PaymentSession(p_s) => Ok(p_s),
+        Session::CardTokenRegSession(_) => Err(Json(WebhookResponse {
+            session_id: req.session_id,
+            status: OperationStatus::Fail(OperationError::BadRequest),
+        })),
+    }
+}
+
+/// Response for the request, which the session can't take in it's state
+fn respond(
+    session_id: uuid::Uuid,
+    result: Result<(), OperationStatus>,
+) -> Result<(), Json<WebhookResponse>> {
+    result.map_err(|status| Json(WebhookResponse { session_id, status }))
+}
+
 /// Try acquire session by id
 fn acquire_session<T>(
     state: &AppState,
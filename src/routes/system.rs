@@ -1,5 +1,6 @@
 use axum::body::Body;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
@@ -13,18 +14,46 @@ use fastwebsockets::OpCode;
 use fastwebsockets::WebSocketError;
 use tokio::sync::TryLockError;
 
+use crate::bank::audit::AuditReport;
+use crate::bank::dump::BankDump;
+use crate::bank::ledger::{InclusionProof, LedgerRoot};
+use crate::bank::loan::LoanSchedule;
+use crate::bank::transfer_order::TransferOrder;
 use crate::bank::BankOperationError;
+use crate::bank::CardExpiry;
 use crate::bank::Transaction;
+use crate::domain::card_number::CardNumber;
 use crate::domain::requests::system_api::AddAccountRequest;
+use crate::domain::requests::system_api::CancelTransferOrderRequest;
+use crate::domain::requests::system_api::CardTokensQuery;
 use crate::domain::requests::system_api::DeleteAccountRequest;
+use crate::domain::requests::system_api::NewCardTokenRequest;
 use crate::domain::requests::system_api::NewTransactionRequest;
+use crate::domain::requests::system_api::NewTransferOrderRequest;
 use crate::domain::requests::system_api::OpenCreditRequest;
+use crate::domain::requests::system_api::OpenLoanRequest;
+use crate::domain::requests::system_api::ReissueCardRequest;
+use crate::domain::requests::system_api::RepayLoanRequest;
+use crate::domain::requests::system_api::RestoreAccountRequest;
+use crate::domain::requests::system_api::RevokeCardTokenRequest;
+use crate::domain::requests::system_api::SetAccountStatusRequest;
+use crate::domain::requests::system_api::SetOverdraftLimitRequest;
+use crate::domain::requests::system_api::StatementRequest;
+use crate::domain::requests::system_api::TerminalQuery;
 use crate::domain::responses::system_api::AddAccountResponse;
 use crate::domain::responses::system_api::ListAccountsResponse;
+use crate::domain::responses::system_api::ListCardTokensResponse;
+use crate::domain::responses::system_api::ListTransferOrdersResponse;
+use crate::domain::responses::system_api::NewCardTokenResponse;
+use crate::domain::responses::system_api::StatementResponse;
+use crate::domain::responses::system_api::TransactionResponse;
 use crate::error_chain_fmt;
 use crate::middleware::BasicAuthLayer;
+use crate::middleware::{system_principal, IdempotencyLayer};
 use crate::startup::AppState;
 
+const DEFAULT_LOAN_PERIOD_SECS: i64 = 30 * 24 * 60 * 60;
+
 // ───── Types ────────────────────────────────────────────────────────────── //
 
 #[derive(thiserror::Error)]
@@ -67,13 +96,49 @@ pub fn system_router(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/account", routing::post(add_account))
         .route("/account", routing::delete(delete_account))
+        .route("/account/restore", routing::post(restore_account))
+        .route("/account/status", routing::post(set_account_status))
+        .route("/card/reissue", routing::post(reissue_card))
+        .route("/token", routing::post(new_card_token))
+        .route("/token/revoke", routing::post(revoke_card_token))
+        .route("/tokens", routing::get(list_card_tokens))
         .route("/list_accounts", routing::get(list_accounts))
-        .route("/credit", routing::post(open_credit))
-        .route("/transaction", routing::post(new_transaction))
+        .route(
+            "/credit",
+            routing::post(open_credit).layer(IdempotencyLayer {
+                state: state.clone(),
+                principal: system_principal,
+            }),
+        )
+        .route(
+            "/transaction",
+            routing::post(new_transaction).layer(IdempotencyLayer {
+                state: state.clone(),
+                principal: system_principal,
+            }),
+        )
+        .route("/transaction/:id", routing::get(get_transaction))
+        .route("/ledger/root", routing::get(ledger_root))
+        .route("/ledger/proof/:id", routing::get(ledger_proof))
+        .route("/audit", routing::get(audit))
+        .route("/export", routing::get(export))
+        .route("/import", routing::post(import))
+        .route("/overdraft_limit", routing::post(set_overdraft_limit))
+        .route("/loan", routing::post(open_loan))
+        .route("/loan/repay", routing::post(repay_loan))
+        .route("/loan/:id", routing::get(loan_schedule))
+        .route("/transfer_order", routing::post(new_transfer_order))
+        .route(
+            "/transfer_order/cancel",
+            routing::post(cancel_transfer_order),
+        )
+        .route("/transfer_orders", routing::get(list_transfer_orders))
         .route("/emission", routing::get(emission))
+        .route("/revenue", routing::get(revenue))
         .route("/store_card", routing::get(store_card))
         .route("/store_balance", routing::get(store_balance))
         .route("/list_transactions", routing::get(list_transactions))
+        .route("/statement", routing::get(account_statement))
         .route("/ws_token", routing::get(get_ws_token))
         .layer(BasicAuthLayer { state })
         .route("/subscribe_on_accounts/:token", routing::get(ws_accounts))
@@ -92,9 +157,28 @@ async fn add_account(
     State(state): State<AppState>,
     Json(req): Json<AddAccountRequest>,
 ) -> Result<Json<AddAccountResponse>, SystemApiError> {
-    let card_number =
-        state.bank.add_account(&req.username, &req.password).await?;
-    Ok(Json(AddAccountResponse { card_number }))
+    let card = state.bank.add_account(&req.username, &req.password).await?;
+    Ok(Json(card.into()))
+}
+
+#[tracing::instrument(name = "Reissue account card", skip_all)]
+async fn reissue_card(
+    State(state): State<AppState>,
+    Json(req): Json<ReissueCardRequest>,
+) -> Result<Json<AddAccountResponse>, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    let expiry = match (req.expiry_month, req.expiry_year) {
+        (Some(month), Some(year)) => Some(CardExpiry::new(month, year)?),
+        (None, None) => None,
+        _ => {
+            return Err(BankOperationError::BadOperation(
+                "Both expiry month and year should be set".to_string(),
+            )
+            .into())
+        }
+    };
+    let card = state.bank.reissue_card(&req.card_number, expiry).await?;
+    Ok(Json(card.into()))
 }
 
 #[tracing::instrument(name = "Delete existing account", skip_all)]
@@ -102,10 +186,66 @@ async fn delete_account(
     State(state): State<AppState>,
     Json(req): Json<DeleteAccountRequest>,
 ) -> Result<StatusCode, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
     state.bank.delete_account(&req.card_number).await?;
     Ok(StatusCode::OK)
 }
 
+#[tracing::instrument(name = "Create card token", skip_all)]
+async fn new_card_token(
+    State(state): State<AppState>,
+    Json(req): Json<NewCardTokenRequest>,
+) -> Result<Json<NewCardTokenResponse>, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    let card_token = state
+        .bank
+        .new_card_token(&req.card_number, req.expires_at, req.terminal_key)
+        .await?;
+    Ok(Json(NewCardTokenResponse { card_token }))
+}
+
+#[tracing::instrument(name = "Revoke card token", skip_all)]
+async fn revoke_card_token(
+    State(state): State<AppState>,
+    Json(req): Json<RevokeCardTokenRequest>,
+) -> Result<StatusCode, SystemApiError> {
+    state.bank.revoke_card_token(&req.card_token).await?;
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "List card tokens of account", skip_all)]
+async fn list_card_tokens(
+    State(state): State<AppState>,
+    Query(query): Query<CardTokensQuery>,
+) -> Result<Json<ListCardTokensResponse>, SystemApiError> {
+    check_cards(&state, [&query.card_number])?;
+    let tokens = state.bank.list_card_tokens(&query.card_number).await?;
+    Ok(Json(ListCardTokensResponse { tokens }))
+}
+
+#[tracing::instrument(name = "Restore deleted account", skip_all)]
+async fn restore_account(
+    State(state): State<AppState>,
+    Json(req): Json<RestoreAccountRequest>,
+) -> Result<StatusCode, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    state.bank.restore_account(&req.card_number).await?;
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "Change account status", skip_all)]
+async fn set_account_status(
+    State(state): State<AppState>,
+    Json(req): Json<SetAccountStatusRequest>,
+) -> Result<StatusCode, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    state
+        .bank
+        .set_account_status(&req.card_number, req.status, req.reason.as_deref())
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[tracing::instrument(name = "List info about accounts", skip_all)]
 async fn list_accounts(
     State(state): State<AppState>,
@@ -118,8 +258,99 @@ async fn list_accounts(
 async fn open_credit(
     State(state): State<AppState>,
     Json(req): Json<OpenCreditRequest>,
+) -> Result<Json<TransactionResponse>, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    let transaction_id =
+        state.bank.open_credit(&req.card_number, req.amount).await?;
+    Ok(Json(TransactionResponse { transaction_id }))
+}
+
+#[tracing::instrument(name = "Open a new loan", skip_all)]
+async fn open_loan(
+    State(state): State<AppState>,
+    Json(req): Json<OpenLoanRequest>,
+) -> Result<Json<LoanSchedule>, SystemApiError> {
+    check_cards(&state, [&req.card_number])?;
+    let period = time::Duration::seconds(
+        req.period_secs.unwrap_or(DEFAULT_LOAN_PERIOD_SECS),
+    );
+    let loan = state
+        .bank
+        .open_loan(
+            &req.card_number,
+            req.principal,
+            req.interest_rate,
+            req.term,
+            period,
+        )
+        .await?;
+    Ok(Json(loan.schedule(time::OffsetDateTime::now_utc())?))
+}
+
+#[tracing::instrument(name = "Create a transfer order", skip_all)]
+async fn new_transfer_order(
+    State(state): State<AppState>,
+    Json(req): Json<NewTransferOrderRequest>,
+) -> Result<Json<TransferOrder>, SystemApiError> {
+    check_cards(&state, [&req.sender, &req.recipient])?;
+    let order = state
+        .bank
+        .new_transfer_order(
+            &req.sender,
+            &req.recipient,
+            req.amount,
+            req.schedule,
+        )
+        .await?;
+    Ok(Json(order))
+}
+
+#[tracing::instrument(name = "Cancel a transfer order", skip_all)]
+async fn cancel_transfer_order(
+    State(state): State<AppState>,
+    Json(req): Json<CancelTransferOrderRequest>,
+) -> Result<StatusCode, SystemApiError> {
+    state.bank.cancel_transfer_order(&req.order_id).await?;
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "List transfer orders", skip_all)]
+async fn list_transfer_orders(
+    State(state): State<AppState>,
+) -> Result<Json<ListTransferOrdersResponse>, SystemApiError> {
+    let orders = state.bank.list_transfer_orders().await?;
+    Ok(Json(ListTransferOrdersResponse { orders }))
+}
+
+#[tracing::instrument(name = "Repay a loan", skip_all)]
+async fn repay_loan(
+    State(state): State<AppState>,
+    Json(req): Json<RepayLoanRequest>,
+) -> Result<Json<TransactionResponse>, SystemApiError> {
+    let transaction_id =
+        state.bank.repay_loan(&req.loan_id, req.amount).await?;
+    Ok(Json(TransactionResponse { transaction_id }))
+}
+
+#[tracing::instrument(name = "Get loan schedule", skip_all)]
+async fn loan_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<LoanSchedule>, SystemApiError> {
+    let loan = state.bank.get_loan(&id).await?;
+    Ok(Json(loan.schedule(time::OffsetDateTime::now_utc())?))
+}
+
+#[tracing::instrument(name = "Set account overdraft limit", skip_all)]
+async fn set_overdraft_limit(
+    State(state): State<AppState>,
+    Json(req): Json<SetOverdraftLimitRequest>,
 ) -> Result<StatusCode, SystemApiError> {
-    state.bank.open_credit(&req.card_number, req.amount).await?;
+    check_cards(&state, [&req.card_number])?;
+    state
+        .bank
+        .set_overdraft_limit(&req.card_number, req.limit)
+        .await?;
     Ok(StatusCode::OK)
 }
 
@@ -127,11 +358,58 @@ async fn open_credit(
 async fn new_transaction(
     State(state): State<AppState>,
     Json(req): Json<NewTransactionRequest>,
-) -> Result<StatusCode, SystemApiError> {
-    state
+) -> Result<Json<TransactionResponse>, SystemApiError> {
+    check_cards(&state, [&req.from, &req.to])?;
+    let transaction_id = state
         .bank
         .new_transaction(&req.from, &req.to, req.amount)
         .await?;
+    Ok(Json(TransactionResponse { transaction_id }))
+}
+
+#[tracing::instrument(name = "Get transaction by id", skip_all)]
+async fn get_transaction(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<Transaction>, SystemApiError> {
+    Ok(Json(state.bank.get_transaction(&id).await?))
+}
+
+#[tracing::instrument(name = "Get ledger root", skip_all)]
+async fn ledger_root(
+    State(state): State<AppState>,
+) -> Result<Json<LedgerRoot>, SystemApiError> {
+    Ok(Json(state.bank.ledger_root().await?))
+}
+
+#[tracing::instrument(name = "Get transaction inclusion proof", skip_all)]
+async fn ledger_proof(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<InclusionProof>, SystemApiError> {
+    Ok(Json(state.bank.ledger_proof(&id).await?))
+}
+
+#[tracing::instrument(name = "Audit bank consistency", skip_all)]
+async fn audit(
+    State(state): State<AppState>,
+) -> Result<Json<AuditReport>, SystemApiError> {
+    Ok(Json(state.bank.audit().await?))
+}
+
+#[tracing::instrument(name = "Export bank state", skip_all)]
+async fn export(
+    State(state): State<AppState>,
+) -> Result<Json<BankDump>, SystemApiError> {
+    Ok(Json(state.bank.export_dump().await?))
+}
+
+#[tracing::instrument(name = "Import bank state", skip_all)]
+async fn import(
+    State(state): State<AppState>,
+    Json(dump): Json<BankDump>,
+) -> Result<StatusCode, SystemApiError> {
+    state.bank.import(&dump).await?;
     Ok(StatusCode::OK)
 }
 
@@ -142,6 +420,18 @@ async fn list_transactions(
     Ok(Json(state.bank.list_transactions().await?))
 }
 
+#[tracing::instrument(name = "Get account statement page", skip_all)]
+async fn account_statement(
+    State(state): State<AppState>,
+    Query(req): Query<StatementRequest>,
+) -> Result<Json<StatementResponse>, SystemApiError> {
+    check_cards(
+        &state,
+        std::iter::once(&req.card_number).chain(&req.counterparty),
+    )?;
+    Ok(Json(state.bank.account_statement(&req).await?))
+}
+
 #[tracing::instrument(name = "Get bank emission", skip_all)]
 async fn emission(
     State(state): State<AppState>,
@@ -149,20 +439,35 @@ async fn emission(
     Ok(state.bank.bank_emission().await?.to_string())
 }
 
+#[tracing::instrument(name = "Get bank revenue", skip_all)]
+async fn revenue(
+    State(state): State<AppState>,
+) -> Result<String, SystemApiError> {
+    Ok(state.bank.bank_revenue().await?.to_string())
+}
+
 #[tracing::instrument(name = "Get store balance", skip_all)]
 async fn store_balance(
     State(state): State<AppState>,
+    Query(query): Query<TerminalQuery>,
 ) -> Result<String, SystemApiError> {
-    Ok(state.bank.store_balance().await?.to_string())
+    let terminal_key = query
+        .terminal_key
+        .unwrap_or(state.settings.terminal_settings.terminal_key);
+    Ok(state.bank.store_balance(&terminal_key).await?.to_string())
 }
 
 #[tracing::instrument(name = "Get store card number", skip_all)]
 async fn store_card(
     State(state): State<AppState>,
+    Query(query): Query<TerminalQuery>,
 ) -> Result<String, SystemApiError> {
+    let terminal_key = query
+        .terminal_key
+        .unwrap_or(state.settings.terminal_settings.terminal_key);
     Ok(state
         .bank
-        .get_store_account()
+        .get_store_account(&terminal_key)
         .await?
         .card()
         .as_ref()
@@ -207,6 +512,16 @@ async fn ws_traces(
 
 // ───── Functions ────────────────────────────────────────────────────────── //
 
+/// Card numbers from requests should pass the Luhn check, if it is enabled
+fn check_cards<'a>(
+    state: &AppState,
+    cards: impl IntoIterator<Item = &'a CardNumber>,
+) -> Result<(), BankOperationError> {
+    cards
+        .into_iter()
+        .try_for_each(|card| card.check_luhn(&state.settings.card_settings))
+}
+
 async fn handle_accounts_subscriber(
     state: AppState,
     fut: upgrade::UpgradeFut,
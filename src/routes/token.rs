@@ -2,49 +2,262 @@ use std::time::Duration;
 
 use askama::Template;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::Html;
 use axum::{routing, Json, Router};
-use banksim_api::token_info::{self, TokenInfoRequest, TokenInfoResponse};
+use banksim_api::token_info::{self, TokenInfoRequest};
 use banksim_api::Tokenizable;
+use banksim_api::{OperationError, OperationStatus};
 use futures::FutureExt;
 use secrecy::Secret;
 use serde::Deserialize;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::bank::BankOperationError;
 use crate::domain::card_number::CardNumber;
+use crate::domain::requests::session_api::{
+    ChargeTokenRequest, RevokeTokenRequest, TerminalRequest,
+};
+use crate::domain::responses::session_api::{
+    self, ChargeTokenResponse, TokenCardInfo, TokenInfoResponse,
+};
 use crate::html_gen::{SubmitCardNumberPage, SubmitPaymentPage};
+use crate::middleware::{
+    terminal_authentication, terminal_principal, IdempotencyLayer,
+};
+use crate::session::call_webhook;
 use crate::startup::AppState;
 
 // ───── Handlers ─────────────────────────────────────────────────────────── //
 
-pub fn token_router() -> Router<AppState> {
-    Router::new().route("/info", routing::get(get_token_info))
+pub fn token_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/info", routing::get(get_token_info))
+        .route("/revoke", routing::post(revoke_token))
+        .route(
+            "/charge",
+            routing::post(charge_token).layer(IdempotencyLayer {
+                state,
+                principal: terminal_principal,
+            }),
+        )
+}
+
+/// Debit the tokenized card without payer's interaction, terminal is
+/// authorized with 'Basic' auth. `PaymentFinished` notification is sent
+/// like for the interactive payment.
+#[tracing::instrument(skip_all)]
+async fn charge_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ChargeTokenRequest>,
+) -> Result<Json<ChargeTokenResponse>, (StatusCode, String)> {
+    let terminal = match terminal_authentication(&state.settings, &headers) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            tracing::warn!("Failed to authorize terminal: {e}");
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "not_authorized".to_string(),
+            ));
+        }
+    };
+    let store_card =
+        match state.bank.get_store_account(&terminal.terminal_key).await {
+            Ok(acc) => acc.card(),
+            Err(e) => {
+                tracing::error!("Failed to get store account: {e}");
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal_error".to_string(),
+                ));
+            }
+        };
+
+    let session_id = Uuid::new_v4();
+    let fee = terminal.fee.fee(req.amount);
+    let result =
+        charge(&state, &terminal.terminal_key, &store_card, &req, fee).await;
+    if let Err(ref e) = result {
+        tracing::error!("Failed to charge card token: {e}");
+    }
+    let status = || match result {
+        Ok(_) => OperationStatus::Success,
+        Err(ref e) => OperationStatus::Fail(OperationError::Failed {
+            reason: e.str_reason_for_client(),
+        }),
+    };
+
+    let fut = call_webhook(
+        session_api::Notification::PaymentNotification(
+            session_api::PaymentNotification::PaymentFinished {
+                session_id,
+                status: status(),
+                captured_amount: result.as_ref().ok().map(|_| req.amount),
+                fee: result.as_ref().ok().map(|_| fee),
+            },
+        ),
+        req.notification_url.clone(),
+        state.http_client.clone(),
+    );
+    // Run with delay
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        fut.await
+    });
+
+    Ok(Json(ChargeTokenResponse {
+        session_id,
+        status: status(),
+        transaction_ids: result.unwrap_or_default(),
+    }))
+}
+
+/// Move money from the token's account to the store or beneficiaries,
+/// acquiring `fee` goes to the bank
+async fn charge(
+    state: &AppState,
+    terminal_key: &Uuid,
+    store_card: &CardNumber,
+    req: &ChargeTokenRequest,
+    fee: i64,
+) -> Result<Vec<Uuid>, BankOperationError> {
+    check_token_terminal(state, &req.card_token, terminal_key).await?;
+    let payer = state.bank.get_account_by_token(&req.card_token).await?;
+    if !payer.is_existing {
+        return Err(BankOperationError::AccountIsDeleted);
+    }
+    let payer_card = payer.card();
+    if let Some(expiry) = state.bank.card_expiry(&payer_card).await? {
+        if expiry.is_expired(OffsetDateTime::now_utc()) {
+            return Err(BankOperationError::CardExpired);
+        }
+    }
+    match req.beneficiaries {
+        Some(ref beneficiaries) if !beneficiaries.is_empty() => {
+            state
+                .bank
+                .new_split_transaction(
+                    &payer_card,
+                    req.amount,
+                    fee,
+                    beneficiaries,
+                )
+                .await
+        }
+        _ => state
+            .bank
+            .new_payment(&payer_card, store_card, req.amount, fee)
+            .await
+            .map(|id| vec![id]),
+    }
+}
+
+/// Tokens of other terminals look like missing ones
+async fn check_token_terminal(
+    state: &AppState,
+    token: &str,
+    terminal_key: &Uuid,
+) -> Result<(), BankOperationError> {
+    let token = state.bank.get_card_token(token).await?;
+    if token.terminal_key.as_ref() != Some(terminal_key) {
+        return Err(BankOperationError::TokenNotFound);
+    }
+    Ok(())
+}
+
+/// Unlink card from the store, terminal is authorized with 'Basic' auth and
+/// can revoke only tokens issued through it
+#[tracing::instrument(skip_all)]
+async fn revoke_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RevokeTokenRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let terminal = match terminal_authentication(&state.settings, &headers) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            tracing::warn!("Failed to authorize terminal: {e}");
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "not_authorized".to_string(),
+            ));
+        }
+    };
+    match revoke(&state, &terminal.terminal_key, &req.card_token).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(e) => {
+            tracing::error!("Failed to revoke card token: {e}");
+            Err((StatusCode::BAD_REQUEST, e.str_reason_for_client()))
+        }
+    }
+}
+
+async fn revoke(
+    state: &AppState,
+    terminal_key: &Uuid,
+    token: &str,
+) -> Result<(), BankOperationError> {
+    check_token_terminal(state, token, terminal_key).await?;
+    state.bank.revoke_card_token(token).await
 }
 
 #[tracing::instrument(skip_all)]
 async fn get_token_info(
     State(state): State<AppState>,
-    Json(req): Json<TokenInfoRequest>,
+    Json(TerminalRequest { terminal_key, req }): Json<
+        TerminalRequest<TokenInfoRequest>,
+    >,
 ) -> Json<TokenInfoResponse> {
-    let password = &state.settings.terminal_settings.password;
-    if req.validate_token(password).is_err() {
+    let Some(terminal) = state.settings.find_terminal(terminal_key) else {
+        return Json(TokenInfoResponse::err("Not authorized request"));
+    };
+    if req.validate_token(&terminal.password).is_err() {
+        return Json(TokenInfoResponse::err("Not authorized request"));
+    }
+    let acc = match state.bank.get_account_by_token(&req.card_token).await {
+        Ok(acc) => acc,
+        Err(e) => {
+            tracing::warn!("Failed to get account by token: {e}");
+            return Json(TokenInfoResponse::err("No token found"));
+        }
+    };
+    if !acc.is_existing {
         return Json(TokenInfoResponse {
-            status: Err("Not authorized request".to_string()),
+            response: token_info::TokenInfoResponse { status: Ok(false) },
+            card: None,
         });
     }
-    Json(
-        match state.bank.get_account_by_token(&req.card_token).await {
-            Ok(acc) => {
-                if acc.is_existing {
-                    TokenInfoResponse { status: Ok(true) }
-                } else {
-                    TokenInfoResponse { status: Ok(false) }
-                }
-            }
-            Err(e) => TokenInfoResponse {
-                status: Err("No token found".to_string()),
-            },
-        },
-    )
+    match token_card_info(&state, &acc.card(), &req.card_token).await {
+        Ok(card) => Json(TokenInfoResponse {
+            response: token_info::TokenInfoResponse { status: Ok(true) },
+            card: Some(card),
+        }),
+        Err(e) => {
+            tracing::error!("Failed to get token card info: {e}");
+            Json(TokenInfoResponse::err("No token found"))
+        }
+    }
+}
+
+async fn token_card_info(
+    state: &AppState,
+    card: &CardNumber,
+    token: &str,
+) -> Result<TokenCardInfo, BankOperationError> {
+    let token_created_at = state
+        .bank
+        .list_card_tokens(card)
+        .await?
+        .into_iter()
+        .find(|t| t.token.eq(token))
+        .ok_or(BankOperationError::TokenNotFound)?
+        .created_at;
+    Ok(TokenCardInfo {
+        masked_card_number: card.masked(),
+        network: card.network(),
+        expiry: state.bank.card_expiry(card).await?,
+        token_created_at,
+    })
 }
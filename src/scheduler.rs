@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use crate::bank::transfer_order::OrderExecution;
+use crate::bank::{Bank, BankOperationError};
+
+/// How often due transfer orders are checked
+const TICK: Duration = Duration::from_secs(1);
+
+/// Launch task, which runs scheduled transfer orders
+pub fn run_transfer_orders(bank: Bank) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            let now = OffsetDateTime::now_utc();
+            if let Err(e) = execute_due_orders(&bank, now).await {
+                tracing::error!("Failed to execute transfer orders: {e}");
+            }
+        }
+    });
+}
+
+/// Run each order, which should run at `now`. The backend performs the
+/// transaction and records it's outcome at once, one broken order
+/// doesn't stop the others.
+pub async fn execute_due_orders(
+    bank: &Bank,
+    now: OffsetDateTime,
+) -> Result<(), BankOperationError> {
+    for order in bank.due_transfer_orders(now).await? {
+        match bank.run_transfer_order(&order.id, now).await {
+            Ok(Some(OrderExecution {
+                error: Some(error), ..
+            })) => {
+                tracing::warn!("Transfer order {} failed: {error}", order.id);
+            }
+            Ok(_) => (),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to run transfer order {}: {e}",
+                    order.id
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -112,19 +112,18 @@ impl Inner {
                     }
                 };
 
-                // Check store account
-                // We have only one store account in our virtual bank
-                let store_card = match bank.get_store_account().await {
-                    Ok(acc) => acc.card(),
+                // Check store account of the session's terminal
+                let store_is_active = match bank
+                    .find_account(&self.store_credentials.card_number)
+                    .await
+                {
+                    Ok(acc) => acc.is_existing,
                     Err(e) => {
                         tracing::error!("Failed to get store account: {e}");
-                        return Response::Transition(State::failed(
-                            self.req.fail_url.to_string(),
-                            OperationError::NotAuthorizedRequest,
-                        ));
+                        false
                     }
                 };
-                if !store_card.eq(&self.store_credentials.card_number) {
+                if !store_is_active {
                     tracing::error!(
                         "Failed to reg token: wrong store account!"
                     );
@@ -166,7 +165,14 @@ impl Inner {
         match event {
             Event::ConfirmRequest { bank } => {
                 let card_for_reg = self.card_for_reg.as_ref().unwrap();
-                let token = match bank.new_card_token(card_for_reg).await {
+                let token = match bank
+                    .new_card_token(
+                        card_for_reg,
+                        None,
+                        Some(self.store_credentials.terminal_key),
+                    )
+                    .await
+                {
                     Ok(t) => t,
                     Err(e) => {
                         tracing::error!(
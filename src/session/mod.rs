@@ -7,6 +7,7 @@ use serde::Serialize;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::bank::Bank;
 use crate::routes::html_pages_and_triggers::Credentials;
 use crate::{error_chain_fmt, RemovableById};
 
@@ -105,6 +106,27 @@ impl Session {
             Session::CardTokenRegSession(s) => Some(s),
         }
     }
+
+    /// Close the session, which is not finished in time,
+    /// payment session releases payer's funds
+    pub async fn expire(&self, bank: &Bank) {
+        match self {
+            Session::PaymentSession(s) => {
+                s.state
+                    .lock()
+                    .await
+                    .handle(&payment::Event::Timeout { bank: bank.clone() })
+                    .await
+            }
+            Session::CardTokenRegSession(s) => {
+                s.state
+                    .lock()
+                    .await
+                    .handle(&card_token::Event::Timeout)
+                    .await
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -135,6 +157,16 @@ impl InteractionSessions {
         }
     }
 
+    pub async fn expire_session_by_id(
+        &self,
+        id: Uuid,
+        bank: &Bank,
+    ) -> Result<(), SessionError> {
+        let session = self.try_acquire_session_by_id(id)?;
+        session.expire(bank).await;
+        Ok(())
+    }
+
     pub fn remove_session_by_id(
         &mut self,
         id: Uuid,
@@ -205,7 +237,7 @@ impl IntoSession for RegisterCardTokenRequest {
     }
 }
 
-async fn call_webhook<T: Serialize + 'static>(
+pub(crate) async fn call_webhook<T: Serialize + 'static>(
     notification: T,
     notification_url: url::Url,
     http_client: reqwest::Client,
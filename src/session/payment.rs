@@ -13,7 +13,9 @@ use tokio::sync::watch::Sender;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::bank::{refund_fee, BankOperationError, CardExpiry};
 use crate::domain::card_number::CardNumber;
+use crate::domain::responses::session_api;
 use crate::routes::html_pages_and_triggers::Credentials;
 
 use super::call_webhook;
@@ -42,6 +44,10 @@ impl PaymentSession {
                 http_client,
                 state_finale_notifier: tx,
                 payer_card: None,
+                hold: None,
+                captured: None,
+                fee: None,
+                refunded: 0,
                 session_watcher_notifier: Some(session_watcher_notifier),
                 id,
             }
@@ -72,20 +78,73 @@ pub struct Inner {
     pub state_finale_notifier: Sender<State>,
     pub session_watcher_notifier: Option<tokio::sync::oneshot::Sender<()>>,
     http_client: reqwest::Client,
+    /// Amount which was actually moved on capture,
+    /// can be less than authorized `req.amount`
+    pub captured: Option<i64>,
+    /// Acquiring fee, withheld on capture
+    pub fee: Option<i64>,
     payer_card: Option<CardNumber>,
+    /// Payer's funds reserved until capture
+    hold: Option<Uuid>,
+    refunded: i64,
+}
+
+impl Inner {
+    /// Amount of captured money which is not refunded yet
+    pub fn refundable_amount(&self) -> i64 {
+        self.captured.unwrap_or(0) - self.refunded
+    }
+
+    /// Amount to capture, the whole authorized amount if not requested,
+    /// capture can't exceed it
+    pub fn capture_amount(
+        &self,
+        requested: Option<i64>,
+    ) -> Result<i64, OperationError> {
+        let amount = requested.unwrap_or(self.req.amount);
+        if amount <= 0 {
+            Err(OperationError::BadRequest)
+        } else if amount > self.req.amount {
+            Err(OperationError::Failed {
+                reason: "capture_exceeds_authorized_amount".to_string(),
+            })
+        } else {
+            Ok(amount)
+        }
+    }
+
+    /// Return reserved funds to the payer, if any
+    async fn release_hold(&mut self, bank: &crate::bank::Bank) {
+        if let Some(hold) = self.hold.take() {
+            if let Err(e) = bank.release_hold(hold).await {
+                tracing::error!("Failed to release funds hold: {e}");
+            }
+        }
+    }
 }
 
 pub enum Event {
     Submit {
         bank: crate::bank::Bank,
-        creds: crate::routes::html_pages_and_triggers::Credentials,
+        creds: crate::routes::html_pages_and_triggers::CardCredentials,
+    },
+    Timeout {
+        bank: crate::bank::Bank,
     },
-    Timeout,
     ConfirmRequest,
     CaptureRequest {
         bank: crate::bank::Bank,
+        amount: i64,
+        /// Acquiring fee, withheld from the captured amount
+        fee: i64,
+    },
+    CancelRequest {
+        bank: crate::bank::Bank,
+    },
+    RefundRequest {
+        bank: crate::bank::Bank,
+        amount: i64,
     },
-    CancelRequest,
 }
 
 #[allow(unused_variables)]
@@ -99,13 +158,36 @@ impl Inner {
     async fn init(&mut self, event: &Event) -> Response<State> {
         match event {
             Event::Submit { bank, creds } => {
-                // Authorize payer's card and password
+                // Authorize payer's card details and password
+                let expiry = CardExpiry {
+                    month: creds.expiry_month,
+                    year: creds.expiry_year,
+                };
                 let payer_card = match bank
-                    .authorize_account(&creds.card_number, &creds.password)
+                    .authorize_card(
+                        &creds.card_number,
+                        &creds.password,
+                        expiry,
+                        &creds.cvv,
+                    )
                     .await
                 {
                     // Authorized
                     Ok(acc) => acc.card(),
+                    // Wrong or expired card details
+                    Err(
+                        e @ (BankOperationError::CardExpired
+                        | BankOperationError::InvalidExpiry
+                        | BankOperationError::InvalidCvv),
+                    ) => {
+                        tracing::error!("Can't authorize card: {e}");
+                        return Response::Transition(State::failed(
+                            self.req.fail_url.to_string(),
+                            OperationError::Failed {
+                                reason: e.str_reason_for_client(),
+                            },
+                        ));
+                    }
                     Err(e) => {
                         // Not authorized
                         tracing::error!("Can't authorize account: {e:?}");
@@ -116,19 +198,18 @@ impl Inner {
                     }
                 };
 
-                // Check store account
-                // We have only one store account in our virtual bank
-                let store_card = match bank.get_store_account().await {
-                    Ok(acc) => acc.card(),
+                // Check store account of the session's terminal
+                let store_is_active = match bank
+                    .find_account(&self.store_credentials.card_number)
+                    .await
+                {
+                    Ok(acc) => acc.is_existing,
                     Err(e) => {
                         tracing::error!("Failed to get store account: {e}");
-                        return Response::Transition(State::failed(
-                            self.req.fail_url.to_string(),
-                            OperationError::NotAuthorizedRequest,
-                        ));
+                        false
                     }
                 };
-                if !store_card.eq(&self.store_credentials.card_number) {
+                if !store_is_active {
                     tracing::error!(
                         "Failed to perform payment: wrong store account!"
                     );
@@ -138,7 +219,40 @@ impl Inner {
                     ));
                 }
 
+                // Frozen payer can't send, blocked store can't receive money
+                let status_check = async {
+                    bank.account_status(&payer_card).await?.check_can_send()?;
+                    bank.account_status(&self.store_credentials.card_number)
+                        .await?
+                        .check_can_receive()
+                };
+                if let Err(e) = status_check.await {
+                    tracing::error!("Failed to perform payment: {e}");
+                    return Response::Transition(State::failed(
+                        self.req.fail_url.to_string(),
+                        OperationError::Failed {
+                            reason: e.str_reason_for_client(),
+                        },
+                    ));
+                }
+
+                // Reserve payer's funds until capture
+                let hold =
+                    match bank.new_hold(&payer_card, self.req.amount).await {
+                        Ok(hold) => hold,
+                        Err(e) => {
+                            tracing::error!("Failed to hold funds: {e}");
+                            return Response::Transition(State::failed(
+                                self.req.fail_url.to_string(),
+                                OperationError::Failed {
+                                    reason: e.str_reason_for_client(),
+                                },
+                            ));
+                        }
+                    };
+
                 self.payer_card = Some(payer_card);
+                self.hold = Some(hold);
 
                 // Webhook future
                 let fut = call_webhook(
@@ -157,7 +271,7 @@ impl Inner {
                 });
                 Response::Transition(State::ready_to_confirm())
             }
-            Event::Timeout => Response::Transition(State::closed(
+            Event::Timeout { .. } => Response::Transition(State::closed(
                 self.req.fail_url.to_string(),
             )),
             _ => Response::Handled,
@@ -165,7 +279,7 @@ impl Inner {
     }
 
     #[state]
-    async fn ready_to_confirm(&self, event: &Event) -> Response<State> {
+    async fn ready_to_confirm(&mut self, event: &Event) -> Response<State> {
         match event {
             Event::ConfirmRequest => {
                 let fut = call_webhook(
@@ -184,40 +298,51 @@ impl Inner {
                 });
                 Response::Transition(State::ready_to_capture())
             }
-            Event::Timeout | Event::CancelRequest => Response::Transition(
-                State::closed(self.req.fail_url.to_string()),
-            ),
+            Event::Timeout { bank } | Event::CancelRequest { bank } => {
+                self.release_hold(bank).await;
+                Response::Transition(State::closed(
+                    self.req.fail_url.to_string(),
+                ))
+            }
             _ => Response::Handled,
         }
     }
 
     #[state]
-    async fn ready_to_capture(&self, event: &Event) -> Response<State> {
+    async fn ready_to_capture(&mut self, event: &Event) -> Response<State> {
         match event {
-            Event::CaptureRequest { bank } => {
-                let payer_card = self.payer_card.as_ref().unwrap();
-                // Perform transaction
+            Event::CaptureRequest { bank, amount, fee } => {
+                let hold = self.hold.unwrap();
+                // Turn hold into the real transaction
                 let result = if self.req.beneficiaries.is_empty() {
-                    bank.new_transaction(
-                        payer_card,
+                    bank.capture_hold(
+                        hold,
                         &self.store_credentials.card_number,
-                        self.req.amount,
+                        *amount,
+                        *fee,
                     )
                     .await
                 } else {
-                    bank.new_split_transaction(
-                        &payer_card,
-                        self.req.amount,
+                    bank.capture_split_hold(
+                        hold,
+                        *amount,
+                        *fee,
                         &self.req.beneficiaries,
                     )
                     .await
                 };
                 match result {
-                    Ok(()) => Response::Transition(State::successed(
-                        self.req.success_url.to_string(),
-                    )),
+                    Ok(()) => {
+                        self.hold = None;
+                        self.captured = Some(*amount);
+                        self.fee = Some(*fee);
+                        Response::Transition(State::successed(
+                            self.req.success_url.to_string(),
+                        ))
+                    }
                     Err(e) => {
                         tracing::error!("Transaction failed: {e}");
+                        self.release_hold(bank).await;
                         Response::Transition(State::failed(
                             self.req.fail_url.to_string(),
                             OperationError::Failed {
@@ -227,9 +352,12 @@ impl Inner {
                     }
                 }
             }
-            Event::Timeout | Event::CancelRequest => Response::Transition(
-                State::closed(self.req.fail_url.to_string()),
-            ),
+            Event::Timeout { bank } | Event::CancelRequest { bank } => {
+                self.release_hold(bank).await;
+                Response::Transition(State::closed(
+                    self.req.fail_url.to_string(),
+                ))
+            }
             _ => Response::Handled,
         }
     }
@@ -245,7 +373,80 @@ impl Inner {
     }
 
     #[state]
-    fn successed(redirect_url: &String) -> Response<State> {
+    async fn successed(
+        &mut self,
+        redirect_url: &String,
+        event: &Event,
+    ) -> Response<State> {
+        match event {
+            Event::RefundRequest { bank, amount } => {
+                let payer_card = self.payer_card.as_ref().unwrap();
+                let fee = refund_fee(
+                    self.captured.unwrap_or(0),
+                    self.fee.unwrap_or(0),
+                    self.refunded,
+                    *amount,
+                );
+                // Perform reversing transaction
+                let result = if self.req.beneficiaries.is_empty() {
+                    bank.new_refund(
+                        &self.store_credentials.card_number,
+                        payer_card,
+                        *amount,
+                        fee,
+                    )
+                    .await
+                } else {
+                    bank.new_split_refund_transaction(
+                        payer_card,
+                        *amount,
+                        fee,
+                        &self.req.beneficiaries,
+                    )
+                    .await
+                };
+                let status = match result {
+                    Ok(()) => {
+                        self.refunded += amount;
+                        OperationStatus::Success
+                    }
+                    Err(e) => {
+                        tracing::error!("Refund transaction failed: {e}");
+                        OperationStatus::Fail(OperationError::Failed {
+                            reason: e.str_reason_for_client(),
+                        })
+                    }
+                };
+
+                let fut = call_webhook(
+                    session_api::Notification::RefundNotification(
+                        session_api::RefundNotification::RefundFinished {
+                            session_id: self.id,
+                            amount: *amount,
+                            status,
+                        },
+                    ),
+                    self.req.notification_url.clone(),
+                    self.http_client.clone(),
+                );
+                // Run with delay
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    fut.await
+                });
+
+                if self.refundable_amount() == 0 {
+                    Response::Transition(State::refunded())
+                } else {
+                    Response::Handled
+                }
+            }
+            _ => Response::Handled,
+        }
+    }
+
+    #[state]
+    fn refunded() -> Response<State> {
         Response::Handled
     }
 
@@ -259,6 +460,11 @@ impl Inner {
             State::Successed { .. } => OperationStatus::Success,
             State::Closed { .. } => OperationStatus::Cancel,
             State::Failed { err, .. } => OperationStatus::Fail(err.clone()),
+            // Refund webhook is already sent, just release the session
+            State::Refunded {} => {
+                self.notify(target.clone());
+                return;
+            }
             _ => return,
         };
         self.notify(target.clone());
@@ -267,13 +473,17 @@ impl Inner {
         let id = self.id;
         let url = self.req.notification_url.clone();
         let client = self.http_client.clone();
+        let captured_amount = self.captured;
+        let fee = self.fee;
         tokio::spawn(async move {
             tokio::time::sleep(Duration::from_secs(1)).await;
             call_webhook(
-                Notification::PaymentNotification(
-                    PaymentNotification::PaymentFinished {
+                session_api::Notification::PaymentNotification(
+                    session_api::PaymentNotification::PaymentFinished {
                         session_id: id,
                         status,
+                        captured_amount,
+                        fee,
                     },
                 ),
                 url,
@@ -284,13 +494,21 @@ impl Inner {
     }
 
     fn notify(&mut self, state: State) {
+        // Successful payment can be refunded later,
+        // so keep the session until it expires
+        let keep_session = matches!(state, State::Successed { .. });
         if let Err(e) = self.state_finale_notifier.send(state) {
             tracing::error!(
                 "Failed to send notification about finale state: {e}"
             );
         }
-        if let Err(e) = self.session_watcher_notifier.take().unwrap().send(()) {
-            tracing::error!("Failed to send notification about finale state to task watcher");
+        if keep_session {
+            return;
+        }
+        if let Some(notifier) = self.session_watcher_notifier.take() {
+            if let Err(e) = notifier.send(()) {
+                tracing::error!("Failed to send notification about finale state to task watcher");
+            }
         }
     }
 }
@@ -13,6 +13,7 @@ use tower_http::services::ServeDir;
 use crate::routes::html_pages_and_triggers::pages_and_triggers_router;
 use crate::routes::session::session_router;
 use crate::routes::token::token_router;
+use crate::scheduler;
 use crate::session::InteractionSessions;
 use crate::ws_tracing_subscriber::WebSocketAppender;
 use crate::{bank::Bank, config::Settings, routes::system::system_router};
@@ -22,6 +23,7 @@ type Server = Serve<IntoMakeService<Router>, Router>;
 pub struct Application {
     _port: u16,
     server: Server,
+    bank: Bank,
 }
 
 #[derive(Clone)]
@@ -52,10 +54,21 @@ impl Application {
             crate::config::DataBackendType::Mem => {
                 Bank::new::<crate::bank::memory::MemoryStorage>(&config)
             }
+            crate::config::DataBackendType::Sqlite => {
+                Bank::new::<crate::bank::sqlite::SqliteStorage>(&config)
+            }
         };
 
+        // Payment sessions are lost on restart, so are their holds
+        bank.release_all_holds().await?;
+
+        // Standing and scheduled transfers
+        scheduler::run_transfer_orders(bank.clone());
+        // Merkle tree over the transactions history
+        bank.run_ledger();
+
         let app_state = AppState {
-            bank,
+            bank: bank.clone(),
             settings: Arc::new(config.clone()),
             sessions: InteractionSessions::new(),
             ws_appender,
@@ -70,8 +83,8 @@ impl Application {
             .allow_origin(Any);
 
         let app = pages_and_triggers_router()
-            .nest("/token", token_router())
-            .nest("/session", session_router())
+            .nest("/token", token_router(app_state.clone()))
+            .nest("/session", session_router(app_state.clone()))
             .nest("/system", system_router(app_state.clone()))
             .route("/healthcheck", routing::get(|| async { StatusCode::OK }))
             .with_state(app_state)
@@ -83,6 +96,7 @@ impl Application {
         Ok(Self {
             _port: port,
             server,
+            bank,
         })
     }
 
@@ -90,6 +104,9 @@ impl Application {
         self.server
             .with_graceful_shutdown(shutdown_signal())
             .await?;
+        if let Err(e) = self.bank.save_snapshot().await {
+            tracing::error!("Failed to save bank data on shutdown: {e}");
+        }
         Ok(())
     }
 }
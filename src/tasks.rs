@@ -1,14 +1,18 @@
+use std::future::Future;
+
 use time::OffsetDateTime;
 use tokio::sync::oneshot::Receiver;
 use uuid::Uuid;
 
 use crate::RemovableById;
 
+/// `on_timeout` finishes the object, which is still active after an hour
 pub fn wait_hour_and_remove(
     mut object: impl RemovableById + Send + 'static,
-    notifier: Receiver<()>,
+    mut notifier: Receiver<()>,
     id: Uuid,
     created_at: OffsetDateTime,
+    on_timeout: impl Future<Output = ()> + Send + 'static,
 ) {
     tokio::spawn(async move {
         let interval =
@@ -24,8 +28,9 @@ pub fn wait_hour_and_remove(
         tokio::select! {
             _ = tokio::time::sleep(duration) => {
                 tracing::info!("Task on watching {id} time is out, removing!");
+                on_timeout.await;
             }
-            _ = notifier => {
+            _ = &mut notifier => {
                 tracing::info!("Task on watching {id} entity got removing request!");
             }
         }